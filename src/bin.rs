@@ -1,41 +1,679 @@
 // @<bin
-use anyhow::Result;
-use pdf_explorer::file_parse_and_back;
-use std::io::{self, Read};
-
-/// This is a simple binary wrapper around `file_parse_and_back`:
-/// - Reads a PDF file from stdin,
-/// - Calls `file_parse_and_back` on it,
-/// - (Saves to a file and) compares the bytes of the input and output PDF files.
-pub fn main() -> Result<()> {
+use pdf_explorer::{
+    parse_pdf_with_options, pdf_from_json, BinSerialize, DictionaryObject, Document,
+    IndirectObjectDefinition, Object, ObjectOrReference, Page, ParseOptions, PdfFile, XrefEntry,
+};
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: pdf_explore <command> <file> [arguments] [options]
+(<file> can be `-`, for stdin.)
+
+Commands:
+  roundtrip [--out <path>]      Parse the file and check that writing it back gives the same bytes
+  json                          Print the parsed file as JSON
+  info                          Print a summary of the file
+  objects                       List the objects (per the cross-reference table), with their types
+  show <objnum>                 Print the definition of an object
+  stream <objnum> [--decode]    Write a stream's data (raw, or with its filters undone) to stdout
+  xref                          List the cross-reference entries, and check them against the file
+  from-json [--out <path>]      Write out the PDF file that <file> (JSON, as from `json`) describes
+  text [<page>]                 Print the text of each page, with a form feed after each (or of
+                                page <page>, counting from 1)
+  words [<page>] [--glyphs]     Print the words (or glyphs) of each page (or of page <page>) as
+                                JSON, with their fonts, sizes and bounding boxes
+
+Options:
+  --lenient                     Accept some common departures from the spec (with warnings)
+  --password <password>         The password for an encrypted file
+
+Exit status:
+  0  success
+  1  the check (`roundtrip` or `xref`) found a problem
+  2  bad usage
+  3  the file could not be read, parsed (or, for `from-json`, turned into a PDF file) or written out
+  4  the object or page is missing, is not a stream, or its data could not be decoded";
+
+/// Why `pdf_explore` failed, which decides its exit status.
+enum Failure {
+    /// A check found a problem.
+    Check(String),
+    /// The command line was wrong.
+    Usage(String),
+    /// The file could not be read or parsed, or the output could not be written.
+    Input(String),
+    /// The requested object is not there, or not suitable.
+    Object(String),
+}
+
+impl Failure {
+    fn exit_code(&self) -> u8 {
+        match self {
+            Failure::Check(_) => 1,
+            Failure::Usage(_) => 2,
+            Failure::Input(_) => 3,
+            Failure::Object(_) => 4,
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            Failure::Check(m) | Failure::Usage(m) | Failure::Input(m) | Failure::Object(m) => m,
+        }
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(e: io::Error) -> Self {
+        Failure::Input(e.to_string())
+    }
+}
+
+/// The parsed command line.
+struct Args {
+    command: String,
+    file: String,
+    objnum: Option<u32>,
+    page: Option<usize>,
+    out: Option<String>,
+    decode: bool,
+    glyphs: bool,
+    lenient: bool,
+    password: Vec<u8>,
+}
+
+impl Args {
+    // Whether `-h` or `--help` is given as an option (and not as the value of one, as in
+    // `--password -h`).
+    fn wants_help(mut args: impl Iterator<Item = String>) -> bool {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return true,
+                "--out" | "--password" => {
+                    args.next();
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, Failure> {
+        let usage = |message: &str| Failure::Usage(format!("{}\n\n{}", message, USAGE));
+        let mut positional = vec![];
+        let (mut out, mut decode, mut glyphs, mut lenient, mut password) =
+            (None, false, false, false, vec![]);
+        while let Some(arg) = args.next() {
+            let mut value = |option: &str| {
+                args.next()
+                    .ok_or_else(|| usage(&format!("{} needs a value", option)))
+            };
+            match arg.as_str() {
+                "--out" => out = Some(value("--out")?),
+                "--password" => password = value("--password")?.into_bytes(),
+                "--decode" => decode = true,
+                "--glyphs" => glyphs = true,
+                "--lenient" => lenient = true,
+                option if option.starts_with("--") => {
+                    return Err(usage(&format!("unknown option {}", option)))
+                }
+                _ => positional.push(arg),
+            }
+        }
+        let mut positional = positional.into_iter();
+        let (Some(command), Some(file)) = (positional.next(), positional.next()) else {
+            return Err(usage("a command and a file are needed"));
+        };
+        let mut page = None;
+        let objnum = match command.as_str() {
+            "roundtrip" | "json" | "info" | "objects" | "xref" | "from-json" => None,
+            "text" | "words" => {
+                if let Some(number) = positional.next() {
+                    page = Some(
+                        number
+                            .parse()
+                            .ok()
+                            .filter(|&n| n > 0)
+                            .ok_or_else(|| usage(&format!("bad page number {}", number)))?,
+                    );
+                }
+                None
+            }
+            "show" | "stream" => {
+                let objnum = positional
+                    .next()
+                    .ok_or_else(|| usage(&format!("{} needs an object number", command)))?;
+                Some(
+                    objnum
+                        .parse()
+                        .map_err(|_| usage(&format!("bad object number {}", objnum)))?,
+                )
+            }
+            _ => return Err(usage(&format!("unknown command {}", command))),
+        };
+        if let Some(extra) = positional.next() {
+            return Err(usage(&format!("unexpected argument {}", extra)));
+        }
+        if out.is_some() && command != "roundtrip" && command != "from-json" {
+            return Err(usage("--out is only for `roundtrip` and `from-json`"));
+        }
+        if decode && command != "stream" {
+            return Err(usage("--decode is only for `stream`"));
+        }
+        if glyphs && command != "words" {
+            return Err(usage("--glyphs is only for `words`"));
+        }
+        Ok(Args {
+            command,
+            file,
+            objnum,
+            page,
+            out,
+            decode,
+            glyphs,
+            lenient,
+            password,
+        })
+    }
+
+    fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            strict: !self.lenient,
+            ..ParseOptions::default()
+        }
+    }
+}
+
+pub fn main() -> ExitCode {
+    if Args::wants_help(std::env::args().skip(1)) {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    match Args::parse(std::env::args().skip(1)).and_then(|args| run(&args)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            eprintln!("{}", failure.message());
+            ExitCode::from(failure.exit_code())
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<(), Failure> {
     let mut data: Vec<u8> = vec![];
-    io::stdin().read_to_end(&mut data)?;
-    let out: Vec<u8> = file_parse_and_back(&data);
-    println!("Called file_parse_and_back");
-    std::fs::write("parsed-file-written-back.pdf", out.clone())?;
-    if out != data {
-        println!(
-            "Unequal serializations: length {} vs length {}",
-            out.len(),
-            data.len()
-        );
-        /*
-        let i = out
+    if args.file == "-" {
+        io::stdin().read_to_end(&mut data)?;
+    } else {
+        data = std::fs::read(&args.file)
+            .map_err(|e| Failure::Input(format!("can't read {}: {}", args.file, e)))?;
+    }
+    command(args, &data, &mut io::stdout().lock())
+}
+
+// Runs the command on the file's contents, `data`.
+fn command(args: &Args, data: &[u8], out: &mut impl Write) -> Result<(), Failure> {
+    match args.command.as_str() {
+        "roundtrip" => roundtrip(args, data, out),
+        "json" => {
+            let parsed = parse(args, data)?;
+            serde_json::to_writer(&mut *out, &parsed).map_err(|e| Failure::Input(e.to_string()))?;
+            writeln!(out)?;
+            Ok(())
+        }
+        "info" => info(args, data, out),
+        "objects" => objects(args, data, out),
+        "show" => show(args, data, out),
+        "stream" => stream(args, data, out),
+        "xref" => xref(args, data, out),
+        "from-json" => from_json(args, data, out),
+        "text" => text(args, data, out),
+        "words" => words(args, data, out),
+        _ => unreachable!("checked in Args::parse"),
+    }
+}
+
+// Parses the whole file, printing any warnings to stderr.
+fn parse<'a>(args: &Args, data: &'a [u8]) -> Result<PdfFile<'a>, Failure> {
+    let parsed = parse_pdf_with_options(data, &args.parse_options())
+        .map_err(|e| Failure::Input(format!("can't parse {}: {}", args.file, e)))?;
+    for warning in parsed.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+    Ok(parsed)
+}
+
+fn open<'a>(args: &Args, data: &'a [u8]) -> Result<Document<'a>, Failure> {
+    Document::open_with_password(data, &args.password)
+        .map_err(|e| Failure::Input(format!("can't open {}: {}", args.file, e)))
+}
+
+fn serialize(thing: &impl BinSerialize) -> Vec<u8> {
+    let mut buf = vec![];
+    // (Writing to a Vec doesn't fail, but writing out e.g. a PdfFile without sections does.)
+    thing.serialize_to(&mut buf).unwrap_or_default();
+    buf
+}
+
+fn roundtrip(args: &Args, data: &[u8], out: &mut impl Write) -> Result<(), Failure> {
+    let parsed = parse(args, data)?;
+    let written = serialize(&parsed);
+    if let Some(path) = &args.out {
+        std::fs::write(path, &written)
+            .map_err(|e| Failure::Input(format!("can't write {}: {}", path, e)))?;
+    }
+    check_same(&written, data)?;
+    writeln!(out, "Success! ({} bytes)", data.len())?;
+    Ok(())
+}
+
+// Checks that writing the file back out gave the bytes it was read from.
+fn check_same(written: &[u8], data: &[u8]) -> Result<(), Failure> {
+    if written == data {
+        return Ok(());
+    }
+    let same = written.iter().zip(data).take_while(|(w, d)| w == d).count();
+    Err(Failure::Check(format!(
+        "Unequal serializations: length {} vs length {}, and only the first {} bytes are equal.",
+        written.len(),
+        data.len(),
+        same
+    )))
+}
+
+fn info(args: &Args, data: &[u8], out: &mut impl Write) -> Result<(), Failure> {
+    // The version is on the `%PDF-` line, which need not be the first.
+    if let Some(i) = data.windows(5).take(1024).position(|w| w == b"%PDF-") {
+        let version = data[i + 5..]
             .iter()
-            .zip(data.iter())
-            .take_while(|&(o, d)| o == d)
-            .count();
-        */
-        let mut i = 0;
-        while i < out.len() && i < data.len() && out[i] == data[i] {
-            i += 1;
+            .take_while(|c| !c.is_ascii_whitespace())
+            .map(|&c| c as char)
+            .collect::<String>();
+        writeln!(out, "Version: {}", version)?;
+    }
+    writeln!(out, "Size: {} bytes", data.len())?;
+    // Parsing the file from start to end and reading it as a document (from its cross-reference
+    // table) can each fail where the other doesn't: say what either one can.
+    let parsed = parse(args, data);
+    match &parsed {
+        Ok(parsed) => {
+            let sections = &parsed.body_crossref_trailers;
+            let definitions: usize = sections.iter().map(|s| s.definitions().count()).sum();
+            writeln!(out, "Sections: {}", sections.len())?;
+            writeln!(out, "Object definitions: {}", definitions)?;
+            if args.lenient {
+                writeln!(out, "Warnings: {}", parsed.warnings().len())?;
+            }
         }
-        println!("The first {} bytes are equal.", i);
-        let start = std::cmp::max(0, i - 10);
-        assert_eq!(&out[start..], &data[start..]);
+        Err(failure) => writeln!(out, "Parse: {}", failure.message())?,
     }
-    assert_eq!(out, data);
-    println!("Success!");
+    let document = match (open(args, data), parsed) {
+        (Ok(document), _) => document,
+        (Err(failure), Ok(_)) => {
+            writeln!(out, "Document: {}", failure.message())?;
+            return Ok(());
+        }
+        (Err(_), Err(failure)) => return Err(failure),
+    };
+    writeln!(out, "Objects: {}", document.objects().count())?;
+    writeln!(
+        out,
+        "Objects in object streams: {}",
+        document.compressed_objects().count()
+    )?;
+    if !document.object_errors().is_empty() {
+        writeln!(
+            out,
+            "Objects that can't be read: {}",
+            document.object_errors().len()
+        )?;
+    }
+    let page_tree = document.page_tree();
+    writeln!(out, "Pages: {}", page_tree.pages.len())?;
+    for problem in &page_tree.problems {
+        writeln!(out, "Page tree problem: {}", problem)?;
+    }
+    writeln!(out, "Encrypted: {}", document.is_encrypted())?;
+    if let Some(e) = document.encryption_error() {
+        writeln!(out, "Encryption error: {}", e)?;
+    }
+    if let Some(title) = title(&document) {
+        writeln!(out, "Title: {}", title)?;
+    }
+    Ok(())
+}
+
+// The /Title in the document information dictionary.
+fn title(document: &Document) -> Option<String> {
+    let Some(ObjectOrReference::Reference(reference)) = document.trailer().get(b"Info") else {
+        return None;
+    };
+    let Some(Object::Dictionary(info)) = document.resolve(reference) else {
+        return None;
+    };
+    let Some(ObjectOrReference::Object(Object::String(title))) = info.get(b"Title") else {
+        return None;
+    };
+    let (number, generation) = reference.id()?;
+    document.text_string(number, generation, title).ok()
+}
+
+fn objects(args: &Args, data: &[u8], out: &mut impl Write) -> Result<(), Failure> {
+    let document = open(args, data)?;
+    for (number, def) in document.objects() {
+        let (_, generation) = def.id().unwrap_or((number, 0));
+        writeln!(out, "{} {}: {}", number, generation, describe(def.object()))?;
+    }
+    for (number, compressed) in document.compressed_objects() {
+        writeln!(
+            out,
+            "{} 0: {} (in object stream {})",
+            number,
+            describe(&compressed.object),
+            compressed.container
+        )?;
+    }
+    Ok(())
+}
+
+// The kind of object, with its /Type (and, for a stream, the length of its data).
+fn describe(object: &Object) -> String {
+    let type_of = |dict: &DictionaryObject| match dict.get(b"Type") {
+        Some(ObjectOrReference::Object(Object::Name(name))) => {
+            format!(" /{}", String::from_utf8_lossy(&name.decoded_bytes()))
+        }
+        _ => String::new(),
+    };
+    match object {
+        Object::Boolean(..) => "boolean".to_string(),
+        Object::Numeric(_) => "number".to_string(),
+        Object::String(_) => "string".to_string(),
+        Object::Name(_) => "name".to_string(),
+        Object::Array(_) => "array".to_string(),
+        Object::Dictionary(dict) => format!("dictionary{}", type_of(dict)),
+        Object::Stream(stream) => format!(
+            "stream{}, {} bytes",
+            type_of(stream.dict()),
+            stream.data().len()
+        ),
+        Object::Null(_) => "null".to_string(),
+    }
+}
+
+// The in-use definition of object `number` (not one in an object stream).
+fn definition<'d, 'a>(
+    document: &'d Document<'a>,
+    number: u32,
+) -> Result<&'d IndirectObjectDefinition<'a>, Failure> {
+    document.object(number).ok_or_else(|| {
+        let in_object_stream = document.compressed_objects().any(|(n, _)| n == number);
+        Failure::Object(if in_object_stream {
+            format!(
+                "object {} is in an object stream, so it is not a stream",
+                number
+            )
+        } else {
+            format!("object {} is not in use", number)
+        })
+    })
+}
+
+fn show(args: &Args, data: &[u8], out: &mut impl Write) -> Result<(), Failure> {
+    let document = open(args, data)?;
+    let number = args.objnum.expect("checked in Args::parse");
+    let bytes = match document.object(number) {
+        Some(def) => serialize(def),
+        None => match document.compressed_objects().find(|&(n, _)| n == number) {
+            Some((_, compressed)) => serialize(&compressed.object),
+            None => return Err(Failure::Object(format!("object {} is not in use", number))),
+        },
+    };
+    out.write_all(&bytes)?;
+    writeln!(out)?;
     Ok(())
 }
+
+fn stream(args: &Args, data: &[u8], out: &mut impl Write) -> Result<(), Failure> {
+    let document = open(args, data)?;
+    let number = args.objnum.expect("checked in Args::parse");
+    let def = definition(&document, number)?;
+    let Object::Stream(stream) = def.object() else {
+        return Err(Failure::Object(format!(
+            "object {} is not a stream: it is a {}",
+            number,
+            describe(def.object())
+        )));
+    };
+    if args.decode {
+        let (_, generation) = def.id().unwrap_or((number, 0));
+        let decoded = document
+            .stream_data(number, generation, stream)
+            .map_err(|e| Failure::Object(format!("can't decode object {}: {}", number, e)))?;
+        out.write_all(&decoded)?;
+    } else {
+        out.write_all(stream.data())?;
+    }
+    Ok(())
+}
+
+fn xref(args: &Args, data: &[u8], out: &mut impl Write) -> Result<(), Failure> {
+    let document = open(args, data)?;
+    for (number, entry) in document.xref() {
+        match entry {
+            XrefEntry::Free {
+                next_free_object_number,
+                generation,
+            } => writeln!(
+                out,
+                "{} {}: free, next {}",
+                number, generation, next_free_object_number
+            )?,
+            XrefEntry::InUse { offset, generation } => {
+                writeln!(out, "{} {}: at {}", number, generation, offset)?
+            }
+            XrefEntry::Compressed {
+                object_stream_number,
+                index,
+            } => writeln!(
+                out,
+                "{} 0: in object stream {}, at index {}",
+                number, object_stream_number, index
+            )?,
+        }
+    }
+    // Checking the entries needs the file parsed from start to end.
+    let problems = match parse(args, data) {
+        Ok(parsed) => parsed.validate_xref(),
+        Err(failure) => {
+            writeln!(out, "Problem: {}", failure.message())?;
+            return Err(Failure::Check(
+                "the cross-reference sections can't be checked".into(),
+            ));
+        }
+    };
+    if problems.is_empty() {
+        return Ok(());
+    }
+    for problem in &problems {
+        writeln!(out, "Problem: {}", problem)?;
+    }
+    Err(Failure::Check(format!(
+        "{} problem(s) with the cross-reference sections",
+        problems.len()
+    )))
+}
+
+fn from_json(args: &Args, data: &[u8], out: &mut impl Write) -> Result<(), Failure> {
+    let json = std::str::from_utf8(data)
+        .map_err(|e| Failure::Input(format!("{} is not UTF-8: {}", args.file, e)))?;
+    let bytes = pdf_from_json(json).map_err(|e| Failure::Input(e.to_string()))?;
+    match &args.out {
+        Some(path) => std::fs::write(path, &bytes)
+            .map_err(|e| Failure::Input(format!("can't write {}: {}", path, e)))?,
+        None => out.write_all(&bytes)?,
+    }
+    Ok(())
+}
+
+// The pages that `args.page` asks for (counting from 1), with their page numbers: all of them, if
+// it's not given.
+fn selected_pages<'d, 'a>(
+    args: &Args,
+    document: &'d Document<'a>,
+) -> Result<Vec<(usize, Page<'d, 'a>)>, Failure> {
+    let mut pages: Vec<_> = document
+        .pages()
+        .enumerate()
+        .map(|(i, page)| (i + 1, page))
+        .collect();
+    let Some(number) = args.page else {
+        return Ok(pages);
+    };
+    if number > pages.len() {
+        return Err(Failure::Object(format!(
+            "there is no page {} (there are {} pages)",
+            number,
+            pages.len()
+        )));
+    }
+    Ok(vec![pages.swap_remove(number - 1)])
+}
+
+fn text(args: &Args, data: &[u8], out: &mut impl Write) -> Result<(), Failure> {
+    let document = open(args, data)?;
+    for (_, page) in selected_pages(args, &document)? {
+        let text = page
+            .extract_text()
+            .map_err(|e| Failure::Object(format!("page object {}: {}", page.number, e)))?;
+        writeln!(out, "{}", text)?;
+        if args.page.is_none() {
+            write!(out, "\x0c")?;
+        }
+    }
+    Ok(())
+}
+
+fn words(args: &Args, data: &[u8], out: &mut impl Write) -> Result<(), Failure> {
+    let document = open(args, data)?;
+    let mut pages = vec![];
+    for (number, page) in selected_pages(args, &document)? {
+        let (key, positioned) = if args.glyphs {
+            ("glyphs", page.positioned_glyphs())
+        } else {
+            ("words", page.positioned_words())
+        };
+        let positioned = positioned
+            .map_err(|e| Failure::Object(format!("page object {}: {}", page.number, e)))?;
+        pages.push(serde_json::json!({
+            "page": number,
+            "object": page.number,
+            "media_box": page.media_box,
+            key: positioned,
+        }));
+    }
+    serde_json::to_writer(&mut *out, &pages).map_err(|e| Failure::Input(e.to_string()))?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+fn args(command_line: &str) -> Result<Args, Failure> {
+    Args::parse(command_line.split_whitespace().map(String::from))
+}
+
+// The exit status and output of `command_line`, run on `data`.
+#[cfg(test)]
+fn run_on(command_line: &str, data: &[u8]) -> (u8, String) {
+    let mut out = vec![];
+    let status = match args(command_line).and_then(|args| command(&args, data, &mut out)) {
+        Ok(()) => 0,
+        Err(failure) => failure.exit_code(),
+    };
+    (status, String::from_utf8_lossy(&out).into_owned())
+}
+
+#[test]
+fn test_args() {
+    let parsed = args("stream file.pdf 4 --decode --password secret")
+        .ok()
+        .unwrap();
+    assert_eq!(
+        (parsed.command.as_str(), parsed.file.as_str()),
+        ("stream", "file.pdf")
+    );
+    assert_eq!(parsed.objnum, Some(4));
+    assert!(parsed.decode && !parsed.lenient);
+    assert_eq!(parsed.password, b"secret");
+    let parsed = args("words - 2 --glyphs --lenient").ok().unwrap();
+    assert_eq!((parsed.page, parsed.glyphs), (Some(2), true));
+    assert!(!parsed.parse_options().strict);
+    for bad in [
+        "",
+        "info",
+        "frobnicate file.pdf",
+        "show file.pdf",
+        "show file.pdf x",
+        "text file.pdf 0",
+        "info file.pdf extra",
+        "info file.pdf --decode",
+        "json file.pdf --out",
+        "json file.pdf --out copy.pdf",
+        "info file.pdf --verbose",
+    ] {
+        assert_eq!(args(bad).err().map(|f| f.exit_code()), Some(2), "{}", bad);
+    }
+    let wants_help =
+        |command_line: &str| Args::wants_help(command_line.split_whitespace().map(String::from));
+    assert!(wants_help("-h"));
+    assert!(wants_help("info file.pdf --help"));
+    assert!(wants_help("info --password -h file.pdf -h"));
+    assert!(!wants_help("info file.pdf --password -h"));
+    assert!(!wants_help("roundtrip file.pdf --out --help"));
+}
+
+#[test]
+fn test_exit_codes() {
+    let simple = include_bytes!("test_simple.pdf");
+    assert_eq!(
+        run_on("roundtrip f", simple),
+        (0, "Success! (683 bytes)\n".into())
+    );
+    assert_eq!(
+        check_same(b"abcd", b"abxy").err().map(|f| f.exit_code()),
+        Some(1)
+    );
+    assert_eq!(run_on("show f 99", simple).0, 4);
+    assert_eq!(run_on("stream f 1", simple).0, 4);
+    assert_eq!(run_on("text f 2", simple).0, 4);
+    assert_eq!(run_on("info f", b"not a PDF file").0, 3);
+    assert_eq!(run_on("from-json f", b"{}").0, 3);
+}
+
+#[test]
+fn test_info_and_xref_without_parse() {
+    // Without the font's `endobj`, the file doesn't parse, but can still be read as a document.
+    let simple = include_bytes!("test_simple.pdf");
+    let font = simple.windows(7).position(|w| w == b"5 0 obj").unwrap();
+    let i = font
+        + simple[font..]
+            .windows(6)
+            .position(|w| w == b"endobj")
+            .unwrap();
+    let damaged = [&simple[..i], b"      ", &simple[i + 6..]].concat();
+    let (status, out) = run_on("info f", &damaged);
+    assert_eq!(status, 0);
+    assert!(out.contains("Parse: can't parse f:"), "{}", out);
+    assert!(out.contains("Pages: 1\n"), "{}", out);
+    assert!(out.contains("Title: Hello\n"), "{}", out);
+    let (status, out) = run_on("xref f", &damaged);
+    assert_eq!(status, 1);
+    assert!(
+        out.starts_with("0 65535: free, next 0\n1 0: at 15\n"),
+        "{}",
+        out
+    );
+    assert!(out.contains("Problem: can't parse f:"), "{}", out);
+    assert_eq!(run_on("xref f", simple).0, 0);
+    // Without a cross-reference table to read, it's bad input.
+    assert_eq!(run_on("xref f", b"%PDF-1.4\n").0, 3);
+}
 // >@bin
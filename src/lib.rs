@@ -1,40 +1,45 @@
 // @<wasm
 use js_sys::Uint8Array;
-use pdf_file_parse::BinSerialize;
 use wasm_bindgen::prelude::*;
 use web_sys::{console, File, FileReaderSync};
 
+pub use pdf_file_parse::{
+    decode_text_string, parse_cmap, parse_content_stream, parse_pdf, parse_pdf_with_options,
+    repair, BinSerialize, CMap, CompressedObject, ContentStream, ContentStreamPart,
+    CrossReferenceStream, CrossReferenceStreamSubsection, DecodeError, Diagnostics,
+    DictionaryObject, Document, DuplicateKey, EncryptionError, HexadecimalString,
+    IndirectObjectDefinition, IndirectObjectReference, InlineImage, Integer, Level, NameObject,
+    NumericObject, Object, ObjectOrReference, ObjectStream, Operation, Page, PageTree,
+    PageTreeProblem, ParseError, ParseOptions, ParseWarning, PdfFile, PositionedText, Real, Span,
+    StderrDiagnostics, StderrTracer, StreamLength, StreamObject, StringObject, TextError, Tracer,
+    XrefEntry, XrefProblem,
+};
+
 /// The function that is called from JS.
 /// Reads `file`, parses it, logs some stuff, and returns the parsed structure.
+/// If the file cannot be read or parsed, the returned error (thrown on the JS side) says why.
 #[wasm_bindgen]
-pub fn handle_file(file: File) -> JsValue {
-    console::log_1(&format!("in Rust handle_file").into());
+pub fn handle_file(file: File) -> Result<JsValue, JsValue> {
+    console::log_1(&"in Rust handle_file".into());
     // Read `file` into a Vec<u8> v
     let v: Vec<u8> = {
-        let filereader = FileReaderSync::new().unwrap();
+        let filereader = FileReaderSync::new()?;
         // Warning: This read_as_array_buffer can't be changed to readAsBinaryString.
-        let buffer = filereader.read_as_array_buffer(&file).unwrap();
+        let buffer = filereader.read_as_array_buffer(&file)?;
         let view = Uint8Array::new(&buffer); // This is instant.
         console::log_1(&format!("read {} bytes to ArrayBuffer", view.byte_length()).into());
-        let v = view.to_vec();
-        v
+        view.to_vec()
     };
-    console::log_1(&format!("copied into Vec<u8>, computing crc32").into());
+    console::log_1(&"copied into Vec<u8>, computing crc32".into());
 
-    let parsed = match pdf_file_parse::pdf_file(&v) {
-        Ok((remaining, parsed)) => {
-            println!("Parsed file with #{}# bytes left", remaining.len());
-            parsed
-        }
-        Err(e) => {
-            panic!("Failed to parse input as PDF. Got error: {:?}", e);
-        }
-    };
+    let parsed = parse_pdf(&v).map_err(|e| JsValue::from_str(&e.to_string()))?;
 
     // Check round-tripping
     {
         let mut out: Vec<u8> = vec![];
-        parsed.serialize_to(&mut out).unwrap();
+        parsed
+            .serialize_to(&mut out)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
         console::log_1(
             &format!(
                 "written-out PdfFile has len {} and crc32 {} (vs {})",
@@ -60,34 +65,120 @@ pub fn handle_file(file: File) -> JsValue {
         console::log_1(&format!("Parsed PdfFile has {} obj defs.", count).into());
     }
 
-    JsValue::from_serde(&parsed).unwrap()
+    // (This is what the deprecated `JsValue::from_serde` did.)
+    let json = serde_json::to_string(&parsed).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    js_sys::JSON::parse(&json)
 }
 // >@wasm
 
 // @<file_parse_and_back
 // TODO: Put this in bin.rs?
 /// Parses `input` as a PDF file, and returns its serialization (hopefully identical to input).
-/// Panics if parsing fails.
-pub fn file_parse_and_back(input: &[u8]) -> Vec<u8> {
-    let parsed = match pdf_file_parse::pdf_file(input) {
-        Ok((remaining, parsed)) => {
-            println!("Parsed file with #{}# bytes left", remaining.len());
-            parsed
-        }
-        Err(e) => {
-            panic!("Failed to parse input as PDF. Got error: {:?}", e);
-        }
-    };
+/// Returns the error if parsing fails.
+pub fn file_parse_and_back(input: &[u8]) -> Result<Vec<u8>, ParseError> {
+    let parsed = parse_pdf(input)?;
     let mut buf: Vec<u8> = vec![];
-    parsed.serialize_to(&mut buf).unwrap();
-    buf
+    parsed
+        .serialize_to(&mut buf)
+        .expect("Writing a just-parsed file to memory should not fail");
+    Ok(buf)
 }
 // >@file_parse_and_back
 
+// @<pdf_from_json
+/// Why `pdf_from_json` failed.
+#[derive(Debug)]
+pub enum FromJsonError {
+    /// The JSON is not that of a `PdfFile` (or it breaks an invariant, like balanced parentheses
+    /// in a literal string).
+    Json(serde_json::Error),
+    /// The `PdfFile` cannot be written out (e.g. it has no sections).
+    Write(std::io::Error),
+    /// What it was written out as does not parse (even leniently), e.g. because some whitespace
+    /// was edited into something else.
+    Parse(ParseError),
+}
+
+impl std::fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FromJsonError::Json(e) => write!(f, "invalid JSON for a PDF file: {}", e),
+            FromJsonError::Write(e) => write!(f, "can't write out the PDF file: {}", e),
+            FromJsonError::Parse(e) => write!(f, "the PDF file written out does not parse: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FromJsonError {}
+
+/// The bytes of the PDF file that `json` (a serialized `PdfFile`, as from `serde_json`, perhaps
+/// edited) describes. Inverse to serializing a parsed file to JSON: the bytes are the original ones.
+/// The bytes are checked by parsing them again (leniently, as the file may have been parsed so).
+pub fn pdf_from_json(json: &str) -> Result<Vec<u8>, FromJsonError> {
+    let parsed: PdfFile = serde_json::from_str(json).map_err(FromJsonError::Json)?;
+    let mut buf: Vec<u8> = vec![];
+    parsed
+        .serialize_to(&mut buf)
+        .map_err(FromJsonError::Write)?;
+    let options = ParseOptions {
+        strict: false,
+        ..ParseOptions::default()
+    };
+    parse_pdf_with_options(&buf, &options).map_err(FromJsonError::Parse)?;
+    Ok(buf)
+}
+
+#[test]
+fn test_pdf_from_json() {
+    let bytes = include_bytes!("test_simple.pdf");
+    let json = serde_json::to_string(&parse_pdf(bytes).unwrap()).unwrap();
+    assert_eq!(pdf_from_json(&json).unwrap(), bytes);
+    // The /Author, "(PDF Explorer)", edited to "(PDF) Explorer)".
+    let bytes_json = |s: &[u8]| serde_json::to_string(s).unwrap();
+    let edited = json.replacen(
+        &bytes_json(b"PDF Explorer"),
+        &bytes_json(b"PDF) Explorer"),
+        1,
+    );
+    assert_ne!(edited, json);
+    assert!(matches!(
+        pdf_from_json(&edited),
+        Err(FromJsonError::Json(_))
+    ));
+    // A stream's length edited to more than its content.
+    let edited = json.replacen(
+        r#""length":{"Direct":43}"#,
+        r#""length":{"Direct":4300}"#,
+        1,
+    );
+    assert_ne!(edited, json);
+    assert!(matches!(
+        pdf_from_json(&edited),
+        Err(FromJsonError::Json(_))
+    ));
+    // A Regular part edited into an Escaped part that is not an escape.
+    let start = json.find(r#"{"Regular":["#).unwrap();
+    let end = start + json[start..].find("]}").unwrap() + 2;
+    let edited = format!(
+        "{}{}{}",
+        &json[..start],
+        r#"{"Escaped":[32]}"#,
+        &json[end..]
+    );
+    assert!(matches!(
+        pdf_from_json(&edited),
+        Err(FromJsonError::Json(_))
+    ));
+    assert!(matches!(
+        pdf_from_json(r#"{"header":[],"body_crossref_trailers":[],"post_eof":[]}"#),
+        Err(FromJsonError::Write(_))
+    ));
+}
+// >@pdf_from_json
+
 // @<mod_header
 mod pdf_file_parse {
     use adorn::adorn;
-    use lazy_static::lazy_static;
     use nom::{
         branch::alt,
         bytes::complete::{tag, take, take_until, take_while, take_while1, take_while_m_n},
@@ -95,111 +186,217 @@ mod pdf_file_parse {
             complete::{digit0, digit1, one_of},
             is_digit, is_oct_digit,
         },
-        combinator::{map, opt, recognize, verify},
+        combinator::{cut, map, opt, recognize, verify},
+        error::ErrorKind,
         multi::{many0, many1},
         sequence::{delimited, tuple},
-        IResult, Parser,
+        Parser,
     };
-    use parking_lot::Mutex;
     use serde::{Deserialize, Serialize};
     use std::{
         borrow::Cow,
+        cell::RefCell,
+        collections::{BTreeMap, HashSet},
         io::{self, Write},
-        ops::Add,
+        rc::Rc,
     };
     // >@mod_header
 
-    // @<tracing
-    lazy_static! {
-        // The current depth of the parse calls
-        static ref DEPTH: Mutex<i32> = Mutex::new(0);
-        // A vector of the number of ops at each depth.
-        // E.g.
-        //  -> object [1]
-        //   -> real  [1, 1]
-        //   <- real (no) [2]
-        //   -> int   [2, 1]
-        //   <- int (ok) [3]
-        static ref COST: Mutex<Vec<i64>> = Mutex::new(vec![0]);
-    }
-
-    #[allow(dead_code)]
-    fn traceable_parser_full<'a, T, F>(
-        f: F,
-        fn_name: &'static str,
-        input: &'a [u8],
-    ) -> IResult<&'a [u8], T>
-    where
-        F: Fn(&'a [u8]) -> IResult<&'a [u8], T>,
-    {
-        const MAX_LEN: usize = 35;
-        assert!(fn_name.len() < MAX_LEN, "{}", fn_name);
-        *DEPTH.lock() += 1;
-        eprint!(
-            "{} -> {:MAX_LEN$}",
-            " ".repeat(std::cmp::max(0i32, DEPTH.lock().add(0)) as usize),
-            fn_name
-        );
-        let padding = std::cmp::max(0, 30_i32 - DEPTH.lock().add(0));
-        eprint!("{}", " ".repeat(padding.try_into().unwrap()));
-        eprint!("                    ");
-        let prefix = &input[..std::cmp::min(input.len(), 70)];
-        let prefix_for_debug = &input[..std::cmp::min(input.len(), 31)];
-        match std::str::from_utf8(prefix) {
-            Ok(s) => eprintln!("    {:?}", s),
-            Err(_) => eprintln!("    {:?}", prefix_for_debug),
+    // @<context
+    // The state of a parse. Our parsers are plain functions of their input, so this is not passed
+    // to them: instead, `with_context` makes it the current thread's for the duration of the parse,
+    // then restores whatever was there before, even if the parse panics. So parsing something else
+    // in the middle (like the objects in an object stream, or anything a `Tracer` or `Diagnostics`
+    // does) is fine, as long as that starts with a context of its own: each public entry point
+    // (`parse_pdf_with_options`, `Document::open`, `parse_cmap`, ...) does. Parses on different
+    // threads each have their own.
+    // The limitation is that a context belongs to a thread, not to a parser: one parse can't be
+    // interleaved with another on the same thread (say, by taking turns parsing pieces of two
+    // inputs), as each would see the other's context.
+    #[derive(Default)]
+    struct ParseContext {
+        // The length of the whole input. Everything parsed is a suffix of it, so the offset of
+        // anything is this length minus its own (as in `ParseError::from_nom`).
+        input_len: Option<usize>,
+        // The values of the (integer) objects that an indirect /Length may refer to, by object
+        // number and generation number.
+        indirect_lengths: BTreeMap<(u32, u16), usize>,
+        // When parsing leniently, the warnings so far; `None` when parsing strictly.
+        warnings: Option<Vec<ParseWarning>>,
+        // The offset and problem of each of those warnings, to find repeats quickly.
+        warned: HashSet<(usize, String)>,
+        // Where to report each traced call (with the `trace` feature), and for each traced call in
+        // progress, how many traced calls it has made so far (counting itself).
+        #[cfg_attr(not(feature = "trace"), allow(dead_code))]
+        tracer: Option<Rc<RefCell<dyn Tracer>>>,
+        #[cfg_attr(not(feature = "trace"), allow(dead_code))]
+        calls: Vec<u64>,
+        // Where diagnostic messages go.
+        diagnostics: Option<Rc<RefCell<dyn Diagnostics>>>,
+    }
+
+    impl ParseContext {
+        fn new(options: &ParseOptions) -> ParseContext {
+            ParseContext {
+                warnings: if options.strict { None } else { Some(vec![]) },
+                tracer: options.tracer.clone(),
+                diagnostics: options.diagnostics.clone(),
+                ..ParseContext::default()
+            }
         }
-        COST.lock().push(1);
+    }
 
-        let ret = f(input);
+    thread_local! {
+        static CONTEXT: RefCell<ParseContext> = RefCell::new(ParseContext::default());
+    }
 
-        let mut costs = COST.lock();
-        eprint!(
-            "{} <- {:MAX_LEN$}",
-            " ".repeat(std::cmp::max(0i32, DEPTH.lock().add(0)) as usize),
-            fn_name
-        );
-        eprint!("{}", " ".repeat(padding.try_into().unwrap()));
-        eprint!(
-            "{} (after {:06} ops)",
-            match ret {
-                Ok(_) => "ok",
-                Err(_) => "no",
-            },
-            costs.last().unwrap()
-        );
-        match std::str::from_utf8(prefix) {
-            Ok(s) => eprint!("    {:?}", s),
-            Err(_) => eprint!("    {:?}", prefix_for_debug),
+    // Runs `f` with `context` as the current one, and returns the context (as `f` left it) too.
+    fn with_context<T>(context: ParseContext, f: impl FnOnce() -> T) -> (T, ParseContext) {
+        let restore = RestoreContext(Some(CONTEXT.with(|c| c.replace(context))));
+        let ret = f();
+        (ret, restore.finish())
+    }
+
+    // Runs `f` in a context of its own (strict, and without a tracer or diagnostics), with spans
+    // being offsets into `whole`.
+    fn with_own_context<T>(whole: &[u8], f: impl FnOnce() -> T) -> T {
+        let context = ParseContext {
+            input_len: Some(whole.len()),
+            ..ParseContext::default()
+        };
+        with_context(context, f).0
+    }
+
+    // Puts the context that was current back when dropped, should the parse panic.
+    struct RestoreContext(Option<ParseContext>);
+    impl RestoreContext {
+        // Puts it back, and returns the context that the parse left.
+        fn finish(mut self) -> ParseContext {
+            let previous = self.0.take().unwrap_or_default();
+            CONTEXT.with(|c| c.replace(previous))
         }
-        if let Ok((left, _)) = ret {
-            let prefix = &left[..std::cmp::min(left.len(), 30)];
-            let prefix_for_debug = &left[..std::cmp::min(left.len(), 10)];
+    }
+    impl Drop for RestoreContext {
+        fn drop(&mut self) {
+            if let Some(previous) = self.0.take() {
+                CONTEXT.with(|c| c.replace(previous));
+            }
+        }
+    }
+
+    #[test]
+    fn test_context_restored() {
+        let lenient_context = || {
+            ParseContext::new(&ParseOptions {
+                strict: false,
+                ..ParseOptions::default()
+            })
+        };
+        // A parse in the middle of another has a context of its own, and the other's is back after.
+        let (inner_and_outer, _) = with_context(lenient_context(), || {
+            (with_own_context(b"", lenient), lenient())
+        });
+        assert_eq!(inner_and_outer, (false, true));
+        // Even if the parse panics.
+        let panicked =
+            std::panic::catch_unwind(|| with_context(lenient_context(), || panic!("mid-parse")));
+        assert!(panicked.is_err());
+        assert!(!lenient());
+    }
+    // >@context
+
+    // @<tracing
+    /// Is told about each call of a (traced) grammar rule, e.g. "object" or "cross_reference_table",
+    /// when parsing with this in `ParseOptions::tracer`. Only with the `trace` feature.
+    pub trait Tracer {
+        /// `rule` is about to parse `input`; `depth` is 1 for the outermost call.
+        fn enter(&mut self, rule: &'static str, depth: usize, input: &[u8]);
+        /// `rule` has parsed `input`, leaving `rest` (or failed, if `None`), after making `calls`
+        /// traced calls (counting itself).
+        fn exit(
+            &mut self,
+            rule: &'static str,
+            depth: usize,
+            input: &[u8],
+            rest: Option<&[u8]>,
+            calls: u64,
+        );
+    }
+
+    /// A `Tracer` that prints each call to stderr, indented by depth, with the start of its input.
+    pub struct StderrTracer;
+
+    impl StderrTracer {
+        // The start of `input`, as a string if it is UTF-8.
+        fn prefix(input: &[u8], len: usize) -> String {
+            let prefix = &input[..std::cmp::min(input.len(), len)];
             match std::str::from_utf8(prefix) {
-                Ok(s) => eprint!("    {:?}", s),
-                Err(_) => eprint!("    {:?}", prefix_for_debug),
+                Ok(s) => format!("{:?}", s),
+                Err(_) => format!("{:?}", &prefix[..std::cmp::min(prefix.len(), len / 2)]),
             }
         }
-        eprintln!("");
-        let current = costs.pop().unwrap();
-        if let Some(_) = costs.last() {
-            let v = costs.pop().unwrap();
-            costs.push(v + current);
+    }
+
+    impl Tracer for StderrTracer {
+        fn enter(&mut self, rule: &'static str, depth: usize, input: &[u8]) {
+            eprintln!(
+                "{} -> {:35}{}    {}",
+                " ".repeat(depth),
+                rule,
+                " ".repeat(30_usize.saturating_sub(depth)),
+                Self::prefix(input, 70)
+            );
         }
-        *DEPTH.lock() -= 1;
 
-        ret
+        fn exit(
+            &mut self,
+            rule: &'static str,
+            depth: usize,
+            input: &[u8],
+            rest: Option<&[u8]>,
+            calls: u64,
+        ) {
+            eprintln!(
+                "{} <- {:35}{}{} (after {:06} ops)    {}    {}",
+                " ".repeat(depth),
+                rule,
+                " ".repeat(30_usize.saturating_sub(depth)),
+                if rest.is_some() { "ok" } else { "no" },
+                calls,
+                Self::prefix(input, 70),
+                rest.map(|rest| Self::prefix(rest, 30)).unwrap_or_default()
+            );
+        }
     }
-    #[allow(dead_code)]
-    fn traceable_parser_fast<'a, T, F>(
-        f: F,
-        _fn_name: &'static str,
+
+    // Runs `f`, telling the tracer (if any) about it.
+    #[cfg(feature = "trace")]
+    fn traced<'a, T>(
+        f: impl Fn(&'a [u8]) -> IResult<&'a [u8], T>,
+        fn_name: &'static str,
         input: &'a [u8],
-    ) -> IResult<&'a [u8], T>
-    where
-        F: Fn(&'a [u8]) -> IResult<&'a [u8], T>,
-    {
-        f(input)
+    ) -> IResult<&'a [u8], T> {
+        let Some(tracer) = CONTEXT.with(|c| c.borrow().tracer.clone()) else {
+            return f(input);
+        };
+        let depth = CONTEXT.with(|c| {
+            let calls = &mut c.borrow_mut().calls;
+            calls.push(1);
+            calls.len()
+        });
+        tracer.borrow_mut().enter(fn_name, depth, input);
+        let ret = f(input);
+        let calls = CONTEXT.with(|c| {
+            let calls = &mut c.borrow_mut().calls;
+            let current = calls.pop().unwrap_or(1);
+            if let Some(parent) = calls.last_mut() {
+                *parent += current;
+            }
+            current
+        });
+        let rest = ret.as_ref().ok().map(|(rest, _)| *rest);
+        tracer.borrow_mut().exit(fn_name, depth, input, rest, calls);
+        ret
     }
 
     fn traceable_parser<'a, T, F>(
@@ -210,17 +407,392 @@ mod pdf_file_parse {
     where
         F: Fn(&'a [u8]) -> IResult<&'a [u8], T>,
     {
-        #[cfg(debug_assertions)]
-        {
-            traceable_parser_full(f, fn_name, input)
+        // (Without the `trace` feature, this doesn't touch the context.)
+        #[cfg(feature = "trace")]
+        let ret = traced(f, fn_name, input);
+        #[cfg(not(feature = "trace"))]
+        let ret = f(input);
+        // Errors remember the innermost traced rule they happened in.
+        ret.map_err(|e| e.map(|e| e.within(fn_name)))
+    }
+
+    #[test]
+    fn test_tracer() {
+        #[derive(Default)]
+        struct Counter {
+            entered: BTreeMap<&'static str, u64>,
+            parsed: BTreeMap<&'static str, u64>,
+            outermost: Option<(&'static str, Option<usize>, u64)>,
         }
-        #[cfg(not(debug_assertions))]
-        {
-            traceable_parser_fast(f, fn_name, input)
+        impl Tracer for Counter {
+            fn enter(&mut self, rule: &'static str, _depth: usize, _input: &[u8]) {
+                *self.entered.entry(rule).or_default() += 1;
+            }
+            fn exit(
+                &mut self,
+                rule: &'static str,
+                depth: usize,
+                _input: &[u8],
+                rest: Option<&[u8]>,
+                calls: u64,
+            ) {
+                if rest.is_some() {
+                    *self.parsed.entry(rule).or_default() += 1;
+                }
+                if depth == 1 {
+                    self.outermost = Some((rule, rest.map(|rest| rest.len()), calls));
+                }
+            }
+        }
+        let counter = Rc::new(RefCell::new(Counter::default()));
+        let tracer: Rc<RefCell<dyn Tracer>> = counter.clone();
+        let options = ParseOptions {
+            tracer: Some(tracer),
+            ..ParseOptions::default()
+        };
+        parse_pdf_with_options(include_bytes!("test_simple.pdf"), &options).unwrap();
+        let counter = counter.borrow();
+        if cfg!(feature = "trace") {
+            assert_eq!(counter.entered["pdf_file"], 1);
+            assert_eq!(counter.parsed["indirect_object_definition"], 6);
+            let total = counter.entered.values().sum();
+            assert_eq!(counter.outermost, Some(("pdf_file", Some(0), total)));
+        } else {
+            assert!(counter.entered.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_on_threads() {
+        let inputs: [&'static [u8]; 2] = [
+            include_bytes!("test_simple.pdf"),
+            include_bytes!("test_xref_stream.pdf"),
+        ];
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    let input = inputs[i % 2];
+                    for _ in 0..10 {
+                        let file = parse_pdf(input).unwrap();
+                        let section = &file.body_crossref_trailers[0];
+                        assert_eq!(section.span().start, 15);
+                        let mut out: Vec<u8> = vec![];
+                        file.serialize_to(&mut out).unwrap();
+                        assert_eq!(out, input);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
         }
     }
     // >@tracing
 
+    // @<errors
+    /// Why parsing failed: the byte offset (from the start of the input) at which it failed,
+    /// the grammar rule being parsed there (e.g. "cross_reference_subsection_entry"),
+    /// and what was expected at that offset.
+    #[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+    pub struct ParseError {
+        pub offset: usize,
+        pub rule: &'static str,
+        pub expected: String,
+    }
+    impl std::fmt::Display for ParseError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "Failed to parse {} at byte {}: expected {}",
+                self.rule, self.offset, self.expected
+            )
+        }
+    }
+    impl std::error::Error for ParseError {}
+    impl ParseError {
+        // Turns the error from a nom parser that was run on `whole` into a ParseError.
+        fn from_nom(whole: &[u8], e: nom::Err<SyntaxError<&[u8]>>) -> ParseError {
+            match e {
+                nom::Err::Error(e) | nom::Err::Failure(e) => ParseError {
+                    offset: whole.len() - e.input.len(),
+                    rule: e.rule.unwrap_or("input"),
+                    expected: e.expected.into_owned(),
+                },
+                nom::Err::Incomplete(_) => ParseError {
+                    offset: whole.len(),
+                    rule: "input",
+                    expected: "more input".to_string(),
+                },
+            }
+        }
+    }
+
+    // The error type used by all our nom parsers, instead of nom's default `nom::error::Error`.
+    // It remembers the innermost traced rule (see `traceable_parser`) that failed, and what was expected.
+    #[derive(Debug)]
+    pub struct SyntaxError<I> {
+        input: I,
+        rule: Option<&'static str>,
+        expected: Cow<'static, str>,
+    }
+    impl<I> SyntaxError<I> {
+        fn new(input: I, expected: impl Into<Cow<'static, str>>) -> Self {
+            SyntaxError {
+                input,
+                rule: None,
+                expected: expected.into(),
+            }
+        }
+        fn within(mut self, rule: &'static str) -> Self {
+            self.rule.get_or_insert(rule);
+            self
+        }
+    }
+    impl<I> nom::error::ParseError<I> for SyntaxError<I> {
+        fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+            let expected = match kind {
+                ErrorKind::Tag => "a keyword or delimiter",
+                ErrorKind::Digit => "a digit",
+                ErrorKind::Char | ErrorKind::OneOf => "a specific character",
+                ErrorKind::Eof => "more input",
+                ErrorKind::TakeUntil => "a terminating keyword",
+                ErrorKind::TakeWhileMN => "a fixed number of characters",
+                ErrorKind::Many1 => "at least one item",
+                kind => return SyntaxError::new(input, kind.description().to_string()),
+            };
+            SyntaxError::new(input, expected)
+        }
+        fn append(_input: I, _kind: ErrorKind, other: Self) -> Self {
+            other
+        }
+    }
+    // All our parsers use `SyntaxError`.
+    type IResult<I, O> = nom::IResult<I, O, SyntaxError<I>>;
+
+    // Runs `parser`, but on failure says that `expected` was expected (rather than e.g. "a digit").
+    fn expecting<'a, O>(
+        expected: &'static str,
+        mut parser: impl FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+    ) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], O> {
+        move |input: &'a [u8]| {
+            parser(input).map_err(|e| {
+                e.map(|e| SyntaxError {
+                    expected: Cow::Borrowed(expected),
+                    ..e
+                })
+            })
+        }
+    }
+    // >@errors
+
+    // @<span
+    /// Where a node was parsed from: the bytes `start..end` of the input. For `parse_pdf` and
+    /// `Document`, the input is the whole file; for the objects in an object stream, it's the
+    /// (decoded) data of the stream.
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Span {
+        pub start: usize,
+        pub end: usize,
+    }
+
+    impl Span {
+        // The span of the part of `input` that was parsed, leaving `rest`.
+        fn of(input: &[u8], rest: &[u8]) -> Span {
+            let len = CONTEXT
+                .with(|c| c.borrow().input_len)
+                .filter(|&len| len >= input.len())
+                .unwrap_or(input.len());
+            Span {
+                start: len - input.len(),
+                end: len - rest.len(),
+            }
+        }
+    }
+
+    // Runs `parser`, and also returns the span of what it parsed.
+    fn spanned<'a, O>(
+        mut parser: impl FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+    ) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], (O, Span)> {
+        move |input: &'a [u8]| {
+            let (rest, parsed) = parser(input)?;
+            Ok((rest, (parsed, Span::of(input, rest))))
+        }
+    }
+
+    // `pub fn span(&self)` for each of the (public) node types with a `span` field.
+    macro_rules! span_accessors {
+        ($($t:ty),*) => {
+            $(impl $t {
+                /// Where this was parsed from.
+                pub fn span(&self) -> Span {
+                    self.span
+                }
+            })*
+        };
+    }
+    span_accessors!(
+        Integer<'_>,
+        Real<'_>,
+        LiteralString<'_>,
+        HexadecimalString<'_>,
+        NameObject,
+        ArrayObject<'_>,
+        DictionaryObject<'_>,
+        StreamObject<'_>,
+        IndirectObjectReference<'_>,
+        IndirectObjectDefinition<'_>,
+        BodyCrossrefTrailer<'_>
+    );
+    // >@span
+
+    // @<options
+    /// How to parse a file.
+    #[derive(Clone)]
+    pub struct ParseOptions {
+        /// Whether to reject things the spec doesn't allow, but that real-world files have anyway
+        /// (like a lone CR after "stream", or junk before `%PDF`). If not, each of them is recorded
+        /// as a `ParseWarning`, and the file is still written back byte for byte.
+        pub strict: bool,
+        /// What to tell about each grammar rule tried, if built with the `trace` feature (else unused).
+        pub tracer: Option<Rc<RefCell<dyn Tracer>>>,
+        /// Where progress messages, statistics and warnings go (by default, nowhere).
+        pub diagnostics: Option<Rc<RefCell<dyn Diagnostics>>>,
+    }
+    impl Default for ParseOptions {
+        fn default() -> Self {
+            ParseOptions {
+                strict: true,
+                tracer: None,
+                diagnostics: None,
+            }
+        }
+    }
+    impl std::fmt::Debug for ParseOptions {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("ParseOptions")
+                .field("strict", &self.strict)
+                .field("tracer", &self.tracer.as_ref().map(|_| "..."))
+                .field("diagnostics", &self.diagnostics.as_ref().map(|_| "..."))
+                .finish()
+        }
+    }
+
+    /// Something the spec doesn't allow, accepted anyway as parsing was not strict: the byte
+    /// offset (from the start of the input) where it is, and what it is.
+    #[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+    pub struct ParseWarning {
+        pub offset: usize,
+        pub problem: String,
+    }
+    impl std::fmt::Display for ParseWarning {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "At byte {}: {}", self.offset, self.problem)
+        }
+    }
+
+    // Whether to accept (with a warning) things that the spec doesn't allow.
+    fn lenient() -> bool {
+        CONTEXT.with(|c| c.borrow().warnings.is_some())
+    }
+
+    // Records that `problem` is at the start of `input`. (A parser that backtracks can get to
+    // the same place twice, so this ignores repeats.)
+    fn warn(input: &[u8], problem: &str) {
+        let warning = ParseWarning {
+            offset: Span::of(input, input).start,
+            problem: problem.to_string(),
+        };
+        let new = CONTEXT.with(|c| {
+            let context = &mut *c.borrow_mut();
+            match context.warnings.as_mut() {
+                Some(warnings)
+                    if context
+                        .warned
+                        .insert((warning.offset, warning.problem.clone())) =>
+                {
+                    warnings.push(warning.clone());
+                    true
+                }
+                _ => false,
+            }
+        });
+        if new {
+            diagnose(Level::Warning, || warning.to_string());
+        }
+    }
+    // >@options
+
+    // @<diagnostics
+    /// How much a diagnostic message matters.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum Level {
+        /// What the parser is doing, e.g. "Trying to parse cross-reference table from ...".
+        Debug,
+        /// Statistics, e.g. how many sections a file has.
+        Info,
+        /// A `ParseWarning` (only when not strict).
+        Warning,
+    }
+
+    /// Where the parser's diagnostic messages go, when it is in `ParseOptions::diagnostics`.
+    pub trait Diagnostics {
+        fn message(&mut self, level: Level, message: &str);
+    }
+
+    /// `Diagnostics` that prints the messages of at least `level` to stderr.
+    pub struct StderrDiagnostics {
+        pub level: Level,
+    }
+    impl Diagnostics for StderrDiagnostics {
+        fn message(&mut self, level: Level, message: &str) {
+            if level >= self.level {
+                eprintln!("{:?}: {}", level, message);
+            }
+        }
+    }
+
+    // Sends a message to the diagnostics (if any). The message is only made if there are.
+    fn diagnose(level: Level, message: impl FnOnce() -> String) {
+        if let Some(diagnostics) = CONTEXT.with(|c| c.borrow().diagnostics.clone()) {
+            diagnostics.borrow_mut().message(level, &message());
+        }
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        struct Collect(Vec<(Level, String)>);
+        impl Diagnostics for Collect {
+            fn message(&mut self, level: Level, message: &str) {
+                self.0.push((level, message.to_string()));
+            }
+        }
+        let collected = Rc::new(RefCell::new(Collect(vec![])));
+        let diagnostics: Rc<RefCell<dyn Diagnostics>> = collected.clone();
+        let options = ParseOptions {
+            strict: false,
+            diagnostics: Some(diagnostics),
+            ..ParseOptions::default()
+        };
+        let input = [&b"junk\n"[..], include_bytes!("test_simple.pdf")].concat();
+        parse_pdf_with_options(&input, &options).unwrap();
+        let messages = &collected.borrow().0;
+        let at = |level: Level| {
+            messages
+                .iter()
+                .filter(|(l, _)| *l == level)
+                .map(|(_, m)| &m[..])
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(at(Level::Warning), ["At byte 0: junk before `%PDF`"]);
+        assert!(at(Level::Info).contains(&"After 1 sections: 1 bytes left."));
+        let definitions = at(Level::Debug)
+            .into_iter()
+            .filter(|m| m.starts_with("Got an indirect object definition"))
+            .count();
+        assert_eq!(definitions, 6);
+    }
+    // >@diagnostics
+
     // @<BinSerialize
     // A trait for being able to serialize a type to bytes.
     pub trait BinSerialize {
@@ -380,6 +952,8 @@ mod pdf_file_parse {
     pub struct Integer<'a> {
         sign: Sign,
         digits: Cow<'a, [u8]>,
+        #[serde(default)]
+        span: Span,
     }
     // >@numeric/integer/type
     // @<numeric/integer
@@ -391,20 +965,45 @@ mod pdf_file_parse {
         }
     }
 
-    fn object_numeric_integer(input: &[u8]) -> IResult<&[u8], Integer> {
-        map(tuple((parse_sign, digit1)), |(sign, digits)| Integer {
-            sign,
-            digits: Cow::Borrowed(digits),
-        })(input)
+    impl Integer<'_> {
+        /// The value, if it fits in an i64.
+        pub fn value(&self) -> Option<i64> {
+            let digits = std::str::from_utf8(&self.digits).ok()?;
+            match self.sign {
+                Sign::Minus => format!("-{}", digits).parse().ok(),
+                Sign::Plus | Sign::None => digits.parse().ok(),
+            }
+        }
+    }
+
+    fn object_numeric_integer(input: &[u8]) -> IResult<&[u8], Integer<'_>> {
+        map(
+            spanned(tuple((parse_sign, digit1))),
+            |((sign, digits), span)| Integer {
+                sign,
+                digits: Cow::Borrowed(digits),
+                span,
+            },
+        )(input)
     }
 
-    fn integer_without_sign(input: &[u8]) -> IResult<&[u8], Integer> {
-        map(digit1, |digits| Integer {
+    fn integer_without_sign(input: &[u8]) -> IResult<&[u8], Integer<'_>> {
+        map(spanned(digit1), |(digits, span)| Integer {
             sign: Sign::None,
             digits: Cow::Borrowed(digits),
+            span,
         })(input)
     }
 
+    #[test]
+    fn test_integer_value() {
+        let value = |input: &[u8]| object_numeric_integer(input).unwrap().1.value();
+        assert_eq!(value(b"+17"), Some(17));
+        assert_eq!(value(b"-0042"), Some(-42));
+        assert_eq!(value(b"-9223372036854775808"), Some(i64::MIN));
+        assert_eq!(value(b"9223372036854775808"), None);
+    }
+
     // Examples from the spec
     test_round_trip!(int1: "123");
     test_round_trip!(int2: "43445");
@@ -440,6 +1039,8 @@ mod pdf_file_parse {
         sign: Sign,
         digits_before: Cow<'a, [u8]>,
         digits_after: Cow<'a, [u8]>,
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for Real<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -449,18 +1050,35 @@ mod pdf_file_parse {
             buf.write_all(&self.digits_after)
         }
     }
-    fn object_numeric_real(input: &[u8]) -> IResult<&[u8], Real> {
+    impl Real<'_> {
+        /// The value, as the nearest f64.
+        pub fn to_f64(&self) -> f64 {
+            // Either side of the `.` may be empty (as in `4.` and `-.002`), so pad both with a 0.
+            let digits = format!(
+                "0{}.{}0",
+                String::from_utf8_lossy(&self.digits_before),
+                String::from_utf8_lossy(&self.digits_after)
+            );
+            let magnitude: f64 = digits.parse().unwrap_or(0.0);
+            match self.sign {
+                Sign::Minus => -magnitude,
+                Sign::Plus | Sign::None => magnitude,
+            }
+        }
+    }
+    fn object_numeric_real(input: &[u8]) -> IResult<&[u8], Real<'_>> {
         map(
-            tuple((
+            spanned(tuple((
                 parse_sign,
                 digit0,
                 nom::character::complete::char('.'),
                 digit0,
-            )),
-            |(sign, digits_before, _, digits_after)| Real {
+            ))),
+            |((sign, digits_before, _, digits_after), span)| Real {
                 sign,
                 digits_before: Cow::Borrowed(digits_before),
                 digits_after: Cow::Borrowed(digits_after),
+                span,
             },
         )(input)
     }
@@ -471,6 +1089,15 @@ mod pdf_file_parse {
     test_round_trip!(real4: "4.");
     test_round_trip!(real5: "-.002");
     test_round_trip!(real6: "0.0");
+
+    #[test]
+    fn test_real_to_f64() {
+        let value = |input: &[u8]| object_numeric_real(input).unwrap().1.to_f64();
+        assert_eq!(value(b"34.5"), 34.5);
+        assert_eq!(value(b"+123.6"), 123.6);
+        assert_eq!(value(b"4."), 4.0);
+        assert_eq!(value(b"-.002"), -0.002);
+    }
     // >@numeric/real
     // @<numeric
     #[derive(Serialize, Deserialize, Debug)]
@@ -512,10 +1139,10 @@ mod pdf_file_parse {
         Regular(Cow<'a, [u8]>), // A part without a backslash
         Escaped(Cow<'a, [u8]>), // The part after the backslash. 11 possibilities: \n \r \t \b \f \( \) \\ \oct \EOL or empty (e.g. in \a \c \d \e \g \h \i \j etc.)
     }
-    #[derive(Serialize, Deserialize, Debug)]
+    #[derive(Serialize, Debug)]
     pub struct LiteralString<'a> {
-        #[serde(borrow)]
         parts: Vec<LiteralStringPart<'a>>,
+        span: Span,
     }
     // Examples of literal strings:
     // (abc)          => parts: [Regular("abc")]
@@ -523,7 +1150,70 @@ mod pdf_file_parse {
     // (ab (c) d)     => parts: [Regular("ab (c) d")]
     // (ab ( \n c) d) => parts: ["Regular("ab ( ", Escaped("n"), Regular(" c) d")]
     // NOTE: We assume that the Regular parts together have balanced parentheses, i.e. that their parentheses don't need escaping.
-    // TODO: While `object_literal_string` ensures this condition, a `LiteralString` could also be created from Deserialize... maybe implement custom deserialization?
+    // `object_literal_string` ensures this condition, and so does deserializing, which rejects parts
+    // (e.g. from hand-edited JSON) that would be written out as something else.
+    impl<'de: 'a, 'a> Deserialize<'de> for LiteralString<'a> {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            use serde::de::Error;
+            #[derive(Deserialize)]
+            struct Parts<'a> {
+                #[serde(borrow)]
+                parts: Vec<LiteralStringPart<'a>>,
+                #[serde(default)]
+                span: Span,
+            }
+            let Parts { parts, span } = Parts::deserialize(deserializer)?;
+            // Each Escaped part must be one of the escapes that `escaped_part` reads.
+            for part in &parts {
+                if let LiteralStringPart::Escaped(part) = part {
+                    if !matches!(escaped_part(part), Ok((b"", _))) {
+                        return Err(D::Error::custom(
+                            "an Escaped part of a literal string that is not an escape",
+                        ));
+                    }
+                }
+            }
+            let string = LiteralString { parts, span };
+            // The parentheses in the Regular parts must balance, so that none needs escaping.
+            let regular = string.parts.iter().filter_map(|part| match part {
+                LiteralStringPart::Regular(part) => Some(&part[..]),
+                LiteralStringPart::Escaped(_) => None,
+            });
+            let mut depth: i64 = 0;
+            for &c in regular.flatten() {
+                match c {
+                    b'\\' => {
+                        return Err(D::Error::custom(
+                            "a backslash in a Regular part of a literal string",
+                        ))
+                    }
+                    b'(' => depth += 1,
+                    b')' => depth -= 1,
+                    _ => {}
+                }
+                if depth < 0 {
+                    break; // A `)` that would end the string early.
+                }
+            }
+            if depth != 0 {
+                return Err(D::Error::custom(
+                    "unbalanced parentheses in the Regular parts of a literal string",
+                ));
+            }
+            // Then only an Escaped part can be wrong (like "x", or "5" followed by a digit): written
+            // out and parsed again, the string must mean the same.
+            let mut buf = vec![];
+            string.serialize_to(&mut buf).map_err(D::Error::custom)?;
+            match object_literal_string(&buf) {
+                Ok((b"", reparsed)) if reparsed.decoded_bytes() == string.decoded_bytes() => {
+                    Ok(string)
+                }
+                _ => Err(D::Error::custom(
+                    "an Escaped part of a literal string that would be read differently",
+                )),
+            }
+        }
+    }
     impl BinSerialize for LiteralString<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
             buf.write_all(b"(")?;
@@ -539,6 +1229,48 @@ mod pdf_file_parse {
             buf.write_all(b")")
         }
     }
+    impl LiteralString<'_> {
+        /// The bytes of the string: escapes undone, and each unescaped end-of-line marker read as `\n`.
+        pub fn decoded_bytes(&self) -> Vec<u8> {
+            let mut out = vec![];
+            for part in &self.parts {
+                match part {
+                    LiteralStringPart::Regular(part) => {
+                        let mut bytes = part.iter().peekable();
+                        while let Some(&b) = bytes.next() {
+                            if b == b'\r' {
+                                bytes.next_if_eq(&&b'\n');
+                                out.push(b'\n');
+                            } else {
+                                out.push(b);
+                            }
+                        }
+                    }
+                    LiteralStringPart::Escaped(part) => match &part[..] {
+                        b"n" => out.push(b'\n'),
+                        b"r" => out.push(b'\r'),
+                        b"t" => out.push(b'\t'),
+                        b"b" => out.push(0x08),
+                        b"f" => out.push(0x0C),
+                        [c @ (b'(' | b')' | b'\\')] => out.push(*c),
+                        // A backslash at the end of a line: the string continues on the next line.
+                        b"\r" | b"\n" | b"\r\n" => {}
+                        // Any other backslash is ignored.
+                        b"" => {}
+                        // 1 to 3 octal digits. "High-order overflow shall be ignored."
+                        digits if digits.iter().all(|&d| is_oct_digit(d)) => out.push(
+                            digits
+                                .iter()
+                                .fold(0u8, |n, d| n.wrapping_mul(8).wrapping_add(d - b'0')),
+                        ),
+                        // Not an escape (deserializing rejects these): the backslash is ignored.
+                        other => out.extend_from_slice(other),
+                    },
+                }
+            }
+            out
+        }
+    }
     // >@string/literal/repr
 
     // @<string/literal/tests
@@ -572,6 +1304,64 @@ mod pdf_file_parse {
     // Note the below is *not* a raw string literal so escapes are interpreted by Rust,
     // so \x80 means the byte 128 in the string, etc.
     test_round_trip_b!(str301: b"( \x80 \x99 \xFF )");
+
+    #[test]
+    fn test_literal_string_decoded_bytes() {
+        let bytes = |input: &[u8]| object_literal_string(input).unwrap().1.decoded_bytes();
+        assert_eq!(bytes(b"(ab (c) d)"), b"ab (c) d");
+        assert_eq!(bytes(br"(\n\r\t\b\f\(\)\\)"), b"\n\r\t\x08\x0C()\\");
+        assert_eq!(
+            bytes(b"(These \\\r\n two strings \\\n are the same.)"),
+            b"These  two strings  are the same."
+        );
+        assert_eq!(
+            bytes(br"(This string contains \245two octal characters\307.)"),
+            b"This string contains \xA5two octal characters\xC7."
+        );
+        assert_eq!(bytes(br"(\0053)"), b"\x053");
+        assert_eq!(bytes(br"(\53)"), b"+");
+        // High-order overflow is ignored.
+        assert_eq!(bytes(br"(\777)"), b"\xFF");
+        // An unknown escape: the backslash is ignored.
+        assert_eq!(bytes(br"(\c)"), b"c");
+        // Unescaped end-of-line markers are all read as `\n`.
+        assert_eq!(bytes(b"(a\r\nb\rc\nd)"), b"a\nb\nc\nd");
+    }
+
+    #[test]
+    fn test_literal_string_deserialize_checks() {
+        // The JSON for a string with these parts (Regular if `regular`, else Escaped).
+        let json = |parts: &[(bool, &str)]| {
+            let parts: Vec<String> = parts
+                .iter()
+                .map(|(regular, part)| {
+                    let kind = if *regular { "Regular" } else { "Escaped" };
+                    format!(r#"{{"{}":{:?}}}"#, kind, part.as_bytes())
+                })
+                .collect();
+            format!(r#"{{"parts":[{}]}}"#, parts.join(","))
+        };
+        let error =
+            |parts: &[(bool, &str)]| match serde_json::from_str::<LiteralString>(&json(parts)) {
+                Ok(_) => String::new(),
+                Err(e) => e.to_string(),
+            };
+        assert_eq!(error(&[(true, "ab (c) d")]), "");
+        assert_eq!(error(&[(false, "("), (true, "a")]), "");
+        assert_eq!(error(&[(false, "53"), (true, "a")]), "");
+        let unbalanced = "unbalanced parentheses in the Regular parts of a literal string";
+        assert!(error(&[(true, "a)b(")]).starts_with(unbalanced));
+        assert!(error(&[(true, "a(b"), (false, ")")]).starts_with(unbalanced));
+        assert!(error(&[(true, "a\\nb")]).starts_with("a backslash in a Regular part"));
+        let not_escape = "an Escaped part of a literal string that is not an escape";
+        assert!(error(&[(false, " ")]).starts_with(not_escape));
+        assert!(error(&[(false, "1234")]).starts_with(not_escape));
+        assert!(error(&[(false, "8")]).starts_with(not_escape));
+        let escaped = "an Escaped part of a literal string that would be read differently";
+        assert!(error(&[(false, "x")]).starts_with(not_escape));
+        assert!(error(&[(false, "5"), (true, "3")]).starts_with(escaped));
+        assert!(error(&[(true, "a"), (false, "")]).starts_with(escaped));
+    }
     // >@string/literal/tests
 
     // @<string/literal/rest
@@ -595,7 +1385,8 @@ mod pdf_file_parse {
 
     // Parses a string literal from `(` to `)`, while keeping track of balanced parentheses and handling backslash-escapes.
     // #[adorn(traceable_parser("literal_string"))]
-    fn object_literal_string<'a>(input: &'a [u8]) -> IResult<&[u8], LiteralString> {
+    fn object_literal_string<'a>(input: &'a [u8]) -> IResult<&'a [u8], LiteralString<'a>> {
+        let start = input;
         let (input, _) = tag(b"(")(input)?;
         let mut parts: Vec<LiteralStringPart<'a>> = vec![]; // The result
         let mut paren_depth = 1;
@@ -629,7 +1420,9 @@ mod pdf_file_parse {
                         if i < j {
                             parts.push(LiteralStringPart::Regular(Cow::Borrowed(&input[i..j])));
                         }
-                        return Ok((&input[j + 1..], LiteralString { parts }));
+                        let rest = &input[j + 1..];
+                        let span = Span::of(start, rest);
+                        return Ok((rest, LiteralString { parts, span }));
                     }
                     // We're at a close paren that does not end the string / current part.
                     j += 1;
@@ -638,8 +1431,10 @@ mod pdf_file_parse {
             }
         }
         // If we reach here (end of input), there were unmatched parentheses.
-        Err(nom::Err::Incomplete(nom::Needed::Size(
-            std::num::NonZeroUsize::new(paren_depth).unwrap(),
+        // Nothing else can start with "(", so there's no point in backtracking.
+        Err(nom::Err::Failure(SyntaxError::new(
+            &input[input.len()..],
+            format!("{} more `)` to close the literal string", paren_depth),
         )))
     }
 
@@ -653,6 +1448,8 @@ mod pdf_file_parse {
     #[derive(Serialize, Deserialize, Debug)]
     pub struct HexadecimalString<'a> {
         chars: Cow<'a, [u8]>,
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for HexadecimalString<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -661,6 +1458,20 @@ mod pdf_file_parse {
                 .and(buf.write_all(b">"))
         }
     }
+    impl HexadecimalString<'_> {
+        /// The bytes of the string. "If the final digit of a hexadecimal string is missing [...] it shall be assumed to be 0."
+        pub fn decoded_bytes(&self) -> Vec<u8> {
+            let digits: Vec<u8> = self
+                .chars
+                .iter()
+                .filter_map(|&c| (c as char).to_digit(16).map(|d| d as u8))
+                .collect();
+            digits
+                .chunks(2)
+                .map(|d| (d[0] << 4) | d.get(1).copied().unwrap_or(0))
+                .collect()
+        }
+    }
 
     fn is_white_space_char(c: u8) -> bool {
         const NUL: u8 = 0;
@@ -669,10 +1480,10 @@ mod pdf_file_parse {
         const FORM_FEED: u8 = 0x0C;
         const CARRIAGE_RETURN: u8 = b'\r';
         const SPACE: u8 = b' ';
-        match c {
-            SPACE | HORIZONTAL_TAB | CARRIAGE_RETURN | LINE_FEED | NUL | FORM_FEED => true,
-            _ => false,
-        }
+        matches!(
+            c,
+            SPACE | HORIZONTAL_TAB | CARRIAGE_RETURN | LINE_FEED | NUL | FORM_FEED
+        )
     }
 
     // A character that can occur inside the <...> in a hexadecimal string.
@@ -680,13 +1491,18 @@ mod pdf_file_parse {
         if is_white_space_char(c) {
             return true;
         }
-        (b'0' <= c && c <= b'9') || (b'a' <= c && c <= b'f') || (b'A' <= c && c <= b'F')
+        c.is_ascii_hexdigit()
     }
-    fn object_hexadecimal_string(input: &[u8]) -> IResult<&[u8], HexadecimalString> {
+    fn object_hexadecimal_string(input: &[u8]) -> IResult<&[u8], HexadecimalString<'_>> {
         map(
-            delimited(tag(b"<"), take_while(is_hex_string_char), tag(b">")),
-            |chars| HexadecimalString {
+            spanned(delimited(
+                tag(b"<"),
+                take_while(is_hex_string_char),
+                tag(b">"),
+            )),
+            |(chars, span)| HexadecimalString {
                 chars: Cow::Borrowed(chars),
+                span,
             },
         )(input)
     }
@@ -699,6 +1515,15 @@ mod pdf_file_parse {
     test_round_trip!(str204: "<90 1f \r \n
                  A>"
     );
+
+    #[test]
+    fn test_hexadecimal_string_decoded_bytes() {
+        let bytes = |input: &[u8]| object_hexadecimal_string(input).unwrap().1.decoded_bytes();
+        assert_eq!(bytes(b"<901FA3>"), [0x90, 0x1F, 0xA3]);
+        assert_eq!(bytes(b"<901FA>"), [0x90, 0x1F, 0xA0]);
+        assert_eq!(bytes(b"<90 1f \r \n a>"), [0x90, 0x1F, 0xA0]);
+        assert!(bytes(b"<>").is_empty());
+    }
     // >@string/hexadecimal
 
     // @<string
@@ -716,6 +1541,16 @@ mod pdf_file_parse {
             }
         }
     }
+    impl StringObject<'_> {
+        /// The bytes of the string, however it was written.
+        /// (For a string in an encrypted file, use `Document::string_bytes` instead.)
+        pub fn decoded_bytes(&self) -> Vec<u8> {
+            match self {
+                StringObject::Literal(s) => s.decoded_bytes(),
+                StringObject::Hex(h) => h.decoded_bytes(),
+            }
+        }
+    }
     #[adorn(traceable_parser("string"))]
     fn object_string(input: &[u8]) -> IResult<&[u8], StringObject> {
         alt((
@@ -746,6 +1581,8 @@ mod pdf_file_parse {
     #[derive(Serialize, Deserialize, Debug)]
     pub struct NameObject {
         chars: Vec<NameObjectChar>,
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for NameObject {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -756,16 +1593,43 @@ mod pdf_file_parse {
             Ok(())
         }
     }
+    impl NameObject {
+        fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+            self.chars.iter().map(|c| match *c {
+                NameObjectChar::Regular(c) => c,
+                NameObjectChar::NumberSignPrefixed(n1, n2) => {
+                    let digit = |d: u8| (d as char).to_digit(16).unwrap_or(0) as u8;
+                    (digit(n1) << 4) | digit(n2)
+                }
+            })
+        }
+        /// The bytes of the name (without the `/`), with any `#` escapes decoded.
+        pub fn decoded_bytes(&self) -> Vec<u8> {
+            self.bytes().collect()
+        }
+        /// Whether this is the name `name` (without the `/`), after decoding any `#` escapes:
+        /// so `/T#79pe` is `Type`.
+        pub fn is(&self, name: &[u8]) -> bool {
+            self.bytes().eq(name.iter().copied())
+        }
+    }
     // >@name/repr
 
     // @<name
-    fn eof_error<I>(input: I) -> nom::Err<nom::error::Error<I>> {
-        nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Eof))
+    fn eof_error<I>(input: I) -> nom::Err<SyntaxError<I>> {
+        nom::Err::Error(SyntaxError::new(input, "two hex digits after `#`"))
     }
 
     // #[adorn(traceable_parser("name"))]
     fn object_name(input: &[u8]) -> IResult<&[u8], NameObject> {
-        let (mut rest, _solidus) = tag(b"/")(input)?;
+        let (rest, _solidus) = tag(b"/")(input)?;
+        let (rest, chars) = name_chars(rest)?;
+        let span = Span::of(input, rest);
+        Ok((rest, NameObject { chars, span }))
+    }
+
+    // The characters of a name, after the solidus.
+    fn name_chars(mut rest: &[u8]) -> IResult<&[u8], Vec<NameObjectChar>> {
         let mut chars: Vec<NameObjectChar> = vec![];
         while let Some(&c) = rest.first() {
             // Spec says characters outside printable ASCII range (! to ~) should also be written with #,
@@ -787,7 +1651,7 @@ mod pdf_file_parse {
                 }
             }
         }
-        Ok((rest, NameObject { chars }))
+        Ok((rest, chars))
     }
 
     // Examples from the spec
@@ -809,6 +1673,15 @@ mod pdf_file_parse {
     test_round_trip!(name301: "/AGSWKP#2bHelvetica");
     // "/ABCDEE+等线,Bold" -- not spec-compliant, but encountered in practice.
     test_round_trip_b!(name302: b"/ABCDEE+\xE7\xAD\x89\xE7\xBA\xBF,Bold");
+
+    #[test]
+    fn test_name_decoded_bytes() {
+        let bytes = |input: &[u8]| object_name(input).unwrap().1.decoded_bytes();
+        assert_eq!(bytes(b"/Name1"), b"Name1");
+        assert_eq!(bytes(b"/lime#20Green"), b"lime Green");
+        assert_eq!(bytes(b"/paired#28#29parentheses"), b"paired()parentheses");
+        assert_eq!(bytes(b"/hello#80#32#99world"), b"hello\x802\x99world");
+    }
     // >@name
 
     // ===================
@@ -835,6 +1708,8 @@ mod pdf_file_parse {
     pub struct ArrayObject<'a> {
         #[serde(borrow)]
         parts: Vec<ArrayObjectPart<'a>>,
+        #[serde(default)]
+        span: Span,
     }
 
     impl BinSerialize for ArrayObject<'_> {
@@ -846,6 +1721,15 @@ mod pdf_file_parse {
             write!(buf, "]")
         }
     }
+    impl<'a> ArrayObject<'a> {
+        // The elements of the array (i.e. skipping whitespace and comments).
+        fn elements(&self) -> impl Iterator<Item = &ObjectOrReference<'a>> {
+            self.parts.iter().filter_map(|part| match part {
+                ArrayObjectPart::ObjectOrRef(o) => Some(o),
+                ArrayObjectPart::Whitespace(_) => None,
+            })
+        }
+    }
     // >@array/repr
 
     // @<comments
@@ -886,9 +1770,9 @@ mod pdf_file_parse {
 
     // @<array/parse
     // #[adorn(traceable_parser("array_part"))]
-    fn array_object_part(input: &[u8]) -> IResult<&[u8], ArrayObjectPart> {
+    fn array_object_part(input: &[u8]) -> IResult<&[u8], ArrayObjectPart<'_>> {
         alt((
-            map(object_or_ref, |o| ArrayObjectPart::ObjectOrRef(o)),
+            map(object_or_ref, ArrayObjectPart::ObjectOrRef),
             map(whitespace_and_comments_nonempty, |w| {
                 ArrayObjectPart::Whitespace(Cow::Borrowed(w))
             }),
@@ -898,8 +1782,8 @@ mod pdf_file_parse {
     #[adorn(traceable_parser("array"))]
     fn object_array(input: &[u8]) -> IResult<&[u8], ArrayObject> {
         map(
-            delimited(tag(b"["), many0(array_object_part), tag(b"]")),
-            |parts| ArrayObject { parts },
+            spanned(delimited(tag(b"["), many0(array_object_part), tag(b"]"))),
+            |(parts, span)| ArrayObject { parts, span },
         )(input)
     }
 
@@ -921,6 +1805,8 @@ mod pdf_file_parse {
         ws: Cow<'a, [u8]>,
         #[serde(borrow)]
         value: ObjectOrReference<'a>,
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for KeyValuePair<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -932,16 +1818,67 @@ mod pdf_file_parse {
     #[adorn(traceable_parser("dict_key_value_pair"))]
     fn key_value_pair(input: &[u8]) -> IResult<&[u8], KeyValuePair> {
         map(
-            tuple((object_name, whitespace_and_comments, object_or_ref)),
-            |(key, ws, value)| KeyValuePair {
+            spanned(alt((
+                tuple((object_name, whitespace_and_comments, object_or_ref)),
+                key_with_spaces_value_pair,
+            ))),
+            |((key, ws, value), span)| KeyValuePair {
                 key,
                 ws: Cow::Borrowed(ws),
                 value,
+                span,
             },
         )(input)
     }
 
+    // Not spec-compliant, but encountered in practice: a key with spaces in it, like "/companyName, LLC".
+    // Only tried when not strict, and when treating the part after the space as the value does not work.
+    fn key_with_spaces_value_pair(
+        input: &[u8],
+    ) -> IResult<&[u8], (NameObject, &[u8], ObjectOrReference<'_>)> {
+        if !lenient() {
+            return Err(nom::Err::Error(SyntaxError::new(
+                input,
+                "a dictionary key without spaces",
+            )));
+        }
+        let (rest, pair) = tuple((
+            dictionary_key_with_spaces,
+            whitespace_and_comments,
+            object_or_ref,
+        ))(input)?;
+        warn(input, "a space in a dictionary key");
+        Ok((rest, pair))
+    }
+    fn dictionary_key_with_spaces(input: &[u8]) -> IResult<&[u8], NameObject> {
+        let (mut rest, mut name) = object_name(input)?;
+        let mut extended = false;
+        loop {
+            let spaces = rest.iter().take_while(|&&c| c == b' ').count();
+            match name_chars(&rest[spaces..]) {
+                Ok((after, more)) if spaces > 0 && !more.is_empty() => {
+                    name.chars.extend(
+                        std::iter::repeat_with(|| NameObjectChar::Regular(b' ')).take(spaces),
+                    );
+                    name.chars.extend(more);
+                    rest = after;
+                    extended = true;
+                }
+                _ => break,
+            }
+        }
+        if !extended {
+            return Err(nom::Err::Error(SyntaxError::new(
+                rest,
+                "a dictionary value",
+            )));
+        }
+        name.span = Span::of(input, rest);
+        Ok((rest, name))
+    }
+
     #[derive(Serialize, Deserialize, Debug)]
+    #[allow(clippy::large_enum_variant)]
     enum DictionaryPart<'a> {
         Whitespace(Cow<'a, [u8]>),
         #[serde(borrow)]
@@ -960,6 +1897,8 @@ mod pdf_file_parse {
     pub struct DictionaryObject<'a> {
         #[serde(borrow)]
         parts: Vec<DictionaryPart<'a>>,
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for DictionaryObject<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -970,9 +1909,98 @@ mod pdf_file_parse {
             buf.write_all(b">>")
         }
     }
+    /// A key that occurs more than once in a dictionary.
+    #[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+    pub struct DuplicateKey {
+        pub key: Vec<u8>, // Decoded, without the `/`
+        // The byte offset of each occurrence of the key: from the dictionary's `<<` for
+        // `DictionaryObject::duplicate_keys`, or from the start of the file for `PdfFile::duplicate_keys`.
+        pub offsets: Vec<usize>,
+    }
+
+    impl<'a> DictionaryObject<'a> {
+        fn entries(&self) -> impl Iterator<Item = &KeyValuePair<'a>> {
+            self.parts.iter().filter_map(|part| match part {
+                DictionaryPart::KeyValuePair(kv) => Some(kv),
+                DictionaryPart::Whitespace(_) => None,
+            })
+        }
+        /// The value for `key` (without the `/`). Keys are compared after decoding any `#` escapes,
+        /// so `get(b"Type")` finds `/T#79pe`. If the key occurs more than once, this is the first
+        /// value; see `duplicate_keys`.
+        pub fn get(&self, key: &[u8]) -> Option<&ObjectOrReference<'a>> {
+            self.entries().find(|kv| kv.key.is(key)).map(|kv| &kv.value)
+        }
+        /// The entries, in order (including any with duplicate keys).
+        pub fn iter(&self) -> impl Iterator<Item = (&NameObject, &ObjectOrReference<'a>)> {
+            self.entries().map(|kv| (&kv.key, &kv.value))
+        }
+        /// The keys, in order (including any duplicates).
+        pub fn keys(&self) -> impl Iterator<Item = &NameObject> {
+            self.entries().map(|kv| &kv.key)
+        }
+        /// The number of entries (including any with duplicate keys).
+        pub fn len(&self) -> usize {
+            self.entries().count()
+        }
+        pub fn is_empty(&self) -> bool {
+            self.entries().next().is_none()
+        }
+        /// The keys (compared after decoding) that occur more than once, in order of first occurrence,
+        /// each with the offsets of its occurrences from the `<<`.
+        pub fn duplicate_keys(&self) -> Vec<DuplicateKey> {
+            self.duplicate_keys_from(self.span != Span::default())
+        }
+
+        // Like `duplicate_keys`, finding the offsets from the spans if `spans` (for a dictionary that
+        // was parsed), else by adding up the lengths of the entries written out.
+        fn duplicate_keys_from(&self, spans: bool) -> Vec<DuplicateKey> {
+            let mut seen: Vec<DuplicateKey> = vec![];
+            for (kv, offset) in self.key_value_pairs_with_offsets(spans) {
+                let key = kv.key.decoded_bytes();
+                match seen.iter_mut().find(|d| d.key == key) {
+                    Some(d) => d.offsets.push(offset),
+                    None => seen.push(DuplicateKey {
+                        key,
+                        offsets: vec![offset],
+                    }),
+                }
+            }
+            seen.retain(|d| d.offsets.len() > 1);
+            seen
+        }
+
+        // Each key-value pair, with its offset from the `<<` (found as in `duplicate_keys_from`).
+        fn key_value_pairs_with_offsets(&self, spans: bool) -> Vec<(&KeyValuePair<'a>, usize)> {
+            let mut offset = b"<<".len();
+            let mut out = vec![];
+            for part in &self.parts {
+                if let DictionaryPart::KeyValuePair(kv) = part {
+                    let kv_offset = if spans {
+                        kv.span.start.saturating_sub(self.span.start)
+                    } else {
+                        offset
+                    };
+                    out.push((kv, kv_offset));
+                }
+                if !spans {
+                    offset += serialized_len(part);
+                }
+            }
+            out
+        }
+    }
+
+    // The number of bytes that `x` serializes to.
+    fn serialized_len(x: &impl BinSerialize) -> usize {
+        let mut buf = vec![];
+        x.serialize_to(&mut buf).unwrap();
+        buf.len()
+    }
 
     #[adorn(traceable_parser("dict"))]
     fn object_dictionary(input: &[u8]) -> IResult<&[u8], DictionaryObject> {
+        let start = input;
         let (input, (parts, final_ws)) = delimited(
             tag(b"<<"),
             tuple((
@@ -993,7 +2021,13 @@ mod pdf_file_parse {
             dict_parts.push(DictionaryPart::Whitespace(Cow::Borrowed(final_ws)));
         }
 
-        Ok((input, DictionaryObject { parts: dict_parts }))
+        Ok((
+            input,
+            DictionaryObject {
+                parts: dict_parts,
+                span: Span::of(start, input),
+            },
+        ))
     }
 
     test_round_trip!(dict_empty: "<<>>");
@@ -1040,15 +2074,23 @@ mod pdf_file_parse {
 >>
 /Resources 3 0 R
 >>");
-    // From real life, lightly modified. Note the "/companyName, LLC" as key!
-    test_round_trip!(dict202: r"<<
+    // From real life, lightly modified. Note the "/companyName, LLC" as key! A space ends a
+    // name, so this isn't valid PDF: it only parses when not strict (as `test_pdf_file_lenient`
+    // checks), and not here.
+    #[test]
+    #[ignore = "a dictionary key can't have a space in it"]
+    fn dict202() {
+        test_round_trip_str(
+            r"<<
     /Universal PDF(The process that creates this PDF ... United States)
     /Producer(pdfeTeX-1.21a; modified using iText� 5.5.6 �2000-2015 iText Group NV \(AGPL-version\))
     /Creator(TeX)
     /companyName, LLC(http://www.example.com)
     /ModDate(D:20170416015229+05'30')
     /CreationDate(D:20170331194508+02'00')
-    >>");
+    >>",
+        );
+    }
     // >@dict
 
     // ====================
@@ -1057,24 +2099,87 @@ mod pdf_file_parse {
 
     // @<stream
     #[derive(Serialize, Deserialize, Debug)]
+    #[allow(clippy::upper_case_acronyms)]
     enum EolMarker {
         CRLF,
         LF,
+        CR, // Not allowed, but accepted when not strict
+    }
+
+    /// How the end of a stream's data was found.
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum StreamLength {
+        /// From a direct `/Length`: the data is this many bytes, then an optional EOL, then "endstream".
+        Direct(usize),
+        /// Likewise, from an indirect `/Length` (like `/Length 8 0 R`), looked up in the cross-reference table.
+        Indirect(usize),
+        /// By scanning for the first "endstream", as `/Length` was missing, or wrong.
+        #[default]
+        Scanned,
     }
 
     struct RestOfStreamObject<'a> {
         ws_and_comments: Cow<'a, [u8]>, // The whitespace (and comments) after the dict and before the stream
-        eol_after_stream_begin: EolMarker, // The EOL marker (either CRLF or LF) after the "stream" keyword
+        eol_after_stream_begin: EolMarker, // The EOL marker (CRLF or LF, or CR) after the "stream" keyword
         content: Cow<'a, [u8]>,
+        length: StreamLength,
     }
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize)]
     pub struct StreamObject<'a> {
         #[serde(borrow)]
         dict: DictionaryObject<'a>,
         ws_and_comments: Cow<'a, [u8]>, // The whitespace (and comments) after the dict and before the stream
-        eol_after_stream_begin: EolMarker, // The EOL marker (either CRLF or LF) after the "stream" keyword
-        content: Cow<'a, [u8]>,
+        eol_after_stream_begin: EolMarker, // The EOL marker (CRLF or LF, or CR) after the "stream" keyword
+        content: Cow<'a, [u8]>, // Everything up to "endstream", so including any EOL after the data
+        #[serde(default)]
+        length: StreamLength,
+        #[serde(default)]
+        span: Span,
+    }
+
+    // Deserializing checks that the length (if from /Length) is within the content, as `data` assumes.
+    impl<'de: 'a, 'a> Deserialize<'de> for StreamObject<'a> {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            use serde::de::Error;
+            #[derive(Deserialize)]
+            struct Fields<'a> {
+                #[serde(borrow)]
+                dict: DictionaryObject<'a>,
+                ws_and_comments: Cow<'a, [u8]>,
+                eol_after_stream_begin: EolMarker,
+                content: Cow<'a, [u8]>,
+                #[serde(default)]
+                length: StreamLength,
+                #[serde(default)]
+                span: Span,
+            }
+            let Fields {
+                dict,
+                ws_and_comments,
+                eol_after_stream_begin,
+                content,
+                length,
+                span,
+            } = Fields::deserialize(deserializer)?;
+            if let StreamLength::Direct(n) | StreamLength::Indirect(n) = length {
+                if n > content.len() {
+                    return Err(D::Error::custom(format!(
+                        "a stream length of {} bytes, but only {} bytes of content",
+                        n,
+                        content.len()
+                    )));
+                }
+            }
+            Ok(StreamObject {
+                dict,
+                ws_and_comments,
+                eol_after_stream_begin,
+                content,
+                length,
+                span,
+            })
+        }
     }
 
     impl std::fmt::Debug for StreamObject<'_> {
@@ -1089,6 +2194,8 @@ mod pdf_file_parse {
                 &self.eol_after_stream_begin
             )?;
             write!(f, "content: ({} bytes)", &self.content.len())?;
+            write!(f, "length: {:?}", &self.length)?;
+            write!(f, "span: {:?}", &self.span)?;
             write!(f, " }}")
         }
     }
@@ -1101,31 +2208,115 @@ mod pdf_file_parse {
             buf.write_all(match self.eol_after_stream_begin {
                 EolMarker::CRLF => b"\r\n",
                 EolMarker::LF => b"\n",
+                EolMarker::CR => b"\r",
             })?;
             buf.write_all(&self.content)?;
             buf.write_all(b"endstream")
         }
     }
 
-    #[adorn(traceable_parser("rest_of_stream"))]
-    fn object_stream_after_dict(input: &[u8]) -> IResult<&[u8], RestOfStreamObject> {
+    impl<'a> StreamObject<'a> {
+        /// The stream dictionary.
+        pub fn dict(&self) -> &DictionaryObject<'a> {
+            &self.dict
+        }
+
+        /// How the end of the data was found.
+        pub fn length(&self) -> StreamLength {
+            self.length
+        }
+
+        /// The (raw, still encoded) data in the stream: without the EOL marker before "endstream".
+        pub fn data(&self) -> &[u8] {
+            match self.length {
+                StreamLength::Direct(n) | StreamLength::Indirect(n) if n <= self.content.len() => {
+                    &self.content[..n]
+                }
+                // (Or a length longer than the content, which parsing and deserializing rule out.)
+                StreamLength::Direct(_) | StreamLength::Indirect(_) | StreamLength::Scanned => {
+                    let content = &self.content[..];
+                    content
+                        .strip_suffix(b"\r\n")
+                        .or_else(|| content.strip_suffix(b"\n"))
+                        .or_else(|| content.strip_suffix(b"\r"))
+                        .unwrap_or(content)
+                }
+            }
+        }
+    }
+
+    // Runs `f`, with the indirect /Length of a stream looked up in `lengths`.
+    fn with_indirect_lengths<T>(lengths: BTreeMap<(u32, u16), usize>, f: impl FnOnce() -> T) -> T {
+        let previous =
+            CONTEXT.with(|c| std::mem::replace(&mut c.borrow_mut().indirect_lengths, lengths));
+        let ret = f();
+        CONTEXT.with(|c| c.borrow_mut().indirect_lengths = previous);
+        ret
+    }
+
+    #[adorn(traceable_parser("stream_keyword"))]
+    fn stream_keyword(input: &[u8]) -> IResult<&[u8], (&[u8], EolMarker)> {
         let (input, ws_and_comments) = whitespace_and_comments(input)?;
-        // println!("Got some ws: {:?}", from_utf8(ws_and_comments).unwrap());
         let (input, _) = tag("stream")(input)?;
-        // println!(
-        //     "Also parsed 'stream': remaining is #{}# bytes which starts with {} and {}",
-        //     input.len(),
-        //     input[0],
-        //     input[1]
-        // );
-        let (input, eol) = alt((tag(b"\r\n"), tag(b"\n")))(input)?;
-        // println!("And the EOL marker following.");
-        let eol_after_stream_begin = if eol == b"\r\n" {
-            EolMarker::CRLF
-        } else {
-            EolMarker::LF
+        let (after, eol) = alt((tag(b"\r\n"), tag(b"\n"), tag(b"\r")))(input)?;
+        let eol_after_stream_begin = match eol {
+            b"\r\n" => EolMarker::CRLF,
+            b"\n" => EolMarker::LF,
+            _ if lenient() => {
+                warn(input, "a lone CR after `stream` (should be CRLF or LF)");
+                EolMarker::CR
+            }
+            _ => {
+                return Err(nom::Err::Error(SyntaxError::new(
+                    input,
+                    "CRLF or LF after `stream`",
+                )))
+            }
+        };
+        Ok((after, (ws_and_comments, eol_after_stream_begin)))
+    }
+
+    // The rest of a stream whose dictionary is `dict`. The data is `/Length` bytes long if that is
+    // followed by "endstream" (after an optional EOL), else it runs up to the first "endstream".
+    fn object_stream_after_dict<'a>(
+        input: &'a [u8],
+        dict: &DictionaryObject,
+    ) -> IResult<&'a [u8], RestOfStreamObject<'a>> {
+        let (input, (ws_and_comments, eol_after_stream_begin)) = stream_keyword(input)?;
+        let length = match dict.get(b"Length") {
+            Some(ObjectOrReference::Reference(r)) => r
+                .id()
+                .and_then(|id| CONTEXT.with(|c| c.borrow().indirect_lengths.get(&id).copied()))
+                .map(StreamLength::Indirect),
+            Some(length) => length
+                .as_integer()
+                .and_then(|n| usize::try_from(n).ok())
+                .map(StreamLength::Direct),
+            None => None,
+        };
+        let length_then_endstream = |n: usize| -> Option<usize> {
+            let after = input.get(n..)?;
+            let eol = [&b"\r\n"[..], b"\n", b"\r"]
+                .into_iter()
+                .find(|eol| after.starts_with(eol))
+                .map_or(0, |eol| eol.len());
+            after[eol..].starts_with(b"endstream").then_some(n + eol)
+        };
+        let (input, content, length) = match length {
+            Some(StreamLength::Direct(n) | StreamLength::Indirect(n)) => {
+                match length_then_endstream(n) {
+                    Some(end) => (&input[end..], &input[..end], length.unwrap()),
+                    None => {
+                        let (input, content) = take_until("endstream")(input)?;
+                        (input, content, StreamLength::Scanned)
+                    }
+                }
+            }
+            _ => {
+                let (input, content) = take_until("endstream")(input)?;
+                (input, content, StreamLength::Scanned)
+            }
         };
-        let (input, content) = take_until("endstream")(input)?;
         let (input, _) = tag("endstream")(input)?;
         Ok((
             input,
@@ -1133,6 +2324,7 @@ mod pdf_file_parse {
                 ws_and_comments: Cow::Borrowed(ws_and_comments),
                 eol_after_stream_begin,
                 content: Cow::Borrowed(content),
+                length,
             },
         ))
     }
@@ -1157,6 +2349,49 @@ ET
 endstream");
 
     test_round_trip_b!(stream103: include_bytes!("test_4.in"));
+
+    #[test]
+    fn test_stream_length() {
+        fn stream(input: &[u8]) -> StreamObject<'_> {
+            match object(input).unwrap() {
+                (b"", Object::Stream(s)) => s,
+                x => panic!("not (just) a stream: {:?}", x),
+            }
+        }
+        // Data that contains "endstream" is fine, given its length.
+        let s = stream(b"<< /Length 19 >>\nstream\nnot endstream, yet\r\nendstream");
+        assert_eq!(s.length(), StreamLength::Direct(19));
+        assert_eq!(s.data(), b"not endstream, yet\r");
+        // With no EOL before "endstream".
+        let s = stream(b"<< /Length 3 >>\nstream\nabcendstream");
+        assert_eq!(
+            (s.length(), s.data()),
+            (StreamLength::Direct(3), &b"abc"[..])
+        );
+        // If the length is wrong (or missing), the data ends before the first "endstream".
+        for input in [
+            &b"<< /Length 2 >>\nstream\nabc\nendstream"[..],
+            b"<< /Length 300 >>\nstream\nabc\nendstream",
+            b"<< /Length -1 >>\nstream\nabc\nendstream",
+            b"<< >>\nstream\nabc\nendstream",
+        ] {
+            let s = stream(input);
+            assert_eq!((s.length(), s.data()), (StreamLength::Scanned, &b"abc"[..]));
+        }
+        // An indirect length is looked up in the cross-reference table (when parsing a file).
+        let input = b"<< /Length 8 0 R >>\nstream\nendstream\nendstream";
+        // (Without the cross-reference table, it's the first "endstream".)
+        assert_eq!(object(input).unwrap().0, b"\nendstream");
+        let lengths = [((8, 0), 9)].into_iter().collect();
+        let s = with_indirect_lengths(lengths, || {
+            test_round_trip_bytes(input);
+            stream(input)
+        });
+        assert_eq!(
+            (s.length(), s.data()),
+            (StreamLength::Indirect(9), &b"endstream"[..])
+        );
+    }
     // >@stream
 
     // =================
@@ -1176,6 +2411,8 @@ endstream");
         ws1: Cow<'a, [u8]>,
         generation_number: Integer<'a>,
         ws2: Cow<'a, [u8]>,
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for IndirectObjectReference<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -1186,6 +2423,20 @@ endstream");
             buf.write_all(b"R")
         }
     }
+    impl IndirectObjectReference<'_> {
+        /// The object number and generation number, if they are in range.
+        pub fn id(&self) -> Option<(u32, u16)> {
+            let number = self
+                .object_number
+                .value()
+                .and_then(|n| u32::try_from(n).ok())?;
+            let generation = self
+                .generation_number
+                .value()
+                .and_then(|g| u16::try_from(g).ok())?;
+            Some((number, generation))
+        }
+    }
     #[adorn(traceable_parser("indirect_object_reference"))]
     fn indirect_object_reference(input: &[u8]) -> IResult<&[u8], IndirectObjectReference> {
         /*
@@ -1199,6 +2450,7 @@ endstream");
             >>
             endobj
         */
+        let start = input;
         let (input, int1) = object_numeric_integer(input)?;
         let (input, ws1) = whitespace_and_comments(input)?;
         let (input, int2) = integer_without_sign(input)?;
@@ -1211,6 +2463,7 @@ endstream");
                 ws1: Cow::Borrowed(ws1),
                 generation_number: int2,
                 ws2: Cow::Borrowed(ws2),
+                span: Span::of(start, input),
             },
         ))
     }
@@ -1227,6 +2480,12 @@ endstream");
         #[serde(borrow)]
         object: Object<'a>,
         ws4: Cow<'a, [u8]>, // Between the actual object and "endobj"
+        // Whether "endobj" is missing (accepted when not strict, if the next object or the
+        // cross-reference table follows).
+        #[serde(default)]
+        missing_endobj: bool,
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for IndirectObjectDefinition<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -1238,12 +2497,22 @@ endstream");
             buf.write_all(&self.ws3)?;
             self.object.serialize_to(buf)?;
             buf.write_all(&self.ws4)?;
+            if self.missing_endobj {
+                return Ok(());
+            }
             buf.write_all(b"endobj")
         }
     }
+    impl<'a> IndirectObjectDefinition<'a> {
+        /// The object that this defines.
+        pub fn object(&self) -> &Object<'a> {
+            &self.object
+        }
+    }
     #[adorn(traceable_parser("indirect_object_definition"))]
     fn indirect_object_definition(input: &[u8]) -> IResult<&[u8], IndirectObjectDefinition> {
         // println!("Trying to parse obj def from {} bytes", input.len());
+        let start = input;
         let (input, int1) = integer_without_sign(input)?;
         // println!("int1 {:?} trying to parse from {} bytes", int1, input.len());
         let (input, ws1) = whitespace_and_comments(input)?;
@@ -1252,10 +2521,33 @@ endstream");
         let (input, ws2) = whitespace_and_comments(input)?;
         let (input, _def) = tag(b"obj")(input)?;
         // println!("Reached def");
+        // Having seen "obj", this can only be an object definition: errors from here on are not recoverable.
+        let glued = |input| {
+            if !lenient() {
+                return Err(nom::Err::Failure(SyntaxError::new(
+                    input,
+                    "whitespace between `obj` and the numbers",
+                )));
+            }
+            warn(input, "`obj` glued to a number");
+            Ok(())
+        };
+        if ws2.is_empty() {
+            glued(&start[start.len() - input.len() - b"obj".len()..])?;
+        }
         let (input, ws3) = whitespace_and_comments(input)?;
-        let (input, object) = object(input)?;
+        if ws3.is_empty() && input.first().is_some_and(|c| c.is_ascii_digit()) {
+            glued(input)?;
+        }
+        let (input, object) = cut(object)(input)?;
         let (input, ws4) = whitespace_and_comments(input)?;
-        let (input, _endobj) = tag(b"endobj")(input)?;
+        let missing_endobj = lenient() && !input.starts_with(b"endobj") && object_ends_here(input);
+        let input = if missing_endobj {
+            warn(input, "no `endobj`");
+            input
+        } else {
+            cut(expecting("`endobj`", tag(b"endobj")))(input)?.0
+        };
         // println!("Reached endobj");
         let ret = IndirectObjectDefinition {
             object_number: int1,
@@ -1265,16 +2557,35 @@ endstream");
             ws3: Cow::Borrowed(ws3),
             object,
             ws4: Cow::Borrowed(ws4),
+            missing_endobj,
+            span: Span::of(start, input),
         };
-        let mut out: Vec<u8> = vec![];
-        ret.serialize_to(&mut out).unwrap();
-        println!(
-            "Got an indirect object definition of {} bytes, with {} bytes left",
-            out.len(),
-            input.len()
-        );
+        diagnose(Level::Debug, || {
+            format!(
+                "Got an indirect object definition of {} bytes, with {} bytes left",
+                start.len() - input.len(),
+                input.len()
+            )
+        });
         Ok((input, ret))
     }
+
+    // Whether what follows an object definition without "endobj" is what would follow "endobj":
+    // the next object definition, the cross-reference table or trailer, or nothing.
+    fn object_ends_here(input: &[u8]) -> bool {
+        input.is_empty()
+            || [&b"xref"[..], b"trailer", b"startxref"]
+                .iter()
+                .any(|keyword| input.starts_with(keyword))
+            || tuple((
+                integer_without_sign,
+                whitespace_and_comments,
+                integer_without_sign,
+                whitespace_and_comments,
+                tag(b"obj"),
+            ))(input)
+            .is_ok()
+    }
     // >@indirect_object_definition
 
     // ===========
@@ -1283,7 +2594,7 @@ endstream");
     // @<object
     #[derive(Serialize, Deserialize, Debug)]
     pub enum Object<'a> {
-        Boolean(BooleanObject),
+        Boolean(BooleanObject, Span),
         #[serde(borrow)]
         Numeric(NumericObject<'a>),
         String(StringObject<'a>),
@@ -1291,25 +2602,51 @@ endstream");
         Array(ArrayObject<'a>),
         Dictionary(DictionaryObject<'a>),
         Stream(StreamObject<'a>),
-        Null,
+        Null(Span),
     }
     impl BinSerialize for Object<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
             match self {
-                Object::Boolean(b) => b.serialize_to(buf),
+                Object::Boolean(b, _) => b.serialize_to(buf),
                 Object::Numeric(n) => n.serialize_to(buf),
                 Object::String(s) => s.serialize_to(buf),
                 Object::Name(name) => name.serialize_to(buf),
                 Object::Array(arr) => arr.serialize_to(buf),
                 Object::Dictionary(dict) => dict.serialize_to(buf),
                 Object::Stream(stream) => stream.serialize_to(buf),
-                Object::Null => buf.write_all(b"null"),
+                Object::Null(_) => buf.write_all(b"null"),
+            }
+        }
+    }
+    impl Object<'_> {
+        /// Where this was parsed from.
+        pub fn span(&self) -> Span {
+            match self {
+                Object::Boolean(_, span) | Object::Null(span) => *span,
+                Object::Numeric(NumericObject::Integer(i)) => i.span,
+                Object::Numeric(NumericObject::Real(r)) => r.span,
+                Object::String(StringObject::Literal(s)) => s.span,
+                Object::String(StringObject::Hex(h)) => h.span,
+                Object::Name(name) => name.span,
+                Object::Array(arr) => arr.span,
+                Object::Dictionary(dict) => dict.span,
+                Object::Stream(stream) => stream.span,
+            }
+        }
+    }
+    impl Object<'_> {
+        // The value, if this is a numeric object.
+        fn as_number(&self) -> Option<f64> {
+            match self {
+                Object::Numeric(NumericObject::Integer(i)) => i.value().map(|v| v as f64),
+                Object::Numeric(NumericObject::Real(r)) => Some(r.to_f64()),
+                _ => None,
             }
         }
     }
 
     #[adorn(traceable_parser("object"))]
-    pub fn object(input: &[u8]) -> IResult<&[u8], Object> {
+    fn object(input: &[u8]) -> IResult<&[u8], Object> {
         // Indirect way of returning on empty input with right error type
         let (_, first) = take(1usize)(input)?;
         if first == b"[" {
@@ -1327,8 +2664,9 @@ endstream");
                     Object::String(StringObject::Hex(s))
                 })(input)
             } else {
+                let start = input;
                 let (input, dict) = object_dictionary(input)?;
-                match object_stream_after_dict(input) {
+                match object_stream_after_dict(input, &dict) {
                     Ok((input, rest_of_stream)) => Ok((
                         input,
                         Object::Stream(StreamObject {
@@ -1336,6 +2674,8 @@ endstream");
                             ws_and_comments: rest_of_stream.ws_and_comments,
                             eol_after_stream_begin: rest_of_stream.eol_after_stream_begin,
                             content: rest_of_stream.content,
+                            length: rest_of_stream.length,
+                            span: Span::of(start, input),
                         }),
                     )),
                     Err(_) => Ok((input, Object::Dictionary(dict))),
@@ -1343,9 +2683,11 @@ endstream");
             }
         } else {
             alt((
-                map(object_boolean, |b| Object::Boolean(b)),
+                map(spanned(object_boolean), |(b, span)| {
+                    Object::Boolean(b, span)
+                }),
                 map(object_numeric, |n| Object::Numeric(n)),
-                map(tag(b"null"), |_| Object::Null),
+                map(spanned(tag(b"null")), |(_, span)| Object::Null(span)),
             ))(input)
         }
     }
@@ -1364,9 +2706,56 @@ endstream");
             }
         }
     }
+    impl<'a> ObjectOrReference<'a> {
+        // The value, if this is a (direct) integer object.
+        fn as_integer(&self) -> Option<i64> {
+            match self {
+                ObjectOrReference::Object(Object::Numeric(NumericObject::Integer(i))) => i.value(),
+                _ => None,
+            }
+        }
+        // The name, if this is a (direct) name object.
+        fn as_name(&self) -> Option<&NameObject> {
+            match self {
+                ObjectOrReference::Object(Object::Name(n)) => Some(n),
+                _ => None,
+            }
+        }
+        // The array, if this is a (direct) array object.
+        fn as_array(&self) -> Option<&ArrayObject<'a>> {
+            match self {
+                ObjectOrReference::Object(Object::Array(a)) => Some(a),
+                _ => None,
+            }
+        }
+        // The dictionary, if this is a (direct) dictionary object.
+        fn as_dictionary(&self) -> Option<&DictionaryObject<'a>> {
+            match self {
+                ObjectOrReference::Object(Object::Dictionary(d)) => Some(d),
+                _ => None,
+            }
+        }
+        // The string, if this is a (direct) string object.
+        fn as_string(&self) -> Option<&StringObject<'a>> {
+            match self {
+                ObjectOrReference::Object(Object::String(s)) => Some(s),
+                _ => None,
+            }
+        }
+    }
+
+    impl ObjectOrReference<'_> {
+        /// Where this was parsed from.
+        pub fn span(&self) -> Span {
+            match self {
+                ObjectOrReference::Object(o) => o.span(),
+                ObjectOrReference::Reference(r) => r.span,
+            }
+        }
+    }
 
     #[adorn(traceable_parser("object_or_ref"))]
-    pub fn object_or_ref(input: &[u8]) -> IResult<&[u8], ObjectOrReference> {
+    fn object_or_ref(input: &[u8]) -> IResult<&[u8], ObjectOrReference> {
         alt((
             map(indirect_object_reference, |r| {
                 ObjectOrReference::Reference(r)
@@ -1381,6 +2770,7 @@ endstream");
     // ==================
     // @<body_part
     #[derive(Serialize, Deserialize, Debug)]
+    #[allow(clippy::large_enum_variant)]
     pub enum BodyPart<'a> {
         #[serde(borrow)]
         ObjDef(IndirectObjectDefinition<'a>),
@@ -1416,7 +2806,9 @@ endstream");
         nnnnnnnnnn: [u8; 10],
         ggggg: [u8; 5],
         n_or_f: CrossReferenceEntryInUse,
-        eol: [u8; 2],
+        eol: Vec<u8>, // 2 bytes, or 1 (CR or LF) when not strict
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for CrossReferenceEntry {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -1433,31 +2825,41 @@ endstream");
     }
     #[adorn(traceable_parser("cross_reference_subsection_entry"))]
     fn cross_reference_subsection_entry(input: &[u8]) -> IResult<&[u8], CrossReferenceEntry> {
+        let start = input;
         let (input, nnnnnnnnnn) = take_while_m_n(10, 10, is_digit)(input)?;
-
-        let (input, _sp) = take(1usize)(input)?;
-        assert_eq!(_sp, b" ");
-
-        let (input, ggggg) = digit1(input)?;
-        assert_eq!(ggggg.len(), 5);
-
-        let (input, _sp) = take(1usize)(input)?;
-        assert_eq!(_sp, b" ");
-
-        let (input, n_or_f) = take(1usize)(input)?;
-        assert!(n_or_f == b"n" || n_or_f == b"f");
-        let n_or_f = if n_or_f == b"n" {
-            CrossReferenceEntryInUse::InUse
+        // Having seen the 10-digit offset, this can only be an entry: errors from here on are not recoverable.
+        let (input, _sp) = cut(expecting("a space after the offset", tag(b" ")))(input)?;
+        let (input, ggggg) = cut(expecting(
+            "a 5-digit generation number",
+            take_while_m_n(5, 5, is_digit),
+        ))(input)?;
+        let (input, _sp) = cut(expecting("a space after the generation number", tag(b" ")))(input)?;
+        let (input, n_or_f) = cut(expecting(
+            "`n` or `f`",
+            alt((
+                map(tag(b"n"), |_| CrossReferenceEntryInUse::InUse),
+                map(tag(b"f"), |_| CrossReferenceEntryInUse::Free),
+            )),
+        ))(input)?;
+        let one_byte_eol =
+            (input.starts_with(b"\r") && !input.starts_with(b"\r\n")) || input.starts_with(b"\n");
+        let (input, eol) = if one_byte_eol && lenient() {
+            warn(
+                input,
+                "a 1-byte end-of-line marker in a cross-reference entry (should be 2 bytes)",
+            );
+            let (eol, input) = input.split_at(1);
+            (input, eol)
         } else {
-            CrossReferenceEntryInUse::Free
+            let (input, eol) = cut(expecting("a 2-byte end-of-line marker", take(2usize)))(input)?;
+            (input, eol)
         };
-
-        let (input, eol) = take(2usize)(input)?;
         let ret = CrossReferenceEntry {
             nnnnnnnnnn: nnnnnnnnnn.try_into().unwrap(),
             ggggg: ggggg.try_into().unwrap(),
             n_or_f,
-            eol: eol.try_into().unwrap(),
+            eol: eol.to_vec(),
+            span: Span::of(start, input),
         };
         Ok((input, ret))
     }
@@ -1468,6 +2870,8 @@ endstream");
         number_of_entries: Integer<'a>,   // How many objects this subsection is about
         ws: Cow<'a, [u8]>,                // After the first line (e.g. "28 5") of the subsection
         entries: Vec<CrossReferenceEntry>,
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for CrossReferenceSubsection<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -1486,18 +2890,19 @@ endstream");
     #[adorn(traceable_parser("cross_reference_subsection"))]
     fn cross_reference_subsection(input: &[u8]) -> IResult<&[u8], CrossReferenceSubsection> {
         map(
-            tuple((
+            spanned(tuple((
                 integer_without_sign,
                 tag(b" "),
                 integer_without_sign,
                 whitespace_and_comments,
                 many0(cross_reference_subsection_entry),
-            )),
-            |(n1, _sp, n2, ws, entries)| CrossReferenceSubsection {
+            ))),
+            |((n1, _sp, n2, ws, entries), span)| CrossReferenceSubsection {
                 first_object_number: n1,
                 number_of_entries: n2,
                 ws: Cow::Borrowed(ws),
                 entries,
+                span,
             },
         )(input)
     }
@@ -1507,6 +2912,8 @@ endstream");
         ws1: Cow<'a, [u8]>, // The newline after "xref"
         subsections: Vec<CrossReferenceSubsection<'a>>,
         ws2: Cow<'a, [u8]>, // At the very end
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for CrossReferenceTable<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -1520,21 +2927,24 @@ endstream");
     }
     #[adorn(traceable_parser("cross_reference_table"))]
     fn cross_reference_table(input: &[u8]) -> IResult<&[u8], CrossReferenceTable> {
-        println!(
-            "Trying to parse cross-reference table from {:?}",
-            &input[..std::cmp::min(input.len(), 50)]
-        );
+        diagnose(Level::Debug, || {
+            format!(
+                "Trying to parse cross-reference table from {:?}",
+                &input[..std::cmp::min(input.len(), 50)]
+            )
+        });
         map(
-            tuple((
+            spanned(tuple((
                 tag(b"xref"),
                 whitespace_and_comments,
                 many1(cross_reference_subsection),
                 whitespace_and_comments,
-            )),
-            |(_xref, ws1, subsections, ws2)| CrossReferenceTable {
+            ))),
+            |((_xref, ws1, subsections, ws2), span)| CrossReferenceTable {
                 ws1: Cow::Borrowed(ws1),
                 subsections,
                 ws2: Cow::Borrowed(ws2),
+                span,
             },
         )(input)
     }
@@ -1544,6 +2954,8 @@ endstream");
         ws3: Cow<'a, [u8]>, // After "startxref", before last penultimate line
         last_crossref_offset: Integer<'a>, // Byte offset of the last cross-reference section
         eol_marker: Cow<'a, [u8]>, // EOL after the byte offset
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for StartxrefOffsetEof<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -1580,17 +2992,18 @@ endstream");
     #[adorn(traceable_parser("startxref_offset_eof"))]
     fn startxref_offset_eof(input: &[u8]) -> IResult<&[u8], StartxrefOffsetEof> {
         map(
-            tuple((
+            spanned(tuple((
                 tag(b"startxref"),
                 whitespace_and_comments,
                 integer_without_sign,
                 eol_markers_after_offset,
                 tag(b"%%EOF"),
-            )),
-            |(_startxref, ws3, offset, eol, _eof)| StartxrefOffsetEof {
+            ))),
+            |((_startxref, ws3, offset, eol, _eof), span)| StartxrefOffsetEof {
                 ws3: Cow::Borrowed(ws3),
                 last_crossref_offset: offset,
                 eol_marker: Cow::Borrowed(eol),
+                span,
             },
         )(input)
     }
@@ -1611,6 +3024,8 @@ endstream");
         #[serde(borrow)]
         dict: DictionaryObject<'a>, // The actual trailer dictionary
         ws2: Cow<'a, [u8]>, // After dict, before "startxref"
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for Trailer<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -1623,16 +3038,17 @@ endstream");
     #[adorn(traceable_parser("trailer"))]
     fn trailer(input: &[u8]) -> IResult<&[u8], Trailer> {
         map(
-            tuple((
+            spanned(tuple((
                 tag(b"trailer"),
                 whitespace_and_comments,
                 object_dictionary,
                 whitespace_and_comments,
-            )),
-            |(_trailer, ws1, dict, ws2)| Trailer {
+            ))),
+            |((_trailer, ws1, dict, ws2), span)| Trailer {
                 ws1: Cow::Borrowed(ws1),
                 dict,
                 ws2: Cow::Borrowed(ws2),
+                span,
             },
         )(input)
     }
@@ -1642,6 +3058,8 @@ endstream");
         cross_reference_table: CrossReferenceTable<'a>,
         #[serde(borrow)]
         trailer: Trailer<'a>,
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for CrossReferenceTableAndTrailer<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -1651,7 +3069,8 @@ endstream");
     }
     fn cross_reference_table_and_trailer(
         input: &[u8],
-    ) -> IResult<&[u8], CrossReferenceTableAndTrailer> {
+    ) -> IResult<&[u8], CrossReferenceTableAndTrailer<'_>> {
+        let start = input;
         let (input, cross_reference_table) = cross_reference_table(input)?;
         let (input, trailer) = trailer(input)?;
         Ok((
@@ -1659,6 +3078,7 @@ endstream");
             CrossReferenceTableAndTrailer {
                 cross_reference_table,
                 trailer,
+                span: Span::of(start, input),
             },
         ))
     }
@@ -1671,6 +3091,13 @@ endstream");
         pub body: Vec<BodyPart<'a>>,
         cross_reference_table_and_trailer: Option<CrossReferenceTableAndTrailer<'a>>,
         startxref_offset_eof: StartxrefOffsetEof<'a>,
+        // If the section has a cross-reference stream (in `body`) instead of a table, its decoded entries,
+        // or what was expected but not found when decoding it.
+        // This is derived from `body`, so it's not written out by `serialize_to`, nor read back from JSON.
+        #[serde(skip_deserializing)]
+        cross_reference_stream: Option<Result<CrossReferenceStream, String>>,
+        #[serde(default)]
+        span: Span,
     }
     impl BinSerialize for BodyCrossrefTrailer<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
@@ -1685,11 +3112,14 @@ endstream");
     }
     #[adorn(traceable_parser("body_crossref_trailer"))]
     fn body_crossref_trailer(input: &[u8]) -> IResult<&[u8], BodyCrossrefTrailer> {
-        println!(
-            "Trying to parse b/c/t section from {} bytes starting {:?}",
-            input.len(),
-            &input[..std::cmp::min(input.len(), 20)]
-        );
+        diagnose(Level::Debug, || {
+            format!(
+                "Trying to parse b/c/t section from {} bytes starting {:?}",
+                input.len(),
+                &input[..std::cmp::min(input.len(), 20)]
+            )
+        });
+        let start = input;
         let mut input = input;
         let mut body = vec![];
         loop {
@@ -1705,29 +3135,46 @@ endstream");
                     // );
                     input = left;
                     match part {
-                        BodyPart::Whitespace(w) if w.len() == 0 => break,
+                        BodyPart::Whitespace(w) if w.is_empty() => break,
                         x => body.push(x),
                     }
                 }
+                // A malformed object definition: no point looking for a cross-reference table.
+                Err(e @ nom::Err::Failure(_)) => return Err(e),
                 Err(_) => break,
             }
         }
-        println!("{} objects; {} bytes left.", body.len(), input.len());
+        diagnose(Level::Info, || {
+            format!("{} body parts; {} bytes left.", body.len(), input.len())
+        });
 
         // Two options: Either a cross-reference table, starting with "xref", or just the "startxref"...%%EOF
         let (input, cross_reference_table_and_trailer) =
             opt(cross_reference_table_and_trailer)(input)?;
-        println!(
-            "{} bytes left after cross-reference table and trailer",
-            input.len(),
-        );
+        diagnose(Level::Debug, || {
+            format!(
+                "{} bytes left after cross-reference table and trailer",
+                input.len()
+            )
+        });
         let (input, startxref_offset_eof) = startxref_offset_eof(input)?;
+        let cross_reference_stream = match cross_reference_table_and_trailer {
+            Some(_) => None,
+            None => body.iter().rev().find_map(|part| match part {
+                BodyPart::ObjDef(def) if xref_stream::is_cross_reference_stream(def) => {
+                    Some(CrossReferenceStream::from_definition(def))
+                }
+                _ => None,
+            }),
+        };
         Ok((
             input,
             BodyCrossrefTrailer {
                 body,
                 cross_reference_table_and_trailer,
                 startxref_offset_eof,
+                cross_reference_stream,
+                span: Span::of(start, input),
             },
         ))
     }
@@ -1736,45 +3183,359 @@ endstream");
     // @<pdf_file
     #[derive(Serialize, Deserialize)]
     pub struct PdfFile<'a> {
+        // Junk before the `%PDF` line (accepted when not strict).
+        #[serde(default)]
+        before_header: Cow<'a, [u8]>,
         header: Cow<'a, [u8]>,
         #[serde(borrow)]
         pub body_crossref_trailers: Vec<BodyCrossrefTrailer<'a>>,
+        // Whitespace and comments after the final %%EOF (or, when not strict, anything).
         post_eof: Cow<'a, [u8]>,
+        // What parsing accepted despite the spec, if it was not strict.
+        // Like `cross_reference_stream`, this is not read back from JSON.
+        #[serde(skip_deserializing)]
+        warnings: Vec<ParseWarning>,
+        // Whether this was parsed, so that every span says where its node is in the file (which is
+        // not so for one deserialized from JSON, perhaps edited, or made by `repair`).
+        #[serde(skip)]
+        parsed: bool,
     }
     impl BinSerialize for PdfFile<'_> {
         fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+            buf.write_all(&self.before_header)?;
             buf.write_all(&self.header)?;
+            // (Can happen with a PdfFile deserialized from JSON.)
+            if self.body_crossref_trailers.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "A PDF file needs at least one (body, crossref, trailer) section",
+                ));
+            }
             for bct in &self.body_crossref_trailers {
                 bct.serialize_to(buf)?;
             }
-            assert!(self.body_crossref_trailers.len() > 0);
-            assert!(buf.ends_with(b"%%EOF"));
             buf.write_all(&self.post_eof)
         }
     }
 
     #[adorn(traceable_parser("pdf_file"))]
-    pub fn pdf_file(input: &[u8]) -> IResult<&[u8], PdfFile> {
+    fn pdf_file(input: &[u8]) -> IResult<&[u8], PdfFile> {
+        // "Acrobat viewers require only that the header appear somewhere within the first 1024
+        // bytes of the file" (Implementation note 13 in the PDF 1.7 reference).
+        let header_at = input
+            .windows(b"%PDF-".len())
+            .take(1024)
+            .position(|w| w == b"%PDF-");
+        let (before_header, input) = match header_at {
+            Some(i) if i > 0 && lenient() && !recognize_header(input) => {
+                warn(input, "junk before `%PDF`");
+                input.split_at(i)
+            }
+            _ => input.split_at(0),
+        };
         let (input, header) = whitespace_and_comments(input)?;
-        println!("{} bytes header, {} bytes left.", header.len(), input.len());
+        diagnose(Level::Debug, || {
+            format!("{} bytes header, {} bytes left.", header.len(), input.len())
+        });
 
         let (input, bcts) = many1(body_crossref_trailer)(input)?;
-        println!("After {} sections: {} bytes left.", bcts.len(), input.len());
+        diagnose(Level::Info, || {
+            format!("After {} sections: {} bytes left.", bcts.len(), input.len())
+        });
 
-        // Ideally, the remaining "input" won't contain any "%%EOF"
-        let foo = input
+        // Ideally, the remaining "input" won't contain any "%%EOF".
+        // If it does, there's a section we failed to parse: report why.
+        if input
             .windows(b"%%EOF".len())
-            .position(|window| window == b"%%EOF");
-        assert_eq!(foo, None);
-        let (input, final_ws) = whitespace_and_comments(input)?;
+            .any(|window| window == b"%%EOF")
+        {
+            body_crossref_trailer(input)?;
+        }
+        let (rest, final_ws) = whitespace_and_comments(input)?;
+        let (input, post_eof) = if !rest.is_empty() && lenient() {
+            warn(rest, "data after the final %%EOF");
+            (&input[input.len()..], input)
+        } else {
+            (rest, final_ws)
+        };
         Ok((
             input,
             PdfFile {
+                before_header: Cow::Borrowed(before_header),
                 header: Cow::Borrowed(header),
                 body_crossref_trailers: bcts,
-                post_eof: Cow::Borrowed(final_ws),
+                post_eof: Cow::Borrowed(post_eof),
+                warnings: vec![],
+                parsed: true,
             },
         ))
     }
+
+    // Whether `input` starts with the `%PDF` line (possibly after other whitespace and comments).
+    fn recognize_header(input: &[u8]) -> bool {
+        whitespace_and_comments(input)
+            .is_ok_and(|(_, header)| header.windows(5).any(|w| w == b"%PDF-"))
+    }
+
+    /// Parses `input` as a PDF file. All of the input must be consumed.
+    /// A stream's data is `/Length` bytes long (looked up in the cross-reference table if the length
+    /// is indirect), unless that is missing or wrong, in which case it runs up to the first "endstream".
+    pub fn parse_pdf(input: &[u8]) -> Result<PdfFile<'_>, ParseError> {
+        parse_pdf_with_options(input, &ParseOptions::default())
+    }
+
+    /// Like `parse_pdf`, with `options`. The warnings, if not strict, are in `PdfFile::warnings`.
+    pub fn parse_pdf_with_options<'a>(
+        input: &'a [u8],
+        options: &ParseOptions,
+    ) -> Result<PdfFile<'a>, ParseError> {
+        let context = || ParseContext {
+            input_len: Some(input.len()),
+            ..ParseContext::new(options)
+        };
+        // Read ahead under the same options; what it warns about, the parse below warns about too.
+        let (indirect_lengths, _) =
+            with_context(context(), || document::file_indirect_lengths(input));
+        let context = ParseContext {
+            indirect_lengths,
+            ..context()
+        };
+        let (parsed, context) = with_context(context, || pdf_file(input));
+        let (remaining, mut parsed) = parsed.map_err(|e| ParseError::from_nom(input, e))?;
+        parsed.warnings = context.warnings.unwrap_or_default();
+        if !remaining.is_empty() {
+            return Err(ParseError {
+                offset: input.len() - remaining.len(),
+                rule: "pdf_file",
+                expected: "nothing after the final %%EOF (other than whitespace and comments)"
+                    .to_string(),
+            });
+        }
+        Ok(parsed)
+    }
+
+    impl PdfFile<'_> {
+        /// What parsing accepted despite the spec (only when it was not strict).
+        pub fn warnings(&self) -> &[ParseWarning] {
+            &self.warnings
+        }
+    }
+
+    #[cfg(test)]
+    // Returns `haystack` with the first occurrence of `from` replaced by `to`.
+    fn replace_first(haystack: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+        let i = haystack
+            .windows(from.len())
+            .position(|w| w == from)
+            .unwrap();
+        [&haystack[..i], to, &haystack[i + from.len()..]].concat()
+    }
+
+    #[test]
+    fn test_pdf_file_round_trip() {
+        let input = include_bytes!("test_simple.pdf");
+        let mut out: Vec<u8> = vec![];
+        parse_pdf(input).unwrap().serialize_to(&mut out).unwrap();
+        assert_eq!(out, input);
+    }
+
+    #[test]
+    fn test_pdf_file_bad_xref_generation() {
+        let input = include_bytes!("test_simple.pdf");
+        let bad = replace_first(input, b"0000000015 00000 n", b"0000000015 0000 n ");
+        let err = parse_pdf(&bad).err().unwrap();
+        assert_eq!(err.rule, "cross_reference_subsection_entry");
+        assert_eq!(err.expected, "a 5-digit generation number");
+        let entry_offset = bad.windows(10).position(|w| w == b"0000000015").unwrap();
+        assert_eq!(err.offset, entry_offset + 11);
+    }
+
+    #[test]
+    fn test_pdf_file_stray_eof() {
+        let input = include_bytes!("test_simple.pdf");
+        let bad = [&input[..], b"junk\n%%EOF\n"].concat();
+        let err = parse_pdf(&bad).err().unwrap();
+        assert_eq!(err.offset, input.len());
+        assert_eq!(err.rule, "startxref_offset_eof");
+    }
+
+    #[test]
+    fn test_pdf_file_unclosed_string() {
+        let input = include_bytes!("test_simple.pdf");
+        let bad = replace_first(input, b"(Hello)", b"((Hello)");
+        let err = parse_pdf(&bad).err().unwrap();
+        assert_eq!(err.rule, "object");
+        assert_eq!(err.expected, "1 more `)` to close the literal string");
+    }
+
+    #[test]
+    fn test_pdf_file_spans() {
+        let input = include_bytes!("test_simple.pdf");
+        let file = parse_pdf(input).unwrap();
+        let at = |span: Span| &input[span.start..span.end];
+        let section = &file.body_crossref_trailers[0];
+        // (The final newline, after `%%EOF`, is not part of the section.)
+        let end = input.len() - 1;
+        assert_eq!(section.span(), Span { start: 15, end });
+        let defs: Vec<_> = section
+            .body
+            .iter()
+            .filter_map(|part| match part {
+                BodyPart::ObjDef(def) => Some(def),
+                BodyPart::Whitespace(_) => None,
+            })
+            .collect();
+        assert_eq!(defs[0].span(), Span { start: 15, end: 63 });
+        assert!(at(defs[2].span()).starts_with(b"3 0 obj"));
+        assert!(at(defs[2].span()).ends_with(b"endobj"));
+        assert_eq!(at(defs[0].object_number.span()), b"1");
+        let page = match &defs[2].object {
+            Object::Dictionary(d) => d,
+            o => panic!("not a dictionary: {:?}", o),
+        };
+        assert!(at(page.span()).starts_with(b"<< /Type /Page "));
+        let media_box = page.entries().find(|kv| kv.key.is(b"MediaBox")).unwrap();
+        assert_eq!(at(media_box.span), b"/MediaBox [0 0 612 792]");
+        assert_eq!(at(media_box.key.span()), b"/MediaBox");
+        assert_eq!(at(media_box.value.span()), b"[0 0 612 792]");
+        assert_eq!(at(page.get(b"Parent").unwrap().span()), b"2 0 R");
+        match &defs[3].object {
+            Object::Stream(s) => {
+                assert!(at(s.span()).starts_with(b"<< /Length 43 >>\nstream\n"));
+                assert!(at(s.span()).ends_with(b"ET\nendstream"));
+            }
+            o => panic!("not a stream: {:?}", o),
+        }
+
+        let table = &section.cross_reference_table_and_trailer.as_ref().unwrap();
+        let entries = &table.cross_reference_table.subsections[0].entries;
+        assert_eq!(at(entries[1].span), b"0000000015 00000 n \n");
+        assert_eq!(
+            at(table.trailer.dict.span()),
+            b"<< /Size 7 /Root 1 0 R /Info 6 0 R >>"
+        );
+        assert_eq!(
+            at(section.startxref_offset_eof.span),
+            b"startxref\n468\n%%EOF"
+        );
+
+        // The spans are in the JSON too.
+        let json = serde_json::to_string(&file).unwrap();
+        assert!(json.contains(r#""span":{"start":15,"end":63}"#));
+    }
+
+    #[test]
+    fn test_pdf_file_lenient() {
+        let input = include_bytes!("test_simple.pdf");
+        let lenient = ParseOptions {
+            strict: false,
+            ..ParseOptions::default()
+        };
+        let deviations: [(&[u8], &[u8]); 7] = [
+            (b"%PDF-1.4", b"junk\n%PDF-1.4"),
+            (b"stream\n", b"stream\r"),
+            (b"/Helvetica >>\nendobj\n", b"/Helvetica >>\n"),
+            (b"6 0 obj", b"6 0obj"),
+            (b"/Author (", b"/Author name, LLC ("),
+            (b"0000000015 00000 n \n", b"0000000015 00000 n\n"),
+            (b"%%EOF\n", b"%%EOF\njunk after\n"),
+        ];
+        let mut bad = input.to_vec();
+        for (from, to) in deviations {
+            // Each of these is an error when strict.
+            let one = replace_first(input, from, to);
+            assert!(parse_pdf(&one).is_err(), "{:?}", std::str::from_utf8(to));
+            assert_eq!(
+                parse_pdf_with_options(&one, &lenient)
+                    .unwrap()
+                    .warnings()
+                    .len(),
+                1
+            );
+            bad = replace_first(&bad, from, to);
+        }
+
+        let file = parse_pdf_with_options(&bad, &lenient).unwrap();
+        let mut out: Vec<u8> = vec![];
+        file.serialize_to(&mut out).unwrap();
+        assert_eq!(out, bad);
+        let at = |s: &[u8]| bad.windows(s.len()).position(|w| w == s).unwrap();
+        let warnings: Vec<(usize, &str)> = file
+            .warnings()
+            .iter()
+            .map(|w| (w.offset, &w.problem[..]))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (0, "junk before `%PDF`"),
+                (
+                    at(b"stream\r") + 6,
+                    "a lone CR after `stream` (should be CRLF or LF)"
+                ),
+                (at(b"6 0obj"), "no `endobj`"),
+                (at(b"0obj") + 1, "`obj` glued to a number"),
+                (at(b"/Author"), "a space in a dictionary key"),
+                (
+                    at(b"0000000015 00000 n\n") + 18,
+                    "a 1-byte end-of-line marker in a cross-reference entry (should be 2 bytes)"
+                ),
+                (at(b"junk after"), "data after the final %%EOF"),
+            ]
+        );
+        assert_eq!(
+            file.warnings()[0].to_string(),
+            "At byte 0: junk before `%PDF`"
+        );
+    }
+
+    #[test]
+    fn test_pdf_file_in_another_context() {
+        // Parsing strictly in the middle of a lenient parse: the whole of it (including the
+        // cross-reference table read first for indirect lengths) is strict, and warns nothing.
+        let input = include_bytes!("test_simple.pdf");
+        let one = replace_first(input, b"0000000015 00000 n \n", b"0000000015 00000 n\n");
+        let lenient = ParseOptions {
+            strict: false,
+            ..ParseOptions::default()
+        };
+        let (parsed, outer) = with_context(ParseContext::new(&lenient), || parse_pdf(&one));
+        assert!(parsed.is_err());
+        assert_eq!(outer.warnings.map(|w| w.len()), Some(0));
+    }
+    // >@pdf_file
+
+    // @<submodules
+    // Things built on top of the parsing above.
+    mod cmap;
+    mod content_stream;
+    mod document;
+    mod duplicate_keys;
+    mod encodings;
+    mod encryption;
+    mod filters;
+    mod fonts;
+    mod object_stream;
+    mod pages;
+    mod repair;
+    mod standard_fonts;
+    mod text;
+    mod text_string;
+    mod xref_stream;
+    mod xref_validation;
+    pub use cmap::{parse_cmap, CMap};
+    pub use content_stream::{
+        parse_content_stream, ContentStream, ContentStreamPart, InlineImage, Operation,
+    };
+    pub use document::{Document, XrefEntry};
+    pub use encryption::EncryptionError;
+    pub use filters::DecodeError;
+    pub use object_stream::{CompressedObject, ObjectStream};
+    pub use pages::{Page, PageTree, PageTreeProblem};
+    pub use repair::repair;
+    pub use text::{PositionedText, TextError};
+    pub use text_string::decode_text_string;
+    pub use xref_stream::{CrossReferenceStream, CrossReferenceStreamSubsection};
+    pub use xref_validation::XrefProblem;
+    // >@submodules
 }
-// >@pdf_file
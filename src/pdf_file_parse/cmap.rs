@@ -0,0 +1,396 @@
+// ================================
+// 9.7.5 CMaps
+// ================================
+// A CMap says how a string splits into character codes, and what each code stands for. Its
+// codespace ranges give the codes' lengths: a code is as many bytes as the range it falls in,
+// and ranges of different lengths can be mixed (9.7.6.2), as in Shift-JIS. A composite font's
+// /Encoding CMap maps codes to CIDs (`cidchar`, `cidrange`); a /ToUnicode CMap maps them to
+// Unicode text, as UTF-16BE (`bfchar`, `bfrange`; 9.10.3). A CMap can also build on another,
+// named by `usecmap` (or by /UseCMap in its stream dictionary).
+// A CMap is a PostScript program, but only a few of its operators matter here, and the tokens
+// are those of PDF objects: strings, names, numbers, arrays and dictionaries. Procedures (in
+// braces) are skipped over.
+
+use super::content_stream::is_regular_char;
+use super::encodings::glyph_name_text;
+use super::*;
+
+/// A parsed CMap: its codespace ranges, and the codes it maps to Unicode text or to CIDs.
+#[derive(Debug, Clone, Default)]
+pub struct CMap {
+    /// /CMapName, if it's defined.
+    pub name: Option<Vec<u8>>,
+    /// The name given to `usecmap`, if any: the CMap that this one builds on (see `inherit`).
+    pub use_cmap: Option<Vec<u8>>,
+    // (low, high), of the same length; a code is in the range if each byte is between the
+    // corresponding bytes of `low` and `high`.
+    codespace: Vec<(Vec<u8>, Vec<u8>)>,
+    unicode: BTreeMap<Vec<u8>, String>,
+    cids: BTreeMap<Vec<u8>, u32>,
+    // (low, high, the CID for `low`). Kept as ranges, since one can cover every 2-byte code.
+    cid_ranges: Vec<(Vec<u8>, Vec<u8>, u32)>,
+    parent: Option<Box<CMap>>,
+}
+
+// A token of a CMap.
+enum CMapToken<'a> {
+    Operand(Object<'a>),
+    Operator(&'a [u8]),
+    Procedure,
+}
+
+/// Parses `data` (the decoded data of a CMap stream).
+pub fn parse_cmap(data: &[u8]) -> Result<CMap, ParseError> {
+    with_own_context(data, || cmap(data)).map_err(|e| ParseError::from_nom(data, e))
+}
+
+fn cmap(mut input: &[u8]) -> Result<CMap, nom::Err<SyntaxError<&[u8]>>> {
+    let mut cmap = CMap::default();
+    let mut operands = vec![];
+    loop {
+        let (rest, _) = whitespace_and_comments(input)?;
+        if rest.is_empty() {
+            return Ok(cmap);
+        }
+        let (rest, token) = cmap_token(rest)?;
+        match token {
+            CMapToken::Operand(object) => operands.push(object),
+            CMapToken::Operator(operator) => {
+                cmap.operator(operator, &operands);
+                operands.clear();
+            }
+            CMapToken::Procedure => {}
+        }
+        input = rest;
+    }
+}
+
+#[adorn(traceable_parser("cmap_token"))]
+fn cmap_token(input: &[u8]) -> IResult<&[u8], CMapToken> {
+    alt((
+        map(object, CMapToken::Operand),
+        map(cmap_procedure, |_| CMapToken::Procedure),
+        map(
+            expecting("an operand or an operator", take_while1(is_regular_char)),
+            CMapToken::Operator,
+        ),
+    ))(input)
+}
+
+// A procedure: tokens in braces (which can have procedures in them).
+fn cmap_procedure(input: &[u8]) -> IResult<&[u8], ()> {
+    let (mut input, _) = tag(b"{")(input)?;
+    loop {
+        let (rest, _) = whitespace_and_comments(input)?;
+        if let Some(rest) = rest.strip_prefix(b"}") {
+            return Ok((rest, ()));
+        }
+        let (rest, _) = cut(cmap_token)(rest)?;
+        input = rest;
+    }
+}
+
+// The value of a code, as a big-endian number.
+fn code_value(code: &[u8]) -> u32 {
+    code.iter()
+        .fold(0, |value, &byte| value << 8 | u32::from(byte))
+}
+
+// The text for the destination of a `bfchar` or `bfrange` entry: UTF-16BE, or a glyph name.
+fn destination_text(object: &Object) -> Option<String> {
+    match object {
+        Object::String(s) => Some(utf16_be(&s.decoded_bytes())),
+        Object::Name(name) => glyph_name_text(&name.decoded_bytes()),
+        _ => None,
+    }
+}
+
+// Decodes UTF-16BE text, with U+FFFD for anything that isn't valid.
+fn utf16_be(bytes: &[u8]) -> String {
+    String::from_utf16_lossy(&utf16_units(bytes))
+}
+
+fn utf16_units(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+        .collect()
+}
+
+impl CMap {
+    /// The predefined CMap called `name`, if it's one of those known here: Identity-H and
+    /// Identity-V, which map each 2-byte code to the CID with the same value.
+    pub fn predefined(name: &[u8]) -> Option<CMap> {
+        if name != b"Identity-H" && name != b"Identity-V" {
+            return None;
+        }
+        Some(CMap {
+            name: Some(name.to_vec()),
+            codespace: vec![(vec![0x00, 0x00], vec![0xFF, 0xFF])],
+            cid_ranges: vec![(vec![0x00, 0x00], vec![0xFF, 0xFF], 0)],
+            ..CMap::default()
+        })
+    }
+
+    /// Makes `parent` the CMap that this one builds on (the one that `use_cmap` names): its
+    /// codespace ranges are this one's too, and its mappings are used for the codes that this
+    /// one does not map.
+    pub fn inherit(&mut self, parent: CMap) {
+        self.parent = Some(Box::new(parent));
+    }
+
+    /// The codespace ranges, as (low, high): those of this CMap, then those it inherits.
+    pub fn codespace_ranges(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        let mut ranges: Vec<_> = self
+            .codespace
+            .iter()
+            .map(|(low, high)| (&low[..], &high[..]))
+            .collect();
+        if let Some(parent) = &self.parent {
+            ranges.extend(parent.codespace_ranges());
+        }
+        ranges.into_iter()
+    }
+
+    /// The length of the code that `bytes` starts with: that of the codespace range it falls
+    /// in. If there's none, that of the shortest range that its first byte is in, or else 1.
+    pub fn code_length(&self, bytes: &[u8]) -> usize {
+        let in_range = |low: &[u8], high: &[u8], n: usize| {
+            bytes.len() >= n && (0..n).all(|i| low[i] <= bytes[i] && bytes[i] <= high[i])
+        };
+        let ranges: Vec<_> = self.codespace_ranges().collect();
+        let shortest = |n: fn(&[u8]) -> usize| {
+            ranges
+                .iter()
+                .filter(|(low, high)| !low.is_empty() && in_range(low, high, n(low)))
+                .map(|(low, _)| low.len())
+                .min()
+        };
+        let length = shortest(|low| low.len()).or_else(|| shortest(|_| 1));
+        length.unwrap_or(1).clamp(1, bytes.len().max(1))
+    }
+
+    /// The codes in string `bytes`, per the codespace ranges.
+    pub fn codes<'s>(&'s self, mut bytes: &'s [u8]) -> impl Iterator<Item = &'s [u8]> + 's {
+        std::iter::from_fn(move || {
+            if bytes.is_empty() {
+                return None;
+            }
+            let (code, rest) = bytes.split_at(self.code_length(bytes));
+            bytes = rest;
+            Some(code)
+        })
+    }
+
+    /// The Unicode text that `code` maps to, if any.
+    pub fn unicode(&self, code: &[u8]) -> Option<&str> {
+        match self.unicode.get(code) {
+            Some(text) => Some(text),
+            None => self.parent.as_ref()?.unicode(code),
+        }
+    }
+
+    /// The CID that `code` maps to, if any.
+    pub fn cid(&self, code: &[u8]) -> Option<u32> {
+        if let Some(&cid) = self.cids.get(code) {
+            return Some(cid);
+        }
+        // (Later ranges win, but not one whose CIDs would go past the largest there can be.)
+        let cid = self
+            .cid_ranges
+            .iter()
+            .rev()
+            .filter(|(low, high, _)| {
+                low.len() == code.len() && &low[..] <= code && code <= &high[..]
+            })
+            .find_map(|(low, _, cid)| cid.checked_add(code_value(code) - code_value(low)));
+        match cid {
+            Some(cid) => Some(cid),
+            None => self.parent.as_ref()?.cid(code),
+        }
+    }
+
+    // Does what `operator` does with `operands` (all those since the last operator), if it
+    // matters here.
+    fn operator(&mut self, operator: &[u8], operands: &[Object]) {
+        let bytes = |object: &Object| match object {
+            Object::String(s) => Some(s.decoded_bytes()),
+            _ => None,
+        };
+        // The codes from `low` to `high` (which have the same length).
+        let range = |low: &Object, high: &Object| {
+            let (low, high) = (bytes(low)?, bytes(high)?);
+            (low.len() == high.len() && (1..=4).contains(&low.len()) && low <= high)
+                .then_some((low, high))
+        };
+        let integer = |object: &Object| match object {
+            Object::Numeric(NumericObject::Integer(i)) => i.value().and_then(|v| v.try_into().ok()),
+            _ => None,
+        };
+        match operator {
+            b"def" => {
+                if let [Object::Name(key), Object::Name(value)] = operands {
+                    if key.is(b"CMapName") {
+                        self.name = Some(value.decoded_bytes());
+                    }
+                }
+            }
+            b"usecmap" => {
+                if let Some(Object::Name(name)) = operands.last() {
+                    self.use_cmap = Some(name.decoded_bytes());
+                }
+            }
+            b"endcodespacerange" => {
+                for entry in operands.chunks_exact(2) {
+                    if let Some(range) = range(&entry[0], &entry[1]) {
+                        self.codespace.push(range);
+                    }
+                }
+            }
+            b"endbfchar" => {
+                for entry in operands.chunks_exact(2) {
+                    if let (Some(code), Some(text)) =
+                        (bytes(&entry[0]), destination_text(&entry[1]))
+                    {
+                        self.unicode.insert(code, text);
+                    }
+                }
+            }
+            b"endbfrange" => {
+                for entry in operands.chunks_exact(3) {
+                    let Some((low, high)) = range(&entry[0], &entry[1]) else {
+                        continue;
+                    };
+                    let length = low.len();
+                    // (Capped, lest a bad range take forever.)
+                    let codes = (code_value(&low)..=code_value(&high)).take(0x10000);
+                    for (i, value) in codes.enumerate() {
+                        let code = value.to_be_bytes()[4 - length..].to_vec();
+                        // An array has the text for each code; a string is the text for the
+                        // first, and the others' differ by their last character.
+                        let text = match &entry[2] {
+                            Object::Array(texts) => match texts.elements().nth(i) {
+                                Some(ObjectOrReference::Object(text)) => destination_text(text),
+                                _ => None,
+                            },
+                            Object::String(s) => {
+                                let mut units = utf16_units(&s.decoded_bytes());
+                                if let Some(last) = units.last_mut() {
+                                    *last = last.wrapping_add(i as u16);
+                                }
+                                Some(String::from_utf16_lossy(&units))
+                            }
+                            _ => None,
+                        };
+                        if let Some(text) = text {
+                            self.unicode.insert(code, text);
+                        }
+                    }
+                }
+            }
+            b"endcidchar" => {
+                for entry in operands.chunks_exact(2) {
+                    if let (Some(code), Some(cid)) = (bytes(&entry[0]), integer(&entry[1])) {
+                        self.cids.insert(code, cid);
+                    }
+                }
+            }
+            b"endcidrange" => {
+                for entry in operands.chunks_exact(3) {
+                    if let (Some((low, high)), Some(cid)) =
+                        (range(&entry[0], &entry[1]), integer(&entry[2]))
+                    {
+                        self.cid_ranges.push((low, high, cid));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn test_parse_cmap() {
+    let cmap = parse_cmap(
+        b"%!PS-Adobe-3.0 Resource-CMap
+/CIDInit /ProcSet findresource begin
+12 dict begin
+begincmap
+/CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def
+/CMapName /Adobe-Identity-UCS def
+/CMapType 2 def
+1 begincodespacerange <0000> <FFFF> endcodespacerange
+3 beginbfchar
+<0003> <0020>
+<0011> <D83DDE00>
+<0012> /f_i
+endbfchar
+2 beginbfrange
+<0024> <0026> <0041>
+<00FE> <00FF> [<00660066> <006600660069>]
+endbfrange
+endcmap
+CMapName currentdict /CMap defineresource pop
+end
+end",
+    )
+    .unwrap();
+    assert_eq!(cmap.name.as_deref(), Some(&b"Adobe-Identity-UCS"[..]));
+    let text = |code: &[u8]| cmap.unicode(code);
+    assert_eq!(text(&[0x00, 0x03]), Some(" "));
+    assert_eq!(text(&[0x00, 0x11]), Some("\u{1F600}"));
+    assert_eq!(text(&[0x00, 0x12]), Some("fi"));
+    assert_eq!(text(&[0x00, 0x25]), Some("B"));
+    assert_eq!(text(&[0x00, 0x26]), Some("C"));
+    assert_eq!(text(&[0x00, 0x27]), None);
+    assert_eq!(text(&[0x00, 0xFF]), Some("ffi"));
+    assert_eq!(text(&[0x26]), None);
+    assert_eq!(cmap.cid(&[0x00, 0x26]), None);
+
+    // A procedure, and an error.
+    assert!(parse_cmap(b"/Proc { 1 { pop } repeat } bind def").is_ok());
+    let e = parse_cmap(b"1 begincodespacerange <00> <FF> endcodespacerange )").unwrap_err();
+    assert_eq!(
+        (e.offset, e.expected.as_str()),
+        (50, "an operand or an operator")
+    );
+}
+
+#[test]
+fn test_cmap_codespace_and_cids() {
+    // Like Shift-JIS: single bytes, and two-byte codes with a first byte of 81-9F or E0-FC.
+    let mut cmap = parse_cmap(
+        b"/CMapName /Example-H def
+/Identity-H usecmap
+3 begincodespacerange
+<00> <80>
+<8140> <9FFC>
+<E040> <FCFC>
+endcodespacerange
+1 begincidchar <41> 34 endcidchar
+2 begincidrange <8140> <817E> 633 <8180> <81AC> 696 endcidrange",
+    )
+    .unwrap();
+    assert_eq!(cmap.use_cmap.as_deref(), Some(&b"Identity-H"[..]));
+    let codes: Vec<&[u8]> = cmap.codes(b"A\x81\x41\xE0\x40B\x81").collect();
+    assert_eq!(codes, [&b"A"[..], b"\x81\x41", b"\xE0\x40", b"B", b"\x81"]);
+    assert_eq!(cmap.cid(b"A"), Some(34));
+    assert_eq!(cmap.cid(b"\x81\x41"), Some(634));
+    assert_eq!(cmap.cid(b"\x81\x80"), Some(696));
+    assert_eq!(cmap.cid(b"\x90\x00"), None);
+    let overflowing = parse_cmap(
+        b"1 begincodespacerange <0000> <FFFF> endcodespacerange
+2 begincidrange <0000> <FFFF> 1 <0100> <01FF> 4294967295 endcidrange",
+    )
+    .unwrap();
+    assert_eq!(overflowing.cid(b"\x01\x00"), Some(u32::MAX));
+    assert_eq!(overflowing.cid(b"\x01\x01"), Some(0x102));
+
+    cmap.inherit(CMap::predefined(b"Identity-H").unwrap());
+    assert_eq!(cmap.cid(b"\x90\x00"), Some(0x9000));
+    assert_eq!(cmap.cid(b"\x81\x41"), Some(634));
+    // (The inherited codespace range means any 2 bytes make a code, when they aren't one of
+    // this CMap's single-byte codes.)
+    let codes: Vec<&[u8]> = cmap.codes(b"\xFD\x01A").collect();
+    assert_eq!(codes, [&b"\xFD\x01"[..], b"A"]);
+}
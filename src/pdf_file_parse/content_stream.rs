@@ -0,0 +1,382 @@
+// =======================
+// 7.8.2 Content Streams
+// =======================
+// A content stream (like a page's /Contents) is a sequence of operations, each of which is some
+// operands followed by an operator: `/F1 12 Tf` sets the font, `(Hello) Tj` shows some text.
+// The operands are objects (but never indirect references), and the operator is a keyword: a run
+// of regular characters, like `BT`, `T*` or `'`. Whitespace and comments go between them, as
+// anywhere else in the file.
+// The one exception is an inline image (8.9.7): `BI`, then the entries of the image dictionary,
+// then `ID`, a single white-space character, the image data, and finally `EI`. Nothing in the
+// data itself marks its end, so unless the dictionary gives its length (/L or /Length, since
+// PDF 2.0), the end is the first `EI` that has white space before it and is not followed by a
+// regular character.
+
+use super::*;
+
+/// A parsed content stream. Offsets in its spans are from the start of the (decoded) stream data.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContentStream<'a> {
+    #[serde(borrow)]
+    pub parts: Vec<ContentStreamPart<'a>>,
+}
+impl BinSerialize for ContentStream<'_> {
+    fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        for part in &self.parts {
+            part.serialize_to(buf)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum ContentStreamPart<'a> {
+    #[serde(borrow)]
+    Operation(Operation<'a>),
+    InlineImage(InlineImage<'a>),
+    Whitespace(Cow<'a, [u8]>), // Whitespace and comments between operations
+}
+impl BinSerialize for ContentStreamPart<'_> {
+    fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            ContentStreamPart::Operation(operation) => operation.serialize_to(buf),
+            ContentStreamPart::InlineImage(image) => image.serialize_to(buf),
+            ContentStreamPart::Whitespace(ws) => buf.write_all(ws),
+        }
+    }
+}
+
+/// An operator, with its operands: like `/F1 12 Tf`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Operation<'a> {
+    #[serde(borrow)]
+    operands: Vec<Operand<'a>>,
+    operator: Cow<'a, [u8]>,
+    #[serde(default)]
+    span: Span,
+}
+#[derive(Serialize, Deserialize, Debug)]
+struct Operand<'a> {
+    #[serde(borrow)]
+    object: Object<'a>,
+    ws: Cow<'a, [u8]>, // The whitespace and comments after it (none in e.g. `[(a)]TJ`)
+}
+impl BinSerialize for Operation<'_> {
+    fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        for operand in &self.operands {
+            operand.object.serialize_to(buf)?;
+            buf.write_all(&operand.ws)?;
+        }
+        buf.write_all(&self.operator)
+    }
+}
+impl<'a> Operation<'a> {
+    /// The operator, like `Tf`.
+    pub fn operator(&self) -> &[u8] {
+        &self.operator
+    }
+    /// The operands, in order.
+    pub fn operands(&self) -> impl Iterator<Item = &Object<'a>> {
+        self.operands.iter().map(|operand| &operand.object)
+    }
+}
+
+/// An inline image: `BI`, the entries of the image dictionary, `ID`, the image data, and `EI`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InlineImage<'a> {
+    // The entries (and whitespace) between `BI` and `ID`, without any `<<` and `>>`.
+    #[serde(borrow)]
+    dict: DictionaryObject<'a>,
+    ws: Cow<'a, [u8]>,      // The white-space character after `ID`
+    content: Cow<'a, [u8]>, // Everything up to `EI`, so including the white space before it
+    #[serde(default)]
+    span: Span,
+}
+impl BinSerialize for InlineImage<'_> {
+    fn serialize_to(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        buf.write_all(b"BI")?;
+        for part in &self.dict.parts {
+            part.serialize_to(buf)?;
+        }
+        buf.write_all(b"ID")?;
+        buf.write_all(&self.ws)?;
+        buf.write_all(&self.content)?;
+        buf.write_all(b"EI")
+    }
+}
+impl<'a> InlineImage<'a> {
+    /// The image dictionary (with abbreviated keys like /W, /H and /BPC).
+    pub fn dict(&self) -> &DictionaryObject<'a> {
+        &self.dict
+    }
+    /// The image data: without the white space before `EI`.
+    pub fn data(&self) -> &[u8] {
+        match inline_image_length(&self.dict) {
+            Some(n) if n <= self.content.len() => &self.content[..n],
+            _ => match self.content.strip_suffix(b"\r\n") {
+                Some(data) => data,
+                None => &self.content[..self.content.len().saturating_sub(1)],
+            },
+        }
+    }
+}
+span_accessors!(Operation<'_>, InlineImage<'_>);
+
+impl<'a> ContentStream<'a> {
+    /// The operations, in order (leaving out inline images).
+    pub fn operations(&self) -> impl Iterator<Item = &Operation<'a>> {
+        self.parts.iter().filter_map(|part| match part {
+            ContentStreamPart::Operation(operation) => Some(operation),
+            ContentStreamPart::InlineImage(_) | ContentStreamPart::Whitespace(_) => None,
+        })
+    }
+}
+
+// A character that can be part of an operator: anything but white space and delimiters.
+pub(super) fn is_regular_char(c: u8) -> bool {
+    !is_white_space_char(c) && !b"()<>[]{}/%".contains(&c)
+}
+
+// The length of the image data, if the dictionary gives it (/L or /Length, since PDF 2.0).
+fn inline_image_length(dict: &DictionaryObject) -> Option<usize> {
+    dict.get(b"L")
+        .or_else(|| dict.get(b"Length"))
+        .and_then(|length| length.as_integer())
+        .and_then(|length| usize::try_from(length).ok())
+}
+
+/// Parses `data` (the decoded data of a content stream) into operations. All of it must be
+/// consumed: each operation must end with an operator.
+pub fn parse_content_stream(data: &[u8]) -> Result<ContentStream<'_>, ParseError> {
+    with_own_context(data, || content_stream(data)).map_err(|e| ParseError::from_nom(data, e))
+}
+
+fn content_stream(mut input: &[u8]) -> Result<ContentStream<'_>, nom::Err<SyntaxError<&[u8]>>> {
+    let mut parts = vec![];
+    loop {
+        let (rest, ws) = whitespace_and_comments(input)?;
+        if !ws.is_empty() {
+            parts.push(ContentStreamPart::Whitespace(Cow::Borrowed(ws)));
+        }
+        if rest.is_empty() {
+            return Ok(ContentStream { parts });
+        }
+        let (rest, part) = content_stream_operation(rest)?;
+        parts.push(part);
+        input = rest;
+    }
+}
+
+#[adorn(traceable_parser("content_stream_operation"))]
+fn content_stream_operation(input: &[u8]) -> IResult<&[u8], ContentStreamPart> {
+    let start = input;
+    let (input, operands) = many0(map(
+        tuple((object, whitespace_and_comments)),
+        |(object, ws)| Operand {
+            object,
+            ws: Cow::Borrowed(ws),
+        },
+    ))(input)?;
+    let (input, operator) =
+        expecting("an operand or an operator", take_while1(is_regular_char))(input)?;
+    if operator != b"BI" {
+        return Ok((
+            input,
+            ContentStreamPart::Operation(Operation {
+                operands,
+                operator: Cow::Borrowed(operator),
+                span: Span::of(start, input),
+            }),
+        ));
+    }
+    if !operands.is_empty() {
+        return Err(nom::Err::Failure(SyntaxError::new(
+            start,
+            "no operands before `BI`",
+        )));
+    }
+    let (input, image) = inline_image_after_bi(start, input)?;
+    Ok((input, ContentStreamPart::InlineImage(image)))
+}
+
+// The rest of an inline image (which starts at `start`), after the `BI`.
+fn inline_image_after_bi<'a>(
+    start: &'a [u8],
+    input: &'a [u8],
+) -> IResult<&'a [u8], InlineImage<'a>> {
+    let dict_start = input;
+    let (input, (pairs, final_ws)) = tuple((
+        many0(tuple((whitespace_and_comments, key_value_pair))),
+        whitespace_and_comments,
+    ))(input)?;
+    let mut parts = vec![];
+    for (ws, pair) in pairs {
+        if !ws.is_empty() {
+            parts.push(DictionaryPart::Whitespace(Cow::Borrowed(ws)));
+        }
+        parts.push(DictionaryPart::KeyValuePair(pair));
+    }
+    if !final_ws.is_empty() {
+        parts.push(DictionaryPart::Whitespace(Cow::Borrowed(final_ws)));
+    }
+    let dict = DictionaryObject {
+        parts,
+        span: Span::of(dict_start, input),
+    };
+    let (input, _) = cut(expecting("a key, or `ID`", tag(b"ID")))(input)?;
+    let (input, ws) = cut(expecting(
+        "a white-space character after `ID`",
+        take_while_m_n(1, 1, is_white_space_char),
+    ))(input)?;
+
+    // `EI` at `i`, if it's preceded by white space (or the one after `ID`) and ends the token.
+    let ends_at = |i: usize| {
+        input[i..].starts_with(b"EI")
+            && (i == 0 || is_white_space_char(input[i - 1]))
+            && input.get(i + 2).is_none_or(|&c| !is_regular_char(c))
+    };
+    let given = inline_image_length(&dict).and_then(|n| {
+        let ws = input
+            .get(n..)?
+            .iter()
+            .take_while(|&&c| is_white_space_char(c));
+        Some(n + ws.count()).filter(|&end| end <= input.len() && ends_at(end))
+    });
+    let end = given.or_else(|| (0..input.len()).find(|&i| ends_at(i)));
+    let Some(end) = end else {
+        return Err(nom::Err::Failure(SyntaxError::new(
+            &input[input.len()..],
+            "`EI` after the inline image data",
+        )));
+    };
+    let (content, input) = input.split_at(end);
+    let input = &input[b"EI".len()..];
+    Ok((
+        input,
+        InlineImage {
+            dict,
+            ws: Cow::Borrowed(ws),
+            content: Cow::Borrowed(content),
+            span: Span::of(start, input),
+        },
+    ))
+}
+
+#[cfg(test)]
+// The (operator, operands) of each operation in `data`, as strings.
+fn operations(data: &[u8]) -> Vec<(String, Vec<String>)> {
+    let parsed = parse_content_stream(data).unwrap();
+    let mut out: Vec<u8> = vec![];
+    parsed.serialize_to(&mut out).unwrap();
+    assert_eq!(out, data);
+    let to_string = |object: &Object| {
+        let mut buf = vec![];
+        object.serialize_to(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    };
+    parsed
+        .operations()
+        .map(|operation| {
+            (
+                String::from_utf8(operation.operator().to_vec()).unwrap(),
+                operation.operands().map(to_string).collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_content_stream() {
+    let data = b"BT\n/F1 12 Tf\n72 712 Td\n(Hello World) Tj\nET\n";
+    let ops = |list: &[(&str, &[&str])]| -> Vec<(String, Vec<String>)> {
+        list.iter()
+            .map(|(op, args)| (op.to_string(), args.iter().map(|a| a.to_string()).collect()))
+            .collect()
+    };
+    assert_eq!(
+        operations(data),
+        ops(&[
+            ("BT", &[]),
+            ("Tf", &["/F1", "12"]),
+            ("Td", &["72", "712"]),
+            ("Tj", &["(Hello World)"]),
+            ("ET", &[]),
+        ])
+    );
+    // Operators glued to their operands, comments, `'` and `"`, and dictionary operands.
+    let data = b"[(A)-120(B)]TJ%comment\n(x)' 1 2(y)\" /Span<</MCID 0>>BDC EMC q 1 0 0 1 0 0 cm Q";
+    assert_eq!(
+        operations(data),
+        ops(&[
+            ("TJ", &["[(A)-120(B)]"]),
+            ("'", &["(x)"]),
+            ("\"", &["1", "2", "(y)"]),
+            ("BDC", &["/Span", "<</MCID 0>>"]),
+            ("EMC", &[]),
+            ("q", &[]),
+            ("cm", &["1", "0", "0", "1", "0", "0"]),
+            ("Q", &[]),
+        ])
+    );
+    let parsed = parse_content_stream(b"BT\n/F1 12 Tf").unwrap();
+    let spans: Vec<Span> = parsed.operations().map(|o| o.span()).collect();
+    assert_eq!(
+        spans,
+        [Span { start: 0, end: 2 }, Span { start: 3, end: 12 }]
+    );
+}
+
+#[test]
+fn test_content_stream_inline_image() {
+    // The data contains "EI" (not between white space) and a `)`.
+    let data = b"q BI /W 4 /H 1 /BPC 8 /CS /G ID \x01EI)\xFF\nEI Q";
+    let parsed = parse_content_stream(data).unwrap();
+    let mut out: Vec<u8> = vec![];
+    parsed.serialize_to(&mut out).unwrap();
+    assert_eq!(out, data);
+    let images: Vec<&InlineImage> = parsed
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            ContentStreamPart::InlineImage(image) => Some(image),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].data(), b"\x01EI)\xFF");
+    assert_eq!(images[0].dict().len(), 4);
+    assert_eq!(
+        images[0].span(),
+        Span {
+            start: 2,
+            end: data.len() - 2
+        }
+    );
+    let operators: Vec<&[u8]> = parsed.operations().map(|o| o.operator()).collect();
+    assert_eq!(operators, [&b"q"[..], b"Q"]);
+    // And via JSON, like everything else.
+    let json = serde_json::to_string(&parsed).unwrap();
+    let mut out: Vec<u8> = vec![];
+    let deserialized: ContentStream = serde_json::from_str(&json).unwrap();
+    deserialized.serialize_to(&mut out).unwrap();
+    assert_eq!(out, data);
+
+    // With /L, the data can contain " EI ".
+    let data = b"BI /W 4 /H 1 /L 4 ID\n EI \nEI";
+    let parsed = parse_content_stream(data).unwrap();
+    match &parsed.parts[0] {
+        ContentStreamPart::InlineImage(image) => assert_eq!(image.data(), b" EI "),
+        part => panic!("not an inline image: {:?}", part),
+    }
+}
+
+#[test]
+fn test_content_stream_errors() {
+    let err = parse_content_stream(b"BT /F1 12").err().unwrap();
+    assert_eq!(err.offset, 9);
+    assert_eq!(err.expected, "an operand or an operator");
+    let err = parse_content_stream(b"BI /W 4 ID \x00\x01").err().unwrap();
+    assert_eq!(err.expected, "`EI` after the inline image data");
+    let err = parse_content_stream(b"(unbalanced Tj").err().unwrap();
+    assert_eq!(err.expected, "1 more `)` to close the literal string");
+}
@@ -0,0 +1,1081 @@
+// ==============================================
+// 7.5.5 File Trailer, 7.5.6 Incremental Updates:
+// reading a file "trailer first"
+// ==============================================
+// The parser in the parent module reads a file forwards, from the header to the last %%EOF.
+// Real readers instead start at the end: the last "startxref" gives the offset of the last
+// cross-reference section, whose trailer may point (via /Prev) to the previous one, and so on.
+// Together, these sections say where each object is, so anything between objects is never looked at.
+
+use super::encryption::{EncryptionError, SecurityHandler};
+use super::object_stream::{CompressedObject, ObjectStream};
+use super::text_string::decode_text_string;
+use super::xref_stream::{is_cross_reference_stream, CrossReferenceStream};
+use super::*;
+use std::collections::{BTreeMap, HashSet};
+
+/// Where to find an object, according to the cross-reference table (or stream).
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XrefEntry {
+    Free {
+        next_free_object_number: u64,
+        generation: u16,
+    },
+    InUse {
+        offset: usize,
+        generation: u16,
+    },
+    // Only in cross-reference streams: the object is the `index`-th one in an object stream.
+    Compressed {
+        object_stream_number: u32,
+        index: u32,
+    },
+}
+
+impl CrossReferenceEntry {
+    // The entry, or what was expected if the generation number is too large for 16 bits.
+    pub(super) fn entry(&self) -> Result<XrefEntry, String> {
+        // Both fields were parsed as fixed-length digit strings, so these can't fail.
+        let number: u64 = std::str::from_utf8(&self.nnnnnnnnnn)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let generation: u32 = std::str::from_utf8(&self.ggggg).unwrap().parse().unwrap();
+        let generation = u16::try_from(generation).map_err(|_| {
+            format!(
+                "a generation number of at most 16 bits (got {})",
+                generation
+            )
+        })?;
+        Ok(match self.n_or_f {
+            CrossReferenceEntryInUse::Free => XrefEntry::Free {
+                next_free_object_number: number,
+                generation,
+            },
+            CrossReferenceEntryInUse::InUse => XrefEntry::InUse {
+                offset: number as usize,
+                generation,
+            },
+        })
+    }
+}
+
+impl CrossReferenceTable<'_> {
+    // All the entries, with their object numbers, or what was expected of the first bad one.
+    pub(super) fn entries(&self) -> Result<Vec<(u32, XrefEntry)>, String> {
+        let mut entries = vec![];
+        for subsection in &self.subsections {
+            let first = subsection.first_object_number.value().unwrap_or(0);
+            for (i, entry) in subsection.entries.iter().enumerate() {
+                if let Ok(number) = u32::try_from(first + i as i64) {
+                    entries.push((number, entry.entry()?));
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// A PDF file loaded via its cross-reference table(s), starting from the last `startxref`.
+pub struct Document<'a> {
+    // The offsets of the cross-reference sections, starting with the last one in the file.
+    xref_offsets: Vec<usize>,
+    // Object number -> entry. Where several sections have an entry, the newest one wins.
+    xref: BTreeMap<u32, XrefEntry>,
+    // The trailer dictionary of the newest section.
+    trailer: DictionaryObject<'a>,
+    // The in-use objects, parsed at the offsets given in `xref` (or found near them).
+    objects: BTreeMap<u32, IndirectObjectDefinition<'a>>,
+    // The objects in object streams (unless the file is encrypted and could not be decrypted).
+    compressed_objects: BTreeMap<u32, CompressedObject<'static>>,
+    // Why each in-use or compressed object that's in neither of the above could not be read.
+    object_errors: BTreeMap<u32, ParseError>,
+    // If the file is encrypted: how to decrypt it, or why we can't.
+    security_handler: Option<Result<SecurityHandler, EncryptionError>>,
+}
+
+// An error at `offset`, for when the file structure (rather than the syntax) is wrong.
+fn structure_error(offset: usize, rule: &'static str, expected: String) -> ParseError {
+    ParseError {
+        offset,
+        rule,
+        expected,
+    }
+}
+
+impl<'a> Document<'a> {
+    /// Loads `bytes` by following the last `startxref` offset to the cross-reference table (or stream),
+    /// and from there the /Prev chain through any earlier (incremental update) sections.
+    /// It fails only if the cross-reference sections can't be read: objects that can't be are
+    /// left out (see `object_errors`).
+    /// If the file is encrypted, its strings and streams are decrypted with the empty password.
+    pub fn open(bytes: &'a [u8]) -> Result<Document<'a>, ParseError> {
+        Self::open_with_password(bytes, b"")
+    }
+
+    /// Like `open`, but with a user or owner password for decrypting an encrypted file.
+    pub fn open_with_password(
+        bytes: &'a [u8],
+        password: &[u8],
+    ) -> Result<Document<'a>, ParseError> {
+        // Each object is parsed from where it starts, but its span is still an offset in the file.
+        with_own_context(bytes, || Self::read(bytes, password))
+    }
+
+    fn read(bytes: &'a [u8], password: &[u8]) -> Result<Document<'a>, ParseError> {
+        let (xref_offsets, xref, trailer) = Self::read_cross_reference_sections(bytes)?;
+
+        // (Objects in object streams are read after the security handler is set up: see below.)
+        // An object that can't be read is left out, with the reason in `object_errors`.
+        let (objects, object_errors) =
+            with_indirect_lengths(indirect_lengths(bytes, &xref), || {
+                let mut objects = BTreeMap::new();
+                let mut object_errors = BTreeMap::new();
+                for (&number, entry) in &xref {
+                    if let XrefEntry::InUse { offset, generation } = *entry {
+                        match Self::parse_object_at(bytes, offset, number, generation) {
+                            Ok(def) => {
+                                objects.insert(number, def);
+                            }
+                            Err(e) => {
+                                object_errors.insert(number, e);
+                            }
+                        }
+                    }
+                }
+                (objects, object_errors)
+            });
+
+        let security_handler = trailer
+            .get(b"Encrypt")
+            .map(|encrypt| Self::security_handler(encrypt, &trailer, &objects, password));
+        let mut doc = Document {
+            xref_offsets,
+            xref,
+            trailer,
+            objects,
+            compressed_objects: BTreeMap::new(),
+            object_errors,
+            security_handler,
+        };
+        if doc.encryption_error().is_none() {
+            let (compressed_objects, object_errors) = doc.read_object_streams();
+            doc.compressed_objects = compressed_objects;
+            doc.object_errors.extend(object_errors);
+        }
+        Ok(doc)
+    }
+
+    // Reads all the cross-reference sections, starting from the last `startxref`. Returns their
+    // offsets, the combined cross-reference table, and the trailer dictionary of the newest section.
+    #[allow(clippy::type_complexity)]
+    fn read_cross_reference_sections(
+        bytes: &'a [u8],
+    ) -> Result<(Vec<usize>, BTreeMap<u32, XrefEntry>, DictionaryObject<'a>), ParseError> {
+        let startxref = bytes
+            .windows(b"startxref".len())
+            .rposition(|w| w == b"startxref")
+            .ok_or_else(|| {
+                structure_error(
+                    bytes.len(),
+                    "startxref_offset_eof",
+                    "`startxref` near the end of the file".to_string(),
+                )
+            })?;
+        let (_, startxref_offset_eof) = startxref_offset_eof(&bytes[startxref..])
+            .map_err(|e| ParseError::from_nom(bytes, e))?;
+
+        let mut xref_offsets: Vec<usize> = vec![];
+        let mut xref: BTreeMap<u32, XrefEntry> = BTreeMap::new();
+        let mut trailer: Option<DictionaryObject<'a>> = None;
+        let mut visited: HashSet<usize> = HashSet::new();
+        let mut next = startxref_offset_eof.last_crossref_offset.value();
+        let mut referrer = startxref;
+        while let Some(offset) = next {
+            let offset = usize::try_from(offset)
+                .ok()
+                .filter(|&offset| offset < bytes.len() && visited.insert(offset))
+                .ok_or_else(|| {
+                    structure_error(
+                        referrer,
+                        "cross_reference_table",
+                        format!(
+                            "a cross-reference offset within the file and not seen before (got {})",
+                            offset
+                        ),
+                    )
+                })?;
+            let (entries, dict) = Self::read_section(bytes, offset)?;
+            let mut section: BTreeMap<u32, XrefEntry> = entries.into_iter().collect();
+            // In a "hybrid-reference" file, the trailer's /XRefStm points to a cross-reference stream
+            // for the objects (e.g. those in object streams) that the table omits or marks as free.
+            if let Some(stm_offset) = dict.get(b"XRefStm").and_then(|o| o.as_integer()) {
+                let stm_offset = usize::try_from(stm_offset)
+                    .ok()
+                    .filter(|&stm_offset| stm_offset < bytes.len())
+                    .ok_or_else(|| {
+                        structure_error(
+                            offset,
+                            "trailer",
+                            format!("an /XRefStm offset within the file (got {})", stm_offset),
+                        )
+                    })?;
+                let (stm_entries, _) = Self::read_cross_reference_stream(bytes, stm_offset)?;
+                for (number, entry) in stm_entries {
+                    if !matches!(section.get(&number), Some(XrefEntry::InUse { .. })) {
+                        section.insert(number, entry);
+                    }
+                }
+            }
+            for (number, entry) in section {
+                // Entries from newer sections (seen earlier) take precedence.
+                xref.entry(number).or_insert(entry);
+            }
+            next = dict.get(b"Prev").and_then(|prev| prev.as_integer());
+            xref_offsets.push(offset);
+            referrer = offset;
+            trailer.get_or_insert(dict);
+        }
+        // There was at least one section (or we would have returned an error above).
+        Ok((xref_offsets, xref, trailer.unwrap()))
+    }
+
+    // The objects that the cross-reference streams say are in object streams, and why each of
+    // those that isn't could not be read.
+    #[allow(clippy::type_complexity)]
+    fn read_object_streams(
+        &self,
+    ) -> (
+        BTreeMap<u32, CompressedObject<'static>>,
+        BTreeMap<u32, ParseError>,
+    ) {
+        let mut by_container: BTreeMap<u32, Vec<(u32, u32)>> = BTreeMap::new();
+        for (&number, entry) in &self.xref {
+            if let XrefEntry::Compressed {
+                object_stream_number,
+                index,
+            } = *entry
+            {
+                by_container
+                    .entry(object_stream_number)
+                    .or_default()
+                    .push((number, index));
+            }
+        }
+        let mut compressed_objects = BTreeMap::new();
+        let mut object_errors = BTreeMap::new();
+        for (container, wanted) in by_container {
+            let offset = match self.xref.get(&container) {
+                Some(XrefEntry::InUse { offset, .. }) => *offset,
+                _ => self.xref_offsets[0],
+            };
+            let error = |expected: String| structure_error(offset, "object_stream", expected);
+            let mut objects = match self.read_object_stream(container, error) {
+                Ok(objects) => objects,
+                Err(e) => {
+                    for (number, _) in wanted {
+                        object_errors.insert(number, e.clone());
+                    }
+                    continue;
+                }
+            };
+            for (number, index) in wanted {
+                // The index should say where the object is, but the header has the object numbers too.
+                let position = objects
+                    .iter()
+                    .position(|o| o.index == index as usize && o.object_number == number)
+                    .or_else(|| objects.iter().position(|o| o.object_number == number));
+                match position {
+                    Some(position) => {
+                        compressed_objects.insert(number, objects.swap_remove(position));
+                    }
+                    None => {
+                        object_errors.insert(
+                            number,
+                            error(format!(
+                                "object {} in object stream {} (per the cross-reference stream)",
+                                number, container
+                            )),
+                        );
+                    }
+                }
+            }
+        }
+        (compressed_objects, object_errors)
+    }
+
+    // The objects in object stream `container`, with `error` for what's wrong if they can't be read.
+    fn read_object_stream(
+        &self,
+        container: u32,
+        error: impl Fn(String) -> ParseError,
+    ) -> Result<Vec<CompressedObject<'static>>, ParseError> {
+        let (def, stream) = match self.objects.get(&container) {
+            Some(
+                def @ IndirectObjectDefinition {
+                    object: Object::Stream(stream),
+                    ..
+                },
+            ) => (def, stream),
+            _ => {
+                return Err(error(format!(
+                    "object {} to be an object stream (per the cross-reference stream)",
+                    container
+                )))
+            }
+        };
+        let data = self
+            .stream_data(container, 0, stream)
+            .map_err(|e| error(format!("decodable stream data ({})", e)))?;
+        let object_stream =
+            ObjectStream::with_data(def, data.into_owned()).map_err(|e| error(e.expected))?;
+        // Errors in the objects are at offsets in the decoded data, not in the file.
+        let objects = object_stream.objects().map_err(|e| {
+            error(format!(
+                "{} (at offset {} in the data of object stream {})",
+                e.expected, e.offset, container
+            ))
+        })?;
+        Ok(objects.into_iter().map(|o| o.into_owned()).collect())
+    }
+
+    // The security handler for the /Encrypt entry `encrypt` of `trailer`.
+    fn security_handler(
+        encrypt: &ObjectOrReference,
+        trailer: &DictionaryObject,
+        objects: &BTreeMap<u32, IndirectObjectDefinition>,
+        password: &[u8],
+    ) -> Result<SecurityHandler, EncryptionError> {
+        let (dict, number) = match encrypt {
+            ObjectOrReference::Object(Object::Dictionary(dict)) => (dict, None),
+            ObjectOrReference::Reference(r) => {
+                let number = r.id().map(|(number, _)| number);
+                match number.and_then(|number| objects.get(&number)) {
+                    Some(IndirectObjectDefinition {
+                        object: Object::Dictionary(dict),
+                        ..
+                    }) => (dict, number),
+                    _ => {
+                        return Err(EncryptionError::BadEncryptDict(
+                            "/Encrypt is not (a reference to) a dictionary".to_string(),
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(EncryptionError::BadEncryptDict(
+                    "/Encrypt is not a dictionary".to_string(),
+                ))
+            }
+        };
+        // The first element of /ID (which is required for revisions 2 to 4, but only those).
+        let id = trailer
+            .get(b"ID")
+            .and_then(|id| id.as_array())
+            .and_then(|id| id.elements().next())
+            .and_then(|id| id.as_string())
+            .map(|id| id.decoded_bytes())
+            .unwrap_or_default();
+        SecurityHandler::new(dict, number, &id, password)
+    }
+
+    // Reads the cross-reference section at `offset`: either an "xref" table followed by a trailer,
+    // or a cross-reference stream (whose dictionary is the trailer dictionary). If it's neither
+    // (as when the offsets are a little off), the nearest "xref" table is read instead.
+    #[allow(clippy::type_complexity)]
+    fn read_section(
+        bytes: &'a [u8],
+        offset: usize,
+    ) -> Result<(Vec<(u32, XrefEntry)>, DictionaryObject<'a>), ParseError> {
+        let mut offset = offset;
+        if !bytes[offset..].starts_with(b"xref") {
+            match Self::read_cross_reference_stream(bytes, offset) {
+                Err(e) => offset = find_near(bytes, offset, b"xref").ok_or(e)?,
+                section => return section,
+            }
+        }
+        let (_, section) = cross_reference_table_and_trailer(&bytes[offset..])
+            .map_err(|e| ParseError::from_nom(bytes, e))?;
+        let entries = section
+            .cross_reference_table
+            .entries()
+            .map_err(|expected| structure_error(offset, "cross_reference_table", expected))?;
+        Ok((entries, section.trailer.dict))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn read_cross_reference_stream(
+        bytes: &'a [u8],
+        offset: usize,
+    ) -> Result<(Vec<(u32, XrefEntry)>, DictionaryObject<'a>), ParseError> {
+        let not_a_section = || {
+            structure_error(
+                offset,
+                "cross_reference_table",
+                "`xref` or a cross-reference stream object".to_string(),
+            )
+        };
+        let (_, def) = indirect_object_definition(&bytes[offset..]).map_err(|e| match e {
+            nom::Err::Error(_) => not_a_section(),
+            e => ParseError::from_nom(bytes, e),
+        })?;
+        if !is_cross_reference_stream(&def) {
+            return Err(not_a_section());
+        }
+        let xref_stream = CrossReferenceStream::from_definition(&def)
+            .map_err(|expected| structure_error(offset, "cross_reference_stream", expected))?;
+        let entries = xref_stream.entries().collect();
+        match def.object {
+            Object::Stream(stream) => Ok((entries, stream.dict)),
+            _ => unreachable!("Already checked that it's a cross-reference stream"),
+        }
+    }
+
+    // Parses the definition of object `number` that the cross-reference table says is at `offset`,
+    // or if it isn't there (as when the offsets are a little off), the nearest one that is.
+    fn parse_object_at(
+        bytes: &'a [u8],
+        offset: usize,
+        number: u32,
+        generation: u16,
+    ) -> Result<IndirectObjectDefinition<'a>, ParseError> {
+        Self::parse_object_exactly_at(bytes, offset, number, generation).or_else(|e| {
+            find_near(
+                bytes,
+                offset,
+                format!("{} {} obj", number, generation).as_bytes(),
+            )
+            .and_then(|near| Self::parse_object_exactly_at(bytes, near, number, generation).ok())
+            .ok_or(e)
+        })
+    }
+
+    fn parse_object_exactly_at(
+        bytes: &'a [u8],
+        offset: usize,
+        number: u32,
+        generation: u16,
+    ) -> Result<IndirectObjectDefinition<'a>, ParseError> {
+        let wrong_object = || {
+            structure_error(
+                offset,
+                "indirect_object_definition",
+                format!(
+                    "the definition of object {} {} (per the cross-reference table)",
+                    number, generation
+                ),
+            )
+        };
+        let rest = bytes.get(offset..).ok_or_else(wrong_object)?;
+        let (_, def) = indirect_object_definition(rest).map_err(|e| match e {
+            // It didn't even start like an object definition: the offset is wrong.
+            nom::Err::Error(_) => wrong_object(),
+            e => ParseError::from_nom(bytes, e),
+        })?;
+        if def.object_number.value() != Some(number.into())
+            || def.generation_number.value() != Some(generation.into())
+        {
+            return Err(wrong_object());
+        }
+        Ok(def)
+    }
+
+    /// The trailer dictionary of the last cross-reference section.
+    pub fn trailer(&self) -> &DictionaryObject<'a> {
+        &self.trailer
+    }
+
+    /// The byte offsets of the cross-reference sections that were read, last one (in the file) first.
+    pub fn xref_offsets(&self) -> &[usize] {
+        &self.xref_offsets
+    }
+
+    /// The combined cross-reference table: object number -> entry.
+    pub fn xref(&self) -> &BTreeMap<u32, XrefEntry> {
+        &self.xref
+    }
+
+    /// The definition of object `number`, if it is in use.
+    pub fn object(&self, number: u32) -> Option<&IndirectObjectDefinition<'a>> {
+        self.objects.get(&number)
+    }
+
+    /// All the in-use objects, by object number.
+    pub fn objects(&self) -> impl Iterator<Item = (u32, &IndirectObjectDefinition<'a>)> {
+        self.objects.iter().map(|(&number, def)| (number, def))
+    }
+
+    /// The in-use objects (and objects in object streams) that could not be read, by object number,
+    /// with why not: e.g. the cross-reference table gives a wrong offset, or the object stream can't
+    /// be decoded. They are left out of `objects` and `compressed_objects`, and don't resolve.
+    pub fn object_errors(&self) -> &BTreeMap<u32, ParseError> {
+        &self.object_errors
+    }
+
+    /// The objects that are in object streams, by object number.
+    pub fn compressed_objects(&self) -> impl Iterator<Item = (u32, &CompressedObject<'a>)> {
+        self.compressed_objects
+            .iter()
+            .map(|(&number, object)| (number, object))
+    }
+
+    /// The object that `reference` refers to, per the cross-reference table: `None` if the object
+    /// is free (or not in the table), or if the generation number does not match.
+    pub fn resolve(&self, reference: &IndirectObjectReference) -> Option<&Object<'a>> {
+        let (number, generation) = reference.id()?;
+        match *self.xref.get(&number)? {
+            XrefEntry::InUse { generation: g, .. } if g == generation => {
+                self.objects.get(&number).map(|def| &def.object)
+            }
+            // "The generation number of the object stream and of any compressed object is implicitly 0."
+            XrefEntry::Compressed { .. } if generation == 0 => self
+                .compressed_objects
+                .get(&number)
+                .map(|compressed| &compressed.object),
+            _ => None,
+        }
+    }
+
+    /// Whether the file has an /Encrypt dictionary.
+    pub fn is_encrypted(&self) -> bool {
+        self.security_handler.is_some()
+    }
+
+    /// If the file is encrypted but can't be decrypted (e.g. because the password was wrong), why not.
+    pub fn encryption_error(&self) -> Option<&EncryptionError> {
+        self.security_handler.as_ref()?.as_ref().err()
+    }
+
+    // The security handler for decrypting the strings and streams in object `number`, if any:
+    // none if the file is not encrypted, or if the object is in an object stream (which is
+    // decrypted as a whole, so the objects in it are already plaintext).
+    fn security_handler_for(
+        &self,
+        number: u32,
+    ) -> Option<&Result<SecurityHandler, EncryptionError>> {
+        match self.xref.get(&number) {
+            Some(XrefEntry::Compressed { .. }) => None,
+            _ => self.security_handler.as_ref(),
+        }
+    }
+
+    /// The bytes of `s`, a string in object `number` (with generation number `generation`),
+    /// decrypted if the file is encrypted (and the object is not in an object stream).
+    pub fn string_bytes(
+        &self,
+        number: u32,
+        generation: u16,
+        s: &StringObject,
+    ) -> Result<Vec<u8>, EncryptionError> {
+        let bytes = s.decoded_bytes();
+        match self.security_handler_for(number) {
+            None => Ok(bytes),
+            Some(handler) => handler
+                .as_ref()
+                .map_err(|e| e.clone())?
+                .decrypt_string(number, generation, &bytes),
+        }
+    }
+
+    /// `s`, a string in object `number` (with generation number `generation`), decrypted if the
+    /// file is encrypted, and decoded as a text string (see `decode_text_string`).
+    pub fn text_string(
+        &self,
+        number: u32,
+        generation: u16,
+        s: &StringObject,
+    ) -> Result<String, EncryptionError> {
+        Ok(decode_text_string(
+            &self.string_bytes(number, generation, s)?,
+        ))
+    }
+
+    /// The data of `stream`, which is object `number` (with generation number `generation`),
+    /// decrypted if the file is encrypted, and then decoded (see `StreamObject::decoded`).
+    pub fn stream_data<'s>(
+        &self,
+        number: u32,
+        generation: u16,
+        stream: &'s StreamObject,
+    ) -> Result<Cow<'s, [u8]>, DecodeError> {
+        let data = stream.data();
+        let data = match self.security_handler_for(number) {
+            None => Cow::Borrowed(data),
+            Some(handler) => Cow::Owned(
+                handler
+                    .as_ref()
+                    .map_err(|e| DecodeError::Encryption(e.clone()))?
+                    .decrypt_stream(number, generation, stream, data)
+                    .map_err(DecodeError::Encryption)?,
+            ),
+        };
+        stream.undo_filters(data)
+    }
+}
+
+// How far from a wrong offset (of an object, or of a cross-reference table) to look for the
+// object definition, or the `xref`.
+const NEAR: usize = 1024;
+
+// The nearest offset within `NEAR` bytes of `offset` where `pattern` starts (after whitespace).
+fn find_near(bytes: &[u8], offset: usize, pattern: &[u8]) -> Option<usize> {
+    let start = offset.saturating_sub(NEAR).min(bytes.len());
+    let end = offset.saturating_add(NEAR).min(bytes.len());
+    bytes[start..end]
+        .windows(pattern.len())
+        .enumerate()
+        .filter(|&(_, w)| w == pattern)
+        .map(|(i, _)| start + i)
+        .filter(|&i| i == 0 || is_white_space_char(bytes[i - 1]))
+        .min_by_key(|&i| i.abs_diff(offset))
+}
+
+// The value of each in-use object (per `xref`) that is just a non-negative integer, by object
+// number and generation number: what an indirect /Length of a stream may refer to.
+fn indirect_lengths(bytes: &[u8], xref: &BTreeMap<u32, XrefEntry>) -> BTreeMap<(u32, u16), usize> {
+    let mut lengths = BTreeMap::new();
+    for (&number, entry) in xref {
+        if let XrefEntry::InUse { offset, generation } = *entry {
+            let definition = tuple((
+                integer_without_sign,
+                whitespace_and_comments,
+                integer_without_sign,
+                whitespace_and_comments,
+                tag("obj"),
+                whitespace_and_comments,
+                object_numeric_integer,
+                whitespace_and_comments,
+                tag("endobj"),
+            ));
+            let value = match bytes.get(offset..).map(definition) {
+                Some(Ok((_, (n, _, g, _, _, _, value, _, _))))
+                    if n.value() == Some(number.into()) && g.value() == Some(generation.into()) =>
+                {
+                    value.value().and_then(|v| usize::try_from(v).ok())
+                }
+                _ => None,
+            };
+            if let Some(value) = value {
+                lengths.insert((number, generation), value);
+            }
+        }
+    }
+    lengths
+}
+
+// For parsing a file forwards: the values that an indirect /Length may refer to, if the file's
+// cross-reference table can be read (else none, so that such streams are scanned for "endstream").
+pub(super) fn file_indirect_lengths(bytes: &[u8]) -> BTreeMap<(u32, u16), usize> {
+    match Document::read_cross_reference_sections(bytes) {
+        Ok((_, xref, _)) => indirect_lengths(bytes, &xref),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+impl BodyCrossrefTrailer<'_> {
+    // Whether this section's cross-reference table (or stream) marks object `number` as free.
+    fn frees(&self, number: u32) -> bool {
+        let is_free = |entry: &(u32, XrefEntry)| {
+            entry.0 == number && matches!(entry.1, XrefEntry::Free { .. })
+        };
+        match (
+            &self.cross_reference_table_and_trailer,
+            &self.cross_reference_stream,
+        ) {
+            (Some(t), _) => t
+                .cross_reference_table
+                .entries()
+                .is_ok_and(|entries| entries.iter().any(is_free)),
+            (None, Some(Ok(stream))) => stream.entries().any(|entry| is_free(&entry)),
+            (None, _) => false,
+        }
+    }
+}
+
+impl<'a> PdfFile<'a> {
+    /// The object that `reference` refers to: the definition of that object in the last section
+    /// (i.e. the latest incremental update) that defines or frees it. `None` if it is freed, or if
+    /// the generation number does not match. Objects in object streams are not found; for those,
+    /// see `Document::resolve`.
+    pub fn resolve(&self, reference: &IndirectObjectReference) -> Option<&Object<'a>> {
+        let (number, generation) = reference.id()?;
+        for section in self.body_crossref_trailers.iter().rev() {
+            let def = section.body.iter().rev().find_map(|part| match part {
+                BodyPart::ObjDef(def) if def.object_number.value() == Some(number.into()) => {
+                    Some(def)
+                }
+                _ => None,
+            });
+            if let Some(def) = def {
+                return (def.generation_number.value() == Some(generation.into()))
+                    .then_some(&def.object);
+            }
+            if section.frees(number) {
+                return None;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+// The test file, with an incremental update appended that redefines object 6 (the /Info dictionary).
+// `junk` goes between the new object and the new cross-reference table.
+fn with_update(junk: &[u8]) -> Vec<u8> {
+    let mut bytes = include_bytes!("../test_simple.pdf").to_vec();
+    let obj_offset = bytes.len();
+    bytes.extend_from_slice(b"6 0 obj\n<< /Title (Updated) >>\nendobj\n");
+    bytes.extend_from_slice(junk);
+    let xref_offset = bytes.len();
+    bytes.extend_from_slice(
+        format!(
+            "xref\n6 1\n{:010} 00000 n \ntrailer\n<< /Size 7 /Root 1 0 R /Info 6 0 R /Prev 468 >>\nstartxref\n{}\n%%EOF\n",
+            obj_offset, xref_offset
+        )
+        .as_bytes(),
+    );
+    bytes
+}
+
+#[test]
+fn test_document_open() {
+    let bytes = include_bytes!("../test_simple.pdf");
+    let doc = Document::open(bytes).unwrap();
+    assert_eq!(doc.xref_offsets(), &[468]);
+    assert_eq!(doc.xref().len(), 7);
+    assert_eq!(
+        doc.xref()[&4],
+        XrefEntry::InUse {
+            offset: 247,
+            generation: 0
+        }
+    );
+    assert_eq!(doc.objects().count(), 6);
+    assert!(matches!(doc.object(4).unwrap().object, Object::Stream(_)));
+    assert!(doc.trailer().get(b"Root").is_some());
+}
+
+#[test]
+fn test_document_incremental_update() {
+    let bytes = with_update(b"");
+    let doc = Document::open(&bytes).unwrap();
+    assert_eq!(doc.xref_offsets().len(), 2);
+    assert_eq!(doc.xref_offsets()[1], 468);
+    let mut out: Vec<u8> = vec![];
+    doc.object(6).unwrap().serialize_to(&mut out).unwrap();
+    assert_eq!(out, b"6 0 obj\n<< /Title (Updated) >>\nendobj");
+    // Objects not in the update come from the original section.
+    assert_eq!(doc.objects().count(), 6);
+}
+
+#[test]
+fn test_document_junk_between_objects() {
+    let bytes = with_update(b"this is not PDF syntax\n");
+    assert!(parse_pdf(&bytes).is_err());
+    assert!(Document::open(&bytes).is_ok());
+}
+
+#[test]
+fn test_document_cross_reference_stream() {
+    let bytes = include_bytes!("../test_xref_stream.pdf");
+    let doc = Document::open(bytes).unwrap();
+    assert_eq!(doc.xref_offsets(), &[413]);
+    assert_eq!(
+        doc.xref()[&1],
+        XrefEntry::Compressed {
+            object_stream_number: 7,
+            index: 0
+        }
+    );
+    // The content stream, the object stream, and the cross-reference stream itself.
+    assert_eq!(
+        doc.objects().map(|(n, _)| n).collect::<Vec<u32>>(),
+        vec![4, 7, 8]
+    );
+    assert!(doc.trailer().get(b"Root").is_some());
+}
+
+#[test]
+fn test_document_bad_offset() {
+    let bytes = include_bytes!("../test_simple.pdf");
+    // An offset that's a little off: the object is found near it.
+    let bad = replace_first(bytes, b"0000000247 00000 n", b"0000000248 00000 n");
+    let doc = Document::open(&bad).unwrap();
+    assert!(doc.object(4).is_some());
+    assert!(doc.object_errors().is_empty());
+    // An offset past the end of the file, or where there's no such object: the object is left out.
+    let past_end = replace_first(bytes, b"0000000247 00000 n", b"0000005000 00000 n");
+    let renumbered = replace_first(bytes, b"\n4 0 obj", b"\n8 0 obj");
+    for (bad, offset) in [(past_end, 5000), (renumbered, 247)] {
+        let doc = Document::open(&bad).unwrap();
+        assert!(doc.object(4).is_none());
+        assert!(doc.object(5).is_some());
+        let err = &doc.object_errors()[&4];
+        assert_eq!(err.offset, offset);
+        assert_eq!(err.rule, "indirect_object_definition");
+    }
+
+    // An object stream that can't be decoded: the objects in it are left out.
+    let bytes = include_bytes!("../test_xref_stream.pdf");
+    let bad = replace_first(bytes, b"/FlateDecode", b"/JBIG2Decode");
+    let doc = Document::open(&bad).unwrap();
+    assert_eq!(doc.compressed_objects().count(), 0);
+    assert_eq!(
+        doc.object_errors().keys().copied().collect::<Vec<_>>(),
+        vec![1, 2, 3, 5, 6]
+    );
+    assert_eq!(doc.object_errors()[&1].rule, "object_stream");
+}
+
+#[test]
+fn test_document_generation_too_large() {
+    let bytes = include_bytes!("../test_simple.pdf");
+    let bad = replace_first(bytes, b"0000000247 00000 n", b"0000000247 99999 n");
+    let err = Document::open(&bad).err().unwrap();
+    assert_eq!(err.rule, "cross_reference_table");
+    assert_eq!(
+        err.expected,
+        "a generation number of at most 16 bits (got 99999)"
+    );
+}
+
+#[cfg(test)]
+// The /Title string in object 6, and the data of the content stream (object 4).
+fn title_and_content(doc: &Document) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let info = match &doc.object(6).unwrap().object {
+        Object::Dictionary(d) => d,
+        _ => panic!("object 6 is not a dictionary"),
+    };
+    let title = info.get(b"Title").unwrap().as_string().unwrap();
+    let title = doc.string_bytes(6, 0, title).map_err(|e| e.to_string())?;
+    let content = match &doc.object(4).unwrap().object {
+        Object::Stream(s) => doc.stream_data(4, 0, s).map_err(|e| e.to_string())?,
+        _ => panic!("object 4 is not a stream"),
+    };
+    Ok((title, content.into_owned()))
+}
+
+#[test]
+fn test_document_encrypted() {
+    let content = b"BT\n/F1 12 Tf\n72 712 Td\n(Hello World) Tj\nET\n".to_vec();
+    let files: [&[u8]; 3] = [
+        include_bytes!("../test_encrypted_rc4_40.pdf"),
+        include_bytes!("../test_encrypted_rc4_128.pdf"),
+        include_bytes!("../test_encrypted_aes_128.pdf"),
+    ];
+    for bytes in files {
+        // The user password is empty.
+        let doc = Document::open(bytes).unwrap();
+        assert!(doc.is_encrypted());
+        assert_eq!(
+            title_and_content(&doc),
+            Ok((b"Hello".to_vec(), content.clone()))
+        );
+        let doc = Document::open_with_password(bytes, b"owner").unwrap();
+        assert_eq!(
+            title_and_content(&doc),
+            Ok((b"Hello".to_vec(), content.clone()))
+        );
+    }
+
+    // /V 4 without a top-level /Length: the key length is the crypt filter's, or else 128 bits.
+    let aes_128 = include_bytes!("../test_encrypted_aes_128.pdf");
+    let without_length = replace_first(aes_128, b"/Length 128 ", &[b' '; 12]);
+    let without_lengths = replace_first(&without_length, b"/Length 16 ", &[b' '; 11]);
+    for bytes in [without_length, without_lengths] {
+        let doc = Document::open(&bytes).unwrap();
+        assert_eq!(
+            title_and_content(&doc),
+            Ok((b"Hello".to_vec(), content.clone()))
+        );
+    }
+
+    // Not encrypted: the same accessors just return the data.
+    let doc = Document::open(include_bytes!("../test_simple.pdf")).unwrap();
+    assert!(!doc.is_encrypted());
+    assert_eq!(title_and_content(&doc), Ok((b"Hello".to_vec(), content)));
+}
+
+#[test]
+fn test_document_encrypted_aes_256() {
+    // Revision 6, with user password "user" and owner password "owner".
+    let bytes = include_bytes!("../test_encrypted_aes_256.pdf");
+    let doc = Document::open(bytes).unwrap();
+    assert_eq!(
+        doc.encryption_error(),
+        Some(&EncryptionError::IncorrectPassword)
+    );
+    assert_eq!(
+        title_and_content(&doc),
+        Err("incorrect password".to_string())
+    );
+    for password in [&b"user"[..], b"owner"] {
+        let doc = Document::open_with_password(bytes, password).unwrap();
+        assert_eq!(doc.encryption_error(), None);
+        assert_eq!(title_and_content(&doc).unwrap().0, b"Hello");
+        let info = match &doc.object(6).unwrap().object {
+            Object::Dictionary(d) => d,
+            _ => panic!("object 6 is not a dictionary"),
+        };
+        let author = info.get(b"Author").unwrap().as_string().unwrap();
+        assert_eq!(doc.text_string(6, 0, author).unwrap(), "PDF Explorer");
+    }
+
+    // A revision that doesn't go with the version.
+    let mismatched = [
+        (replace_first(bytes, b"/R 6", b"/R 4"), "/R 4 with /V 5"),
+        (
+            replace_first(
+                include_bytes!("../test_encrypted_aes_128.pdf"),
+                b"/R 4",
+                b"/R 6",
+            ),
+            "/R 6 with /V 4",
+        ),
+    ];
+    for (bytes, problem) in mismatched {
+        let doc = Document::open_with_password(&bytes, b"user").unwrap();
+        assert_eq!(
+            doc.encryption_error(),
+            Some(&EncryptionError::BadEncryptDict(problem.to_string()))
+        );
+    }
+}
+
+#[test]
+fn test_document_encrypted_object_streams() {
+    // RC4, with the /Info dictionary (object 6) in an object stream, which is decrypted as a whole.
+    let bytes = include_bytes!("../test_encrypted_object_streams.pdf");
+    let doc = Document::open(bytes).unwrap();
+    assert!(doc.is_encrypted());
+    let info = match doc.resolve(reference(doc.trailer(), b"Info")) {
+        Some(Object::Dictionary(d)) => d,
+        _ => panic!("no /Info dictionary"),
+    };
+    let title = info.get(b"Title").unwrap().as_string().unwrap();
+    assert_eq!(doc.text_string(6, 0, title).unwrap(), "Hello");
+    let content = match &doc.object(4).unwrap().object {
+        Object::Stream(s) => doc.stream_data(4, 0, s).unwrap(),
+        _ => panic!("object 4 is not a stream"),
+    };
+    assert_eq!(
+        &content[..],
+        b"BT\n/F1 12 Tf\n72 712 Td\n(Hello World) Tj\nET\n"
+    );
+}
+
+#[cfg(test)]
+// The reference that is the value of `key` in `dict`.
+fn reference<'d>(dict: &'d DictionaryObject, key: &[u8]) -> &'d IndirectObjectReference<'d> {
+    match dict.get(key) {
+        Some(ObjectOrReference::Reference(r)) => r,
+        _ => panic!("not a reference"),
+    }
+}
+
+#[test]
+fn test_document_resolve() {
+    let bytes = with_update(b"");
+    let doc = Document::open(&bytes).unwrap();
+    let info = doc.resolve(reference(doc.trailer(), b"Info")).unwrap();
+    let mut out: Vec<u8> = vec![];
+    info.serialize_to(&mut out).unwrap();
+    assert_eq!(out, b"<< /Title (Updated) >>");
+    let (_, wrong_generation) = indirect_object_reference(b"6 1 R").unwrap();
+    assert!(doc.resolve(&wrong_generation).is_none());
+
+    // Forwards, the latest definition wins too.
+    let file = parse_pdf(&bytes).unwrap();
+    let info = file.resolve(reference(doc.trailer(), b"Info")).unwrap();
+    let mut out: Vec<u8> = vec![];
+    info.serialize_to(&mut out).unwrap();
+    assert_eq!(out, b"<< /Title (Updated) >>");
+    assert!(file.resolve(&wrong_generation).is_none());
+    let (_, root) = indirect_object_reference(b"1 0 R").unwrap();
+    assert!(matches!(file.resolve(&root), Some(Object::Dictionary(_))));
+}
+
+#[test]
+fn test_resolve_freed_object() {
+    // An update that deletes object 5.
+    let mut bytes = include_bytes!("../test_simple.pdf").to_vec();
+    let xref_offset = bytes.len();
+    bytes.extend_from_slice(
+        format!(
+            "xref\n0 1\n0000000005 65535 f \n5 1\n0000000000 00001 f \ntrailer\n<< /Size 7 /Root 1 0 R /Prev 468 >>\nstartxref\n{}\n%%EOF\n",
+            xref_offset
+        )
+        .as_bytes(),
+    );
+    let (_, five) = indirect_object_reference(b"5 0 R").unwrap();
+    let (_, four) = indirect_object_reference(b"4 0 R").unwrap();
+    let doc = Document::open(&bytes).unwrap();
+    assert!(doc.resolve(&five).is_none());
+    assert!(doc.resolve(&four).is_some());
+    let file = parse_pdf(&bytes).unwrap();
+    assert!(file.resolve(&five).is_none());
+    assert!(file.resolve(&four).is_some());
+}
+
+#[test]
+fn test_document_resolve_compressed() {
+    let bytes = include_bytes!("../test_xref_stream.pdf");
+    let doc = Document::open(bytes).unwrap();
+    assert_eq!(
+        doc.compressed_objects()
+            .map(|(n, o)| (n, o.container, o.index))
+            .collect::<Vec<_>>(),
+        vec![(1, 7, 0), (2, 7, 1), (3, 7, 2), (5, 7, 3), (6, 7, 4)]
+    );
+    let root = doc.resolve(reference(doc.trailer(), b"Root")).unwrap();
+    let mut out: Vec<u8> = vec![];
+    root.serialize_to(&mut out).unwrap();
+    assert_eq!(out, b"<< /Type /Catalog /Pages 2 0 R >>");
+    // The content stream is not in the object stream.
+    let (_, content) = indirect_object_reference(b"4 0 R").unwrap();
+    assert!(matches!(doc.resolve(&content), Some(Object::Stream(_))));
+}
+
+#[test]
+fn test_indirect_length() {
+    // An update adding a stream (object 7) whose data contains "endstream", and whose length is in object 8.
+    let data = b"BT (not endstream) Tj ET\nendstream\n";
+    let mut bytes = include_bytes!("../test_simple.pdf").to_vec();
+    let stream_offset = bytes.len();
+    bytes.extend_from_slice(b"7 0 obj\n<< /Length 8 0 R >>\nstream\n");
+    bytes.extend_from_slice(data);
+    bytes.extend_from_slice(b"\nendstream\nendobj\n");
+    let length_offset = bytes.len();
+    bytes.extend_from_slice(format!("8 0 obj\n{}\nendobj\n", data.len()).as_bytes());
+    let xref_offset = bytes.len();
+    bytes.extend_from_slice(
+        format!(
+            "xref\n7 2\n{:010} 00000 n \n{:010} 00000 n \ntrailer\n<< /Size 9 /Root 1 0 R /Prev 468 >>\nstartxref\n{}\n%%EOF\n",
+            stream_offset, length_offset, xref_offset
+        )
+        .as_bytes(),
+    );
+
+    let file = parse_pdf(&bytes).unwrap();
+    let mut out = vec![];
+    file.serialize_to(&mut out).unwrap();
+    assert_eq!(out, bytes);
+    let (_, stream) = indirect_object_reference(b"7 0 R").unwrap();
+    match file.resolve(&stream) {
+        Some(Object::Stream(s)) => {
+            assert_eq!(s.length(), StreamLength::Indirect(data.len()));
+            assert_eq!(s.data(), data);
+        }
+        x => panic!("not a stream: {:?}", x),
+    }
+
+    let doc = Document::open(&bytes).unwrap();
+    // Spans are offsets in the file, as when parsing forwards.
+    let def = doc.object(7).unwrap();
+    assert_eq!(def.span().start, stream_offset);
+    assert_eq!(def.object.span().end, length_offset - b"\nendobj\n".len());
+    match doc.resolve(&stream) {
+        Some(Object::Stream(s)) => assert_eq!(doc.stream_data(7, 0, s).unwrap(), &data[..]),
+        x => panic!("not a stream: {:?}", x),
+    }
+}
@@ -0,0 +1,162 @@
+// ==========================
+// Duplicate dictionary keys
+// ==========================
+// "Multiple entries in the same dictionary shall not have the same key" (7.3.7), but they do, and
+// readers differ on which one wins. This finds them in every dictionary in a file (including those
+// nested in other objects, stream dictionaries and trailers), with their offsets in the file.
+
+use super::*;
+
+// Adds the duplicate keys in `object`, and in everything inside it. For a parsed file, `offset` is
+// `None`, and offsets come from the spans; otherwise it's where `object` starts, and the offsets of
+// things inside it are found by adding up the lengths of what comes before them, written out.
+fn object_duplicate_keys(object: &Object, offset: Option<usize>, out: &mut Vec<DuplicateKey>) {
+    match object {
+        Object::Dictionary(dict) => dict_duplicate_keys(dict, offset, out),
+        Object::Stream(stream) => dict_duplicate_keys(&stream.dict, offset, out),
+        Object::Array(array) => {
+            let mut offset = offset.map(|offset| offset + b"[".len());
+            for part in &array.parts {
+                if let ArrayObjectPart::ObjectOrRef(ObjectOrReference::Object(o)) = part {
+                    object_duplicate_keys(o, offset, out);
+                }
+                offset = offset.map(|offset| offset + serialized_len(part));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn dict_duplicate_keys(
+    dict: &DictionaryObject,
+    offset: Option<usize>,
+    out: &mut Vec<DuplicateKey>,
+) {
+    let spans = offset.is_none();
+    let start = offset.unwrap_or(dict.span.start);
+    out.extend(
+        dict.duplicate_keys_from(spans)
+            .into_iter()
+            .map(|d| DuplicateKey {
+                key: d.key,
+                offsets: d.offsets.iter().map(|o| start + o).collect(),
+            }),
+    );
+    for (kv, kv_offset) in dict.key_value_pairs_with_offsets(spans) {
+        if let ObjectOrReference::Object(o) = &kv.value {
+            let value_offset =
+                offset.map(|_| start + kv_offset + serialized_len(&kv.key) + kv.ws.len());
+            object_duplicate_keys(o, value_offset, out);
+        }
+    }
+}
+
+impl PdfFile<'_> {
+    /// The duplicate keys in all the dictionaries in the file, with offsets from the start of the file.
+    pub fn duplicate_keys(&self) -> Vec<DuplicateKey> {
+        let mut out = vec![];
+        if self.parsed {
+            for section in &self.body_crossref_trailers {
+                for part in &section.body {
+                    if let BodyPart::ObjDef(def) = part {
+                        object_duplicate_keys(&def.object, None, &mut out);
+                    }
+                }
+                if let Some(t) = &section.cross_reference_table_and_trailer {
+                    dict_duplicate_keys(&t.trailer.dict, None, &mut out);
+                }
+            }
+            return out;
+        }
+        // Deserialized from JSON: the spans may be missing, or out of date.
+        let mut offset = self.before_header.len() + self.header.len();
+        for section in &self.body_crossref_trailers {
+            for part in &section.body {
+                if let BodyPart::ObjDef(def) = part {
+                    let object_offset = offset
+                        + serialized_len(&def.object_number)
+                        + def.ws1.len()
+                        + serialized_len(&def.generation_number)
+                        + def.ws2.len()
+                        + b"obj".len()
+                        + def.ws3.len();
+                    object_duplicate_keys(&def.object, Some(object_offset), &mut out);
+                }
+                offset += serialized_len(part);
+            }
+            if let Some(t) = &section.cross_reference_table_and_trailer {
+                let dict_offset = offset
+                    + serialized_len(&t.cross_reference_table)
+                    + b"trailer".len()
+                    + t.trailer.ws1.len();
+                dict_duplicate_keys(&t.trailer.dict, Some(dict_offset), &mut out);
+                offset += serialized_len(t);
+            }
+            offset += serialized_len(&section.startxref_offset_eof);
+        }
+        out
+    }
+}
+
+#[test]
+fn test_duplicate_keys() {
+    let input = b"<< /Type /Page /Count 1 /T#79pe /Pages /Type /Font >>";
+    let (_, dict) = with_own_context(input, || object_dictionary(input)).unwrap();
+    assert_eq!(
+        dict.duplicate_keys(),
+        vec![DuplicateKey {
+            key: b"Type".to_vec(),
+            offsets: vec![3, 24, 39],
+        }]
+    );
+    // The first one wins.
+    assert!(dict.get(b"Type").unwrap().as_name().unwrap().is(b"Page"));
+    assert_eq!(dict.len(), 4);
+    assert_eq!(
+        dict.keys().map(|k| k.decoded_bytes()).collect::<Vec<_>>(),
+        vec![&b"Type"[..], b"Count", b"Type", b"Type"]
+    );
+}
+
+#[test]
+fn test_file_duplicate_keys() {
+    let bytes = include_bytes!("../test_simple.pdf");
+    assert_eq!(parse_pdf(bytes).unwrap().duplicate_keys(), vec![]);
+
+    // A duplicate in a nested dictionary (in object 3), and one in the trailer.
+    let bytes = replace_first(
+        bytes,
+        b"/Font << /F1 5 0 R >>",
+        b"/Font << /F1 5 0 R /F1 6 0 R >>",
+    );
+    let bytes = replace_first(&bytes, b"/Info 6 0 R >>", b"/Info 6 0 R /Root 1 0 R >>");
+    let file = parse_pdf(&bytes).unwrap();
+    let duplicates = file.duplicate_keys();
+    assert_eq!(duplicates.len(), 2);
+    assert_eq!(duplicates[0].key, b"F1");
+    for offset in &duplicates[0].offsets {
+        assert!(bytes[*offset..].starts_with(b"/F1 "));
+    }
+    assert_eq!(duplicates[1].key, b"Root");
+    assert!(bytes[duplicates[1].offsets[0]..].starts_with(b"/Root 1 0 R /Info"));
+    assert!(bytes[duplicates[1].offsets[1]..].starts_with(b"/Root 1 0 R >>"));
+
+    // With junk before the header (which only a lenient parse accepts), offsets are still in the file.
+    let bytes = [&b"junk\n"[..], &bytes].concat();
+    let options = ParseOptions {
+        strict: false,
+        ..ParseOptions::default()
+    };
+    let file = parse_pdf_with_options(&bytes, &options).unwrap();
+    let duplicates = file.duplicate_keys();
+    assert_eq!(duplicates.len(), 2);
+    for offset in &duplicates[0].offsets {
+        assert!(bytes[*offset..].starts_with(b"/F1 "));
+    }
+    assert!(bytes[duplicates[1].offsets[0]..].starts_with(b"/Root 1 0 R /Info"));
+
+    // Deserialized from JSON (so without trusting the spans), the offsets are the same.
+    let json = serde_json::to_string(&file).unwrap();
+    let deserialized: PdfFile = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.duplicate_keys(), duplicates);
+}
@@ -0,0 +1,1433 @@
+// ========================================
+// Annex D: Character Sets and Encodings
+// ========================================
+// A simple font's /Encoding maps each single-byte code to a glyph name: one of the predefined
+// encodings (StandardEncoding, the default for Type 1 fonts; WinAnsiEncoding; MacRomanEncoding),
+// or the font's built-in one (Symbol and ZapfDingbats have their own), perhaps with some codes
+// changed by /Differences. Glyph names in turn stand for Unicode characters: those of the Adobe
+// Glyph List For New Fonts (Latin, Greek, Cyrillic, Hebrew and Arabic letters, and symbols like
+// `arrowright` or `summation`) are in the table below, along with the pieces of Symbol's large
+// brackets. Others can spell out their characters, like `uni00E9` or `u1F600`, or combine them,
+// like `f_f_i` (as the Adobe Glyph List Specification describes). Anything after a period, as in
+// `a.swash`, is a variant that stands for the same characters. ZapfDingbats' glyphs are named
+// `a1` to `a206`, which only mean dingbats in that font.
+
+/// The predefined encoding called `name` (as the value of /Encoding or /BaseEncoding).
+pub(super) fn predefined_encoding(name: &[u8]) -> Option<&'static [Option<&'static str>; 256]> {
+    match name {
+        b"StandardEncoding" => Some(&STANDARD_ENCODING),
+        b"WinAnsiEncoding" => Some(&WIN_ANSI_ENCODING),
+        b"MacRomanEncoding" => Some(&MAC_ROMAN_ENCODING),
+        _ => None,
+    }
+}
+
+/// The built-in encoding of standard font `name`, if it isn't StandardEncoding.
+pub(super) fn builtin_encoding(name: &str) -> Option<&'static [Option<&'static str>; 256]> {
+    match name {
+        "Symbol" => Some(&SYMBOL_ENCODING),
+        "ZapfDingbats" => Some(&ZAPF_DINGBATS_ENCODING),
+        _ => None,
+    }
+}
+
+/// The text that ZapfDingbats' glyph `name` stands for, if it's one of its dingbats.
+pub(super) fn dingbat_text(name: &[u8]) -> Option<String> {
+    let i = DINGBAT_NAMES
+        .binary_search_by(|(n, _)| n.as_bytes().cmp(name))
+        .ok()?;
+    Some(DINGBAT_NAMES[i].1.to_string())
+}
+
+/// The text that glyph `name` stands for, if it's known.
+pub(super) fn glyph_name_text(name: &[u8]) -> Option<String> {
+    let name = match name.iter().position(|&c| c == b'.') {
+        Some(period) => &name[..period],
+        None => name,
+    };
+    let mut text = String::new();
+    let mut known = false;
+    for component in name.split(|&c| c == b'_') {
+        if let Some(component_text) = glyph_name_component_text(component) {
+            text.push_str(&component_text);
+            known = true;
+        }
+    }
+    known.then_some(text)
+}
+
+// The text for one of the `_`-separated components of a glyph name.
+fn glyph_name_component_text(component: &[u8]) -> Option<String> {
+    if let Ok(i) = GLYPH_NAMES.binary_search_by(|(name, _)| name.as_bytes().cmp(component)) {
+        return Some(GLYPH_NAMES[i].1.to_string());
+    }
+    let hex = |digits: &[u8]| {
+        let digits = std::str::from_utf8(digits).ok()?;
+        if !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        char::from_u32(u32::from_str_radix(digits, 16).ok()?)
+    };
+    if let Some(digits) = component.strip_prefix(b"uni") {
+        // One or more UTF-16 code units, of 4 digits each (but no surrogates).
+        if digits.is_empty() || digits.len() % 4 != 0 {
+            return None;
+        }
+        return digits.chunks(4).map(hex).collect();
+    }
+    match component.strip_prefix(b"u") {
+        Some(digits) if (4..=6).contains(&digits.len()) => hex(digits).map(String::from),
+        _ => None,
+    }
+}
+
+// (glyph name, character), sorted by name.
+static GLYPH_NAMES: [(&str, char); 901] = [
+    ("A", 'A'),
+    ("AE", '\u{00C6}'),
+    ("AEacute", '\u{01FC}'),
+    ("Aacute", '\u{00C1}'),
+    ("Abreve", '\u{0102}'),
+    ("Acircumflex", '\u{00C2}'),
+    ("Adieresis", '\u{00C4}'),
+    ("Agrave", '\u{00C0}'),
+    ("Alpha", '\u{0391}'),
+    ("Alphatonos", '\u{0386}'),
+    ("Amacron", '\u{0100}'),
+    ("Aogonek", '\u{0104}'),
+    ("Aring", '\u{00C5}'),
+    ("Aringacute", '\u{01FA}'),
+    ("Atilde", '\u{00C3}'),
+    ("B", 'B'),
+    ("Beta", '\u{0392}'),
+    ("C", 'C'),
+    ("Cacute", '\u{0106}'),
+    ("Ccaron", '\u{010C}'),
+    ("Ccedilla", '\u{00C7}'),
+    ("Ccircumflex", '\u{0108}'),
+    ("Cdotaccent", '\u{010A}'),
+    ("Chi", '\u{03A7}'),
+    ("D", 'D'),
+    ("Dcaron", '\u{010E}'),
+    ("Dcroat", '\u{0110}'),
+    ("Delta", '\u{2206}'),
+    ("E", 'E'),
+    ("Eacute", '\u{00C9}'),
+    ("Ebreve", '\u{0114}'),
+    ("Ecaron", '\u{011A}'),
+    ("Ecircumflex", '\u{00CA}'),
+    ("Edieresis", '\u{00CB}'),
+    ("Edotaccent", '\u{0116}'),
+    ("Egrave", '\u{00C8}'),
+    ("Emacron", '\u{0112}'),
+    ("Eng", '\u{014A}'),
+    ("Eogonek", '\u{0118}'),
+    ("Epsilon", '\u{0395}'),
+    ("Epsilontonos", '\u{0388}'),
+    ("Eta", '\u{0397}'),
+    ("Etatonos", '\u{0389}'),
+    ("Eth", '\u{00D0}'),
+    ("Euro", '\u{20AC}'),
+    ("F", 'F'),
+    ("G", 'G'),
+    ("Gamma", '\u{0393}'),
+    ("Gbreve", '\u{011E}'),
+    ("Gcaron", '\u{01E6}'),
+    ("Gcircumflex", '\u{011C}'),
+    ("Gcommaaccent", '\u{0122}'),
+    ("Gdotaccent", '\u{0120}'),
+    ("H", 'H'),
+    ("H18533", '\u{25CF}'),
+    ("H18543", '\u{25AA}'),
+    ("H18551", '\u{25AB}'),
+    ("H22073", '\u{25A1}'),
+    ("Hbar", '\u{0126}'),
+    ("Hcircumflex", '\u{0124}'),
+    ("I", 'I'),
+    ("IJ", '\u{0132}'),
+    ("Iacute", '\u{00CD}'),
+    ("Ibreve", '\u{012C}'),
+    ("Icircumflex", '\u{00CE}'),
+    ("Idieresis", '\u{00CF}'),
+    ("Idotaccent", '\u{0130}'),
+    ("Ifraktur", '\u{2111}'),
+    ("Igrave", '\u{00CC}'),
+    ("Imacron", '\u{012A}'),
+    ("Iogonek", '\u{012E}'),
+    ("Iota", '\u{0399}'),
+    ("Iotadieresis", '\u{03AA}'),
+    ("Iotatonos", '\u{038A}'),
+    ("Itilde", '\u{0128}'),
+    ("J", 'J'),
+    ("Jcircumflex", '\u{0134}'),
+    ("K", 'K'),
+    ("Kappa", '\u{039A}'),
+    ("Kcommaaccent", '\u{0136}'),
+    ("L", 'L'),
+    ("Lacute", '\u{0139}'),
+    ("Lambda", '\u{039B}'),
+    ("Lcaron", '\u{013D}'),
+    ("Lcommaaccent", '\u{013B}'),
+    ("Ldot", '\u{013F}'),
+    ("Lslash", '\u{0141}'),
+    ("M", 'M'),
+    ("Mu", '\u{039C}'),
+    ("N", 'N'),
+    ("Nacute", '\u{0143}'),
+    ("Ncaron", '\u{0147}'),
+    ("Ncommaaccent", '\u{0145}'),
+    ("Ntilde", '\u{00D1}'),
+    ("Nu", '\u{039D}'),
+    ("O", 'O'),
+    ("OE", '\u{0152}'),
+    ("Oacute", '\u{00D3}'),
+    ("Obreve", '\u{014E}'),
+    ("Ocircumflex", '\u{00D4}'),
+    ("Odieresis", '\u{00D6}'),
+    ("Ograve", '\u{00D2}'),
+    ("Ohorn", '\u{01A0}'),
+    ("Ohungarumlaut", '\u{0150}'),
+    ("Omacron", '\u{014C}'),
+    ("Omega", '\u{2126}'),
+    ("Omegatonos", '\u{038F}'),
+    ("Omicron", '\u{039F}'),
+    ("Omicrontonos", '\u{038C}'),
+    ("Oslash", '\u{00D8}'),
+    ("Oslashacute", '\u{01FE}'),
+    ("Otilde", '\u{00D5}'),
+    ("P", 'P'),
+    ("Phi", '\u{03A6}'),
+    ("Pi", '\u{03A0}'),
+    ("Psi", '\u{03A8}'),
+    ("Q", 'Q'),
+    ("R", 'R'),
+    ("Racute", '\u{0154}'),
+    ("Rcaron", '\u{0158}'),
+    ("Rcommaaccent", '\u{0156}'),
+    ("Rfraktur", '\u{211C}'),
+    ("Rho", '\u{03A1}'),
+    ("S", 'S'),
+    ("SF010000", '\u{250C}'),
+    ("SF020000", '\u{2514}'),
+    ("SF030000", '\u{2510}'),
+    ("SF040000", '\u{2518}'),
+    ("SF050000", '\u{253C}'),
+    ("SF060000", '\u{252C}'),
+    ("SF070000", '\u{2534}'),
+    ("SF080000", '\u{251C}'),
+    ("SF090000", '\u{2524}'),
+    ("SF100000", '\u{2500}'),
+    ("SF110000", '\u{2502}'),
+    ("SF190000", '\u{2561}'),
+    ("SF200000", '\u{2562}'),
+    ("SF210000", '\u{2556}'),
+    ("SF220000", '\u{2555}'),
+    ("SF230000", '\u{2563}'),
+    ("SF240000", '\u{2551}'),
+    ("SF250000", '\u{2557}'),
+    ("SF260000", '\u{255D}'),
+    ("SF270000", '\u{255C}'),
+    ("SF280000", '\u{255B}'),
+    ("SF360000", '\u{255E}'),
+    ("SF370000", '\u{255F}'),
+    ("SF380000", '\u{255A}'),
+    ("SF390000", '\u{2554}'),
+    ("SF400000", '\u{2569}'),
+    ("SF410000", '\u{2566}'),
+    ("SF420000", '\u{2560}'),
+    ("SF430000", '\u{2550}'),
+    ("SF440000", '\u{256C}'),
+    ("SF450000", '\u{2567}'),
+    ("SF460000", '\u{2568}'),
+    ("SF470000", '\u{2564}'),
+    ("SF480000", '\u{2565}'),
+    ("SF490000", '\u{2559}'),
+    ("SF500000", '\u{2558}'),
+    ("SF510000", '\u{2552}'),
+    ("SF520000", '\u{2553}'),
+    ("SF530000", '\u{256B}'),
+    ("SF540000", '\u{256A}'),
+    ("Sacute", '\u{015A}'),
+    ("Scaron", '\u{0160}'),
+    ("Scedilla", '\u{015E}'),
+    ("Scircumflex", '\u{015C}'),
+    ("Scommaaccent", '\u{0218}'),
+    ("Sigma", '\u{03A3}'),
+    ("T", 'T'),
+    ("Tau", '\u{03A4}'),
+    ("Tbar", '\u{0166}'),
+    ("Tcaron", '\u{0164}'),
+    ("Tcommaaccent", '\u{0162}'),
+    ("Theta", '\u{0398}'),
+    ("Thorn", '\u{00DE}'),
+    ("U", 'U'),
+    ("Uacute", '\u{00DA}'),
+    ("Ubreve", '\u{016C}'),
+    ("Ucircumflex", '\u{00DB}'),
+    ("Udieresis", '\u{00DC}'),
+    ("Ugrave", '\u{00D9}'),
+    ("Uhorn", '\u{01AF}'),
+    ("Uhungarumlaut", '\u{0170}'),
+    ("Umacron", '\u{016A}'),
+    ("Uogonek", '\u{0172}'),
+    ("Upsilon", '\u{03A5}'),
+    ("Upsilon1", '\u{03D2}'),
+    ("Upsilondieresis", '\u{03AB}'),
+    ("Upsilontonos", '\u{038E}'),
+    ("Uring", '\u{016E}'),
+    ("Utilde", '\u{0168}'),
+    ("V", 'V'),
+    ("W", 'W'),
+    ("Wacute", '\u{1E82}'),
+    ("Wcircumflex", '\u{0174}'),
+    ("Wdieresis", '\u{1E84}'),
+    ("Wgrave", '\u{1E80}'),
+    ("X", 'X'),
+    ("Xi", '\u{039E}'),
+    ("Y", 'Y'),
+    ("Yacute", '\u{00DD}'),
+    ("Ycircumflex", '\u{0176}'),
+    ("Ydieresis", '\u{0178}'),
+    ("Ygrave", '\u{1EF2}'),
+    ("Z", 'Z'),
+    ("Zacute", '\u{0179}'),
+    ("Zcaron", '\u{017D}'),
+    ("Zdotaccent", '\u{017B}'),
+    ("Zeta", '\u{0396}'),
+    ("a", 'a'),
+    ("aacute", '\u{00E1}'),
+    ("abreve", '\u{0103}'),
+    ("acircumflex", '\u{00E2}'),
+    ("acute", '\u{00B4}'),
+    ("acutecomb", '\u{0301}'),
+    ("adieresis", '\u{00E4}'),
+    ("ae", '\u{00E6}'),
+    ("aeacute", '\u{01FD}'),
+    ("afii00208", '\u{2015}'),
+    ("afii10017", '\u{0410}'),
+    ("afii10018", '\u{0411}'),
+    ("afii10019", '\u{0412}'),
+    ("afii10020", '\u{0413}'),
+    ("afii10021", '\u{0414}'),
+    ("afii10022", '\u{0415}'),
+    ("afii10023", '\u{0401}'),
+    ("afii10024", '\u{0416}'),
+    ("afii10025", '\u{0417}'),
+    ("afii10026", '\u{0418}'),
+    ("afii10027", '\u{0419}'),
+    ("afii10028", '\u{041A}'),
+    ("afii10029", '\u{041B}'),
+    ("afii10030", '\u{041C}'),
+    ("afii10031", '\u{041D}'),
+    ("afii10032", '\u{041E}'),
+    ("afii10033", '\u{041F}'),
+    ("afii10034", '\u{0420}'),
+    ("afii10035", '\u{0421}'),
+    ("afii10036", '\u{0422}'),
+    ("afii10037", '\u{0423}'),
+    ("afii10038", '\u{0424}'),
+    ("afii10039", '\u{0425}'),
+    ("afii10040", '\u{0426}'),
+    ("afii10041", '\u{0427}'),
+    ("afii10042", '\u{0428}'),
+    ("afii10043", '\u{0429}'),
+    ("afii10044", '\u{042A}'),
+    ("afii10045", '\u{042B}'),
+    ("afii10046", '\u{042C}'),
+    ("afii10047", '\u{042D}'),
+    ("afii10048", '\u{042E}'),
+    ("afii10049", '\u{042F}'),
+    ("afii10050", '\u{0490}'),
+    ("afii10051", '\u{0402}'),
+    ("afii10052", '\u{0403}'),
+    ("afii10053", '\u{0404}'),
+    ("afii10054", '\u{0405}'),
+    ("afii10055", '\u{0406}'),
+    ("afii10056", '\u{0407}'),
+    ("afii10057", '\u{0408}'),
+    ("afii10058", '\u{0409}'),
+    ("afii10059", '\u{040A}'),
+    ("afii10060", '\u{040B}'),
+    ("afii10061", '\u{040C}'),
+    ("afii10062", '\u{040E}'),
+    ("afii10065", '\u{0430}'),
+    ("afii10066", '\u{0431}'),
+    ("afii10067", '\u{0432}'),
+    ("afii10068", '\u{0433}'),
+    ("afii10069", '\u{0434}'),
+    ("afii10070", '\u{0435}'),
+    ("afii10071", '\u{0451}'),
+    ("afii10072", '\u{0436}'),
+    ("afii10073", '\u{0437}'),
+    ("afii10074", '\u{0438}'),
+    ("afii10075", '\u{0439}'),
+    ("afii10076", '\u{043A}'),
+    ("afii10077", '\u{043B}'),
+    ("afii10078", '\u{043C}'),
+    ("afii10079", '\u{043D}'),
+    ("afii10080", '\u{043E}'),
+    ("afii10081", '\u{043F}'),
+    ("afii10082", '\u{0440}'),
+    ("afii10083", '\u{0441}'),
+    ("afii10084", '\u{0442}'),
+    ("afii10085", '\u{0443}'),
+    ("afii10086", '\u{0444}'),
+    ("afii10087", '\u{0445}'),
+    ("afii10088", '\u{0446}'),
+    ("afii10089", '\u{0447}'),
+    ("afii10090", '\u{0448}'),
+    ("afii10091", '\u{0449}'),
+    ("afii10092", '\u{044A}'),
+    ("afii10093", '\u{044B}'),
+    ("afii10094", '\u{044C}'),
+    ("afii10095", '\u{044D}'),
+    ("afii10096", '\u{044E}'),
+    ("afii10097", '\u{044F}'),
+    ("afii10098", '\u{0491}'),
+    ("afii10099", '\u{0452}'),
+    ("afii10100", '\u{0453}'),
+    ("afii10101", '\u{0454}'),
+    ("afii10102", '\u{0455}'),
+    ("afii10103", '\u{0456}'),
+    ("afii10104", '\u{0457}'),
+    ("afii10105", '\u{0458}'),
+    ("afii10106", '\u{0459}'),
+    ("afii10107", '\u{045A}'),
+    ("afii10108", '\u{045B}'),
+    ("afii10109", '\u{045C}'),
+    ("afii10110", '\u{045E}'),
+    ("afii10145", '\u{040F}'),
+    ("afii10146", '\u{0462}'),
+    ("afii10147", '\u{0472}'),
+    ("afii10148", '\u{0474}'),
+    ("afii10193", '\u{045F}'),
+    ("afii10194", '\u{0463}'),
+    ("afii10195", '\u{0473}'),
+    ("afii10196", '\u{0475}'),
+    ("afii10846", '\u{04D9}'),
+    ("afii299", '\u{200E}'),
+    ("afii300", '\u{200F}'),
+    ("afii301", '\u{200D}'),
+    ("afii57381", '\u{066A}'),
+    ("afii57388", '\u{060C}'),
+    ("afii57392", '\u{0660}'),
+    ("afii57393", '\u{0661}'),
+    ("afii57394", '\u{0662}'),
+    ("afii57395", '\u{0663}'),
+    ("afii57396", '\u{0664}'),
+    ("afii57397", '\u{0665}'),
+    ("afii57398", '\u{0666}'),
+    ("afii57399", '\u{0667}'),
+    ("afii57400", '\u{0668}'),
+    ("afii57401", '\u{0669}'),
+    ("afii57403", '\u{061B}'),
+    ("afii57407", '\u{061F}'),
+    ("afii57409", '\u{0621}'),
+    ("afii57410", '\u{0622}'),
+    ("afii57411", '\u{0623}'),
+    ("afii57412", '\u{0624}'),
+    ("afii57413", '\u{0625}'),
+    ("afii57414", '\u{0626}'),
+    ("afii57415", '\u{0627}'),
+    ("afii57416", '\u{0628}'),
+    ("afii57417", '\u{0629}'),
+    ("afii57418", '\u{062A}'),
+    ("afii57419", '\u{062B}'),
+    ("afii57420", '\u{062C}'),
+    ("afii57421", '\u{062D}'),
+    ("afii57422", '\u{062E}'),
+    ("afii57423", '\u{062F}'),
+    ("afii57424", '\u{0630}'),
+    ("afii57425", '\u{0631}'),
+    ("afii57426", '\u{0632}'),
+    ("afii57427", '\u{0633}'),
+    ("afii57428", '\u{0634}'),
+    ("afii57429", '\u{0635}'),
+    ("afii57430", '\u{0636}'),
+    ("afii57431", '\u{0637}'),
+    ("afii57432", '\u{0638}'),
+    ("afii57433", '\u{0639}'),
+    ("afii57434", '\u{063A}'),
+    ("afii57440", '\u{0640}'),
+    ("afii57441", '\u{0641}'),
+    ("afii57442", '\u{0642}'),
+    ("afii57443", '\u{0643}'),
+    ("afii57444", '\u{0644}'),
+    ("afii57445", '\u{0645}'),
+    ("afii57446", '\u{0646}'),
+    ("afii57447", '\u{0647}'),
+    ("afii57448", '\u{0648}'),
+    ("afii57449", '\u{0649}'),
+    ("afii57450", '\u{064A}'),
+    ("afii57451", '\u{064B}'),
+    ("afii57452", '\u{064C}'),
+    ("afii57453", '\u{064D}'),
+    ("afii57454", '\u{064E}'),
+    ("afii57455", '\u{064F}'),
+    ("afii57456", '\u{0650}'),
+    ("afii57457", '\u{0651}'),
+    ("afii57458", '\u{0652}'),
+    ("afii57505", '\u{06A4}'),
+    ("afii57506", '\u{067E}'),
+    ("afii57507", '\u{0686}'),
+    ("afii57508", '\u{0698}'),
+    ("afii57509", '\u{06AF}'),
+    ("afii57511", '\u{0679}'),
+    ("afii57512", '\u{0688}'),
+    ("afii57513", '\u{0691}'),
+    ("afii57514", '\u{06BA}'),
+    ("afii57519", '\u{06D2}'),
+    ("afii57534", '\u{06D5}'),
+    ("afii57636", '\u{20AA}'),
+    ("afii57645", '\u{05BE}'),
+    ("afii57658", '\u{05C3}'),
+    ("afii57664", '\u{05D0}'),
+    ("afii57665", '\u{05D1}'),
+    ("afii57666", '\u{05D2}'),
+    ("afii57667", '\u{05D3}'),
+    ("afii57668", '\u{05D4}'),
+    ("afii57669", '\u{05D5}'),
+    ("afii57670", '\u{05D6}'),
+    ("afii57671", '\u{05D7}'),
+    ("afii57672", '\u{05D8}'),
+    ("afii57673", '\u{05D9}'),
+    ("afii57674", '\u{05DA}'),
+    ("afii57675", '\u{05DB}'),
+    ("afii57676", '\u{05DC}'),
+    ("afii57677", '\u{05DD}'),
+    ("afii57678", '\u{05DE}'),
+    ("afii57679", '\u{05DF}'),
+    ("afii57680", '\u{05E0}'),
+    ("afii57681", '\u{05E1}'),
+    ("afii57682", '\u{05E2}'),
+    ("afii57683", '\u{05E3}'),
+    ("afii57684", '\u{05E4}'),
+    ("afii57685", '\u{05E5}'),
+    ("afii57686", '\u{05E6}'),
+    ("afii57687", '\u{05E7}'),
+    ("afii57688", '\u{05E8}'),
+    ("afii57689", '\u{05E9}'),
+    ("afii57690", '\u{05EA}'),
+    ("afii57694", '\u{FB2A}'),
+    ("afii57695", '\u{FB2B}'),
+    ("afii57700", '\u{FB4B}'),
+    ("afii57705", '\u{FB1F}'),
+    ("afii57716", '\u{05F0}'),
+    ("afii57717", '\u{05F1}'),
+    ("afii57718", '\u{05F2}'),
+    ("afii57723", '\u{FB35}'),
+    ("afii57793", '\u{05B4}'),
+    ("afii57794", '\u{05B5}'),
+    ("afii57795", '\u{05B6}'),
+    ("afii57796", '\u{05BB}'),
+    ("afii57797", '\u{05B8}'),
+    ("afii57798", '\u{05B7}'),
+    ("afii57799", '\u{05B0}'),
+    ("afii57800", '\u{05B2}'),
+    ("afii57801", '\u{05B1}'),
+    ("afii57802", '\u{05B3}'),
+    ("afii57803", '\u{05C2}'),
+    ("afii57804", '\u{05C1}'),
+    ("afii57806", '\u{05B9}'),
+    ("afii57807", '\u{05BC}'),
+    ("afii57839", '\u{05BD}'),
+    ("afii57841", '\u{05BF}'),
+    ("afii57842", '\u{05C0}'),
+    ("afii57929", '\u{02BC}'),
+    ("afii61248", '\u{2105}'),
+    ("afii61289", '\u{2113}'),
+    ("afii61352", '\u{2116}'),
+    ("afii61573", '\u{202C}'),
+    ("afii61574", '\u{202D}'),
+    ("afii61575", '\u{202E}'),
+    ("afii61664", '\u{200C}'),
+    ("afii63167", '\u{066D}'),
+    ("afii64937", '\u{02BD}'),
+    ("agrave", '\u{00E0}'),
+    ("aleph", '\u{2135}'),
+    ("alpha", '\u{03B1}'),
+    ("alphatonos", '\u{03AC}'),
+    ("amacron", '\u{0101}'),
+    ("ampersand", '&'),
+    ("angle", '\u{2220}'),
+    ("angleleft", '\u{2329}'),
+    ("angleright", '\u{232A}'),
+    ("anoteleia", '\u{0387}'),
+    ("aogonek", '\u{0105}'),
+    ("approxequal", '\u{2248}'),
+    ("aring", '\u{00E5}'),
+    ("aringacute", '\u{01FB}'),
+    ("arrowboth", '\u{2194}'),
+    ("arrowdblboth", '\u{21D4}'),
+    ("arrowdbldown", '\u{21D3}'),
+    ("arrowdblleft", '\u{21D0}'),
+    ("arrowdblright", '\u{21D2}'),
+    ("arrowdblup", '\u{21D1}'),
+    ("arrowdown", '\u{2193}'),
+    ("arrowhorizex", '\u{23AF}'),
+    ("arrowleft", '\u{2190}'),
+    ("arrowright", '\u{2192}'),
+    ("arrowup", '\u{2191}'),
+    ("arrowupdn", '\u{2195}'),
+    ("arrowupdnbse", '\u{21A8}'),
+    ("arrowvertex", '\u{23D0}'),
+    ("asciicircum", '^'),
+    ("asciitilde", '~'),
+    ("asterisk", '*'),
+    ("asteriskmath", '\u{2217}'),
+    ("at", '@'),
+    ("atilde", '\u{00E3}'),
+    ("b", 'b'),
+    ("backslash", '\\'),
+    ("bar", '|'),
+    ("beta", '\u{03B2}'),
+    ("block", '\u{2588}'),
+    ("braceex", '\u{23AA}'),
+    ("braceleft", '{'),
+    ("braceleftbt", '\u{23A9}'),
+    ("braceleftmid", '\u{23A8}'),
+    ("bracelefttp", '\u{23A7}'),
+    ("braceright", '}'),
+    ("bracerightbt", '\u{23AD}'),
+    ("bracerightmid", '\u{23AC}'),
+    ("bracerighttp", '\u{23AB}'),
+    ("bracketleft", '['),
+    ("bracketleftbt", '\u{23A3}'),
+    ("bracketleftex", '\u{23A2}'),
+    ("bracketlefttp", '\u{23A1}'),
+    ("bracketright", ']'),
+    ("bracketrightbt", '\u{23A6}'),
+    ("bracketrightex", '\u{23A5}'),
+    ("bracketrighttp", '\u{23A4}'),
+    ("breve", '\u{02D8}'),
+    ("brokenbar", '\u{00A6}'),
+    ("bullet", '\u{2022}'),
+    ("c", 'c'),
+    ("cacute", '\u{0107}'),
+    ("caron", '\u{02C7}'),
+    ("carriagereturn", '\u{21B5}'),
+    ("ccaron", '\u{010D}'),
+    ("ccedilla", '\u{00E7}'),
+    ("ccircumflex", '\u{0109}'),
+    ("cdotaccent", '\u{010B}'),
+    ("cedilla", '\u{00B8}'),
+    ("cent", '\u{00A2}'),
+    ("chi", '\u{03C7}'),
+    ("circle", '\u{25CB}'),
+    ("circlemultiply", '\u{2297}'),
+    ("circleplus", '\u{2295}'),
+    ("circumflex", '\u{02C6}'),
+    ("club", '\u{2663}'),
+    ("colon", ':'),
+    ("colonmonetary", '\u{20A1}'),
+    ("comma", ','),
+    ("congruent", '\u{2245}'),
+    ("copyright", '\u{00A9}'),
+    ("copyrightsans", '\u{00A9}'),
+    ("copyrightserif", '\u{00A9}'),
+    ("currency", '\u{00A4}'),
+    ("d", 'd'),
+    ("dagger", '\u{2020}'),
+    ("daggerdbl", '\u{2021}'),
+    ("dcaron", '\u{010F}'),
+    ("dcroat", '\u{0111}'),
+    ("degree", '\u{00B0}'),
+    ("delta", '\u{03B4}'),
+    ("diamond", '\u{2666}'),
+    ("dieresis", '\u{00A8}'),
+    ("dieresistonos", '\u{0385}'),
+    ("divide", '\u{00F7}'),
+    ("dkshade", '\u{2593}'),
+    ("dnblock", '\u{2584}'),
+    ("dollar", '$'),
+    ("dong", '\u{20AB}'),
+    ("dotaccent", '\u{02D9}'),
+    ("dotbelowcomb", '\u{0323}'),
+    ("dotlessi", '\u{0131}'),
+    ("dotmath", '\u{22C5}'),
+    ("e", 'e'),
+    ("eacute", '\u{00E9}'),
+    ("ebreve", '\u{0115}'),
+    ("ecaron", '\u{011B}'),
+    ("ecircumflex", '\u{00EA}'),
+    ("edieresis", '\u{00EB}'),
+    ("edotaccent", '\u{0117}'),
+    ("egrave", '\u{00E8}'),
+    ("eight", '8'),
+    ("eightinferior", '\u{2088}'),
+    ("eightsuperior", '\u{2078}'),
+    ("element", '\u{2208}'),
+    ("ellipsis", '\u{2026}'),
+    ("emacron", '\u{0113}'),
+    ("emdash", '\u{2014}'),
+    ("emptyset", '\u{2205}'),
+    ("endash", '\u{2013}'),
+    ("eng", '\u{014B}'),
+    ("eogonek", '\u{0119}'),
+    ("epsilon", '\u{03B5}'),
+    ("epsilontonos", '\u{03AD}'),
+    ("equal", '='),
+    ("equivalence", '\u{2261}'),
+    ("estimated", '\u{212E}'),
+    ("eta", '\u{03B7}'),
+    ("etatonos", '\u{03AE}'),
+    ("eth", '\u{00F0}'),
+    ("exclam", '!'),
+    ("exclamdbl", '\u{203C}'),
+    ("exclamdown", '\u{00A1}'),
+    ("existential", '\u{2203}'),
+    ("f", 'f'),
+    ("female", '\u{2640}'),
+    ("ff", '\u{FB00}'),
+    ("ffi", '\u{FB03}'),
+    ("ffl", '\u{FB04}'),
+    ("fi", '\u{FB01}'),
+    ("figuredash", '\u{2012}'),
+    ("filledbox", '\u{25A0}'),
+    ("filledrect", '\u{25AC}'),
+    ("five", '5'),
+    ("fiveeighths", '\u{215D}'),
+    ("fiveinferior", '\u{2085}'),
+    ("fivesuperior", '\u{2075}'),
+    ("fl", '\u{FB02}'),
+    ("florin", '\u{0192}'),
+    ("four", '4'),
+    ("fourinferior", '\u{2084}'),
+    ("foursuperior", '\u{2074}'),
+    ("fraction", '\u{2044}'),
+    ("franc", '\u{20A3}'),
+    ("g", 'g'),
+    ("gamma", '\u{03B3}'),
+    ("gbreve", '\u{011F}'),
+    ("gcaron", '\u{01E7}'),
+    ("gcircumflex", '\u{011D}'),
+    ("gcommaaccent", '\u{0123}'),
+    ("gdotaccent", '\u{0121}'),
+    ("germandbls", '\u{00DF}'),
+    ("gradient", '\u{2207}'),
+    ("grave", '`'),
+    ("gravecomb", '\u{0300}'),
+    ("greater", '>'),
+    ("greaterequal", '\u{2265}'),
+    ("guillemotleft", '\u{00AB}'),
+    ("guillemotright", '\u{00BB}'),
+    ("guilsinglleft", '\u{2039}'),
+    ("guilsinglright", '\u{203A}'),
+    ("h", 'h'),
+    ("hbar", '\u{0127}'),
+    ("hcircumflex", '\u{0125}'),
+    ("heart", '\u{2665}'),
+    ("hookabovecomb", '\u{0309}'),
+    ("house", '\u{2302}'),
+    ("hungarumlaut", '\u{02DD}'),
+    ("hyphen", '-'),
+    ("i", 'i'),
+    ("iacute", '\u{00ED}'),
+    ("ibreve", '\u{012D}'),
+    ("icircumflex", '\u{00EE}'),
+    ("idieresis", '\u{00EF}'),
+    ("igrave", '\u{00EC}'),
+    ("ij", '\u{0133}'),
+    ("imacron", '\u{012B}'),
+    ("infinity", '\u{221E}'),
+    ("integral", '\u{222B}'),
+    ("integralbt", '\u{2321}'),
+    ("integralex", '\u{23AE}'),
+    ("integraltp", '\u{2320}'),
+    ("intersection", '\u{2229}'),
+    ("invbullet", '\u{25D8}'),
+    ("invcircle", '\u{25D9}'),
+    ("invsmileface", '\u{263B}'),
+    ("iogonek", '\u{012F}'),
+    ("iota", '\u{03B9}'),
+    ("iotadieresis", '\u{03CA}'),
+    ("iotadieresistonos", '\u{0390}'),
+    ("iotatonos", '\u{03AF}'),
+    ("itilde", '\u{0129}'),
+    ("j", 'j'),
+    ("jcircumflex", '\u{0135}'),
+    ("k", 'k'),
+    ("kappa", '\u{03BA}'),
+    ("kcommaaccent", '\u{0137}'),
+    ("kgreenlandic", '\u{0138}'),
+    ("l", 'l'),
+    ("lacute", '\u{013A}'),
+    ("lambda", '\u{03BB}'),
+    ("lcaron", '\u{013E}'),
+    ("lcommaaccent", '\u{013C}'),
+    ("ldot", '\u{0140}'),
+    ("less", '<'),
+    ("lessequal", '\u{2264}'),
+    ("lfblock", '\u{258C}'),
+    ("lira", '\u{20A4}'),
+    ("logicaland", '\u{2227}'),
+    ("logicalnot", '\u{00AC}'),
+    ("logicalor", '\u{2228}'),
+    ("longs", '\u{017F}'),
+    ("lozenge", '\u{25CA}'),
+    ("lslash", '\u{0142}'),
+    ("ltshade", '\u{2591}'),
+    ("m", 'm'),
+    ("macron", '\u{00AF}'),
+    ("male", '\u{2642}'),
+    ("minus", '\u{2212}'),
+    ("minute", '\u{2032}'),
+    ("mu", '\u{00B5}'),
+    ("multiply", '\u{00D7}'),
+    ("musicalnote", '\u{266A}'),
+    ("musicalnotedbl", '\u{266B}'),
+    ("n", 'n'),
+    ("nacute", '\u{0144}'),
+    ("napostrophe", '\u{0149}'),
+    ("nbspace", '\u{00A0}'),
+    ("ncaron", '\u{0148}'),
+    ("ncommaaccent", '\u{0146}'),
+    ("nine", '9'),
+    ("nineinferior", '\u{2089}'),
+    ("ninesuperior", '\u{2079}'),
+    ("notelement", '\u{2209}'),
+    ("notequal", '\u{2260}'),
+    ("notsubset", '\u{2284}'),
+    ("nsuperior", '\u{207F}'),
+    ("ntilde", '\u{00F1}'),
+    ("nu", '\u{03BD}'),
+    ("numbersign", '#'),
+    ("o", 'o'),
+    ("oacute", '\u{00F3}'),
+    ("obreve", '\u{014F}'),
+    ("ocircumflex", '\u{00F4}'),
+    ("odieresis", '\u{00F6}'),
+    ("oe", '\u{0153}'),
+    ("ogonek", '\u{02DB}'),
+    ("ograve", '\u{00F2}'),
+    ("ohorn", '\u{01A1}'),
+    ("ohungarumlaut", '\u{0151}'),
+    ("omacron", '\u{014D}'),
+    ("omega", '\u{03C9}'),
+    ("omega1", '\u{03D6}'),
+    ("omegatonos", '\u{03CE}'),
+    ("omicron", '\u{03BF}'),
+    ("omicrontonos", '\u{03CC}'),
+    ("one", '1'),
+    ("onedotenleader", '\u{2024}'),
+    ("oneeighth", '\u{215B}'),
+    ("onehalf", '\u{00BD}'),
+    ("oneinferior", '\u{2081}'),
+    ("onequarter", '\u{00BC}'),
+    ("onesuperior", '\u{00B9}'),
+    ("onethird", '\u{2153}'),
+    ("openbullet", '\u{25E6}'),
+    ("ordfeminine", '\u{00AA}'),
+    ("ordmasculine", '\u{00BA}'),
+    ("orthogonal", '\u{221F}'),
+    ("oslash", '\u{00F8}'),
+    ("oslashacute", '\u{01FF}'),
+    ("otilde", '\u{00F5}'),
+    ("p", 'p'),
+    ("paragraph", '\u{00B6}'),
+    ("parenleft", '('),
+    ("parenleftbt", '\u{239D}'),
+    ("parenleftex", '\u{239C}'),
+    ("parenleftinferior", '\u{208D}'),
+    ("parenleftsuperior", '\u{207D}'),
+    ("parenlefttp", '\u{239B}'),
+    ("parenright", ')'),
+    ("parenrightbt", '\u{23A0}'),
+    ("parenrightex", '\u{239F}'),
+    ("parenrightinferior", '\u{208E}'),
+    ("parenrightsuperior", '\u{207E}'),
+    ("parenrighttp", '\u{239E}'),
+    ("partialdiff", '\u{2202}'),
+    ("percent", '%'),
+    ("period", '.'),
+    ("periodcentered", '\u{00B7}'),
+    ("perpendicular", '\u{22A5}'),
+    ("perthousand", '\u{2030}'),
+    ("peseta", '\u{20A7}'),
+    ("phi", '\u{03C6}'),
+    ("phi1", '\u{03D5}'),
+    ("pi", '\u{03C0}'),
+    ("plus", '+'),
+    ("plusminus", '\u{00B1}'),
+    ("prescription", '\u{211E}'),
+    ("product", '\u{220F}'),
+    ("propersubset", '\u{2282}'),
+    ("propersuperset", '\u{2283}'),
+    ("proportional", '\u{221D}'),
+    ("psi", '\u{03C8}'),
+    ("q", 'q'),
+    ("question", '?'),
+    ("questiondown", '\u{00BF}'),
+    ("quotedbl", '"'),
+    ("quotedblbase", '\u{201E}'),
+    ("quotedblleft", '\u{201C}'),
+    ("quotedblright", '\u{201D}'),
+    ("quoteleft", '\u{2018}'),
+    ("quotereversed", '\u{201B}'),
+    ("quoteright", '\u{2019}'),
+    ("quotesinglbase", '\u{201A}'),
+    ("quotesingle", '\''),
+    ("r", 'r'),
+    ("racute", '\u{0155}'),
+    ("radical", '\u{221A}'),
+    ("radicalex", '\u{203E}'),
+    ("rcaron", '\u{0159}'),
+    ("rcommaaccent", '\u{0157}'),
+    ("reflexsubset", '\u{2286}'),
+    ("reflexsuperset", '\u{2287}'),
+    ("registered", '\u{00AE}'),
+    ("registersans", '\u{00AE}'),
+    ("registerserif", '\u{00AE}'),
+    ("revlogicalnot", '\u{2310}'),
+    ("rho", '\u{03C1}'),
+    ("ring", '\u{02DA}'),
+    ("rtblock", '\u{2590}'),
+    ("s", 's'),
+    ("sacute", '\u{015B}'),
+    ("scaron", '\u{0161}'),
+    ("scedilla", '\u{015F}'),
+    ("scircumflex", '\u{015D}'),
+    ("scommaaccent", '\u{0219}'),
+    ("second", '\u{2033}'),
+    ("section", '\u{00A7}'),
+    ("semicolon", ';'),
+    ("seven", '7'),
+    ("seveneighths", '\u{215E}'),
+    ("seveninferior", '\u{2087}'),
+    ("sevensuperior", '\u{2077}'),
+    ("sfthyphen", '\u{00AD}'),
+    ("shade", '\u{2592}'),
+    ("sigma", '\u{03C3}'),
+    ("sigma1", '\u{03C2}'),
+    ("similar", '\u{223C}'),
+    ("six", '6'),
+    ("sixinferior", '\u{2086}'),
+    ("sixsuperior", '\u{2076}'),
+    ("slash", '/'),
+    ("smileface", '\u{263A}'),
+    ("space", ' '),
+    ("spade", '\u{2660}'),
+    ("sterling", '\u{00A3}'),
+    ("suchthat", '\u{220B}'),
+    ("summation", '\u{2211}'),
+    ("sun", '\u{263C}'),
+    ("t", 't'),
+    ("tau", '\u{03C4}'),
+    ("tbar", '\u{0167}'),
+    ("tcaron", '\u{0165}'),
+    ("tcommaaccent", '\u{0163}'),
+    ("therefore", '\u{2234}'),
+    ("theta", '\u{03B8}'),
+    ("theta1", '\u{03D1}'),
+    ("thorn", '\u{00FE}'),
+    ("three", '3'),
+    ("threeeighths", '\u{215C}'),
+    ("threeinferior", '\u{2083}'),
+    ("threequarters", '\u{00BE}'),
+    ("threesuperior", '\u{00B3}'),
+    ("tilde", '\u{02DC}'),
+    ("tildecomb", '\u{0303}'),
+    ("tonos", '\u{0384}'),
+    ("trademark", '\u{2122}'),
+    ("trademarksans", '\u{2122}'),
+    ("trademarkserif", '\u{2122}'),
+    ("triagdn", '\u{25BC}'),
+    ("triaglf", '\u{25C4}'),
+    ("triagrt", '\u{25BA}'),
+    ("triagup", '\u{25B2}'),
+    ("two", '2'),
+    ("twodotenleader", '\u{2025}'),
+    ("twoinferior", '\u{2082}'),
+    ("twosuperior", '\u{00B2}'),
+    ("twothirds", '\u{2154}'),
+    ("u", 'u'),
+    ("uacute", '\u{00FA}'),
+    ("ubreve", '\u{016D}'),
+    ("ucircumflex", '\u{00FB}'),
+    ("udieresis", '\u{00FC}'),
+    ("ugrave", '\u{00F9}'),
+    ("uhorn", '\u{01B0}'),
+    ("uhungarumlaut", '\u{0171}'),
+    ("umacron", '\u{016B}'),
+    ("underscore", '_'),
+    ("underscoredbl", '\u{2017}'),
+    ("union", '\u{222A}'),
+    ("universal", '\u{2200}'),
+    ("uogonek", '\u{0173}'),
+    ("upblock", '\u{2580}'),
+    ("upsilon", '\u{03C5}'),
+    ("upsilondieresis", '\u{03CB}'),
+    ("upsilondieresistonos", '\u{03B0}'),
+    ("upsilontonos", '\u{03CD}'),
+    ("uring", '\u{016F}'),
+    ("utilde", '\u{0169}'),
+    ("v", 'v'),
+    ("w", 'w'),
+    ("wacute", '\u{1E83}'),
+    ("wcircumflex", '\u{0175}'),
+    ("wdieresis", '\u{1E85}'),
+    ("weierstrass", '\u{2118}'),
+    ("wgrave", '\u{1E81}'),
+    ("x", 'x'),
+    ("xi", '\u{03BE}'),
+    ("y", 'y'),
+    ("yacute", '\u{00FD}'),
+    ("ycircumflex", '\u{0177}'),
+    ("ydieresis", '\u{00FF}'),
+    ("yen", '\u{00A5}'),
+    ("ygrave", '\u{1EF3}'),
+    ("z", 'z'),
+    ("zacute", '\u{017A}'),
+    ("zcaron", '\u{017E}'),
+    ("zdotaccent", '\u{017C}'),
+    ("zero", '0'),
+    ("zeroinferior", '\u{2080}'),
+    ("zerosuperior", '\u{2070}'),
+    ("zeta", '\u{03B6}'),
+];
+// StandardEncoding: the glyph name for each code.
+#[rustfmt::skip]
+pub(super) const STANDARD_ENCODING: [Option<&str>; 256] = [
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    Some("space"), Some("exclam"), Some("quotedbl"), Some("numbersign"), Some("dollar"), Some("percent"), Some("ampersand"), Some("quoteright"),
+    Some("parenleft"), Some("parenright"), Some("asterisk"), Some("plus"), Some("comma"), Some("hyphen"), Some("period"), Some("slash"),
+    Some("zero"), Some("one"), Some("two"), Some("three"), Some("four"), Some("five"), Some("six"), Some("seven"),
+    Some("eight"), Some("nine"), Some("colon"), Some("semicolon"), Some("less"), Some("equal"), Some("greater"), Some("question"),
+    Some("at"), Some("A"), Some("B"), Some("C"), Some("D"), Some("E"), Some("F"), Some("G"),
+    Some("H"), Some("I"), Some("J"), Some("K"), Some("L"), Some("M"), Some("N"), Some("O"),
+    Some("P"), Some("Q"), Some("R"), Some("S"), Some("T"), Some("U"), Some("V"), Some("W"),
+    Some("X"), Some("Y"), Some("Z"), Some("bracketleft"), Some("backslash"), Some("bracketright"), Some("asciicircum"), Some("underscore"),
+    Some("quoteleft"), Some("a"), Some("b"), Some("c"), Some("d"), Some("e"), Some("f"), Some("g"),
+    Some("h"), Some("i"), Some("j"), Some("k"), Some("l"), Some("m"), Some("n"), Some("o"),
+    Some("p"), Some("q"), Some("r"), Some("s"), Some("t"), Some("u"), Some("v"), Some("w"),
+    Some("x"), Some("y"), Some("z"), Some("braceleft"), Some("bar"), Some("braceright"), Some("asciitilde"), None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, Some("exclamdown"), Some("cent"), Some("sterling"), Some("fraction"), Some("yen"), Some("florin"), Some("section"),
+    Some("currency"), Some("quotesingle"), Some("quotedblleft"), Some("guillemotleft"), Some("guilsinglleft"), Some("guilsinglright"), Some("fi"), Some("fl"),
+    None, Some("endash"), Some("dagger"), Some("daggerdbl"), Some("periodcentered"), None, Some("paragraph"), Some("bullet"),
+    Some("quotesinglbase"), Some("quotedblbase"), Some("quotedblright"), Some("guillemotright"), Some("ellipsis"), Some("perthousand"), None, Some("questiondown"),
+    None, Some("grave"), Some("acute"), Some("circumflex"), Some("tilde"), Some("macron"), Some("breve"), Some("dotaccent"),
+    Some("dieresis"), None, Some("ring"), Some("cedilla"), None, Some("hungarumlaut"), Some("ogonek"), Some("caron"),
+    Some("emdash"), None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, Some("AE"), None, Some("ordfeminine"), None, None, None, None,
+    Some("Lslash"), Some("Oslash"), Some("OE"), Some("ordmasculine"), None, None, None, None,
+    None, Some("ae"), None, None, None, Some("dotlessi"), None, None,
+    Some("lslash"), Some("oslash"), Some("oe"), Some("germandbls"), None, None, None, None,
+];
+// WinAnsiEncoding.
+#[rustfmt::skip]
+const WIN_ANSI_ENCODING: [Option<&str>; 256] = [
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    Some("space"), Some("exclam"), Some("quotedbl"), Some("numbersign"), Some("dollar"), Some("percent"), Some("ampersand"), Some("quotesingle"),
+    Some("parenleft"), Some("parenright"), Some("asterisk"), Some("plus"), Some("comma"), Some("hyphen"), Some("period"), Some("slash"),
+    Some("zero"), Some("one"), Some("two"), Some("three"), Some("four"), Some("five"), Some("six"), Some("seven"),
+    Some("eight"), Some("nine"), Some("colon"), Some("semicolon"), Some("less"), Some("equal"), Some("greater"), Some("question"),
+    Some("at"), Some("A"), Some("B"), Some("C"), Some("D"), Some("E"), Some("F"), Some("G"),
+    Some("H"), Some("I"), Some("J"), Some("K"), Some("L"), Some("M"), Some("N"), Some("O"),
+    Some("P"), Some("Q"), Some("R"), Some("S"), Some("T"), Some("U"), Some("V"), Some("W"),
+    Some("X"), Some("Y"), Some("Z"), Some("bracketleft"), Some("backslash"), Some("bracketright"), Some("asciicircum"), Some("underscore"),
+    Some("grave"), Some("a"), Some("b"), Some("c"), Some("d"), Some("e"), Some("f"), Some("g"),
+    Some("h"), Some("i"), Some("j"), Some("k"), Some("l"), Some("m"), Some("n"), Some("o"),
+    Some("p"), Some("q"), Some("r"), Some("s"), Some("t"), Some("u"), Some("v"), Some("w"),
+    Some("x"), Some("y"), Some("z"), Some("braceleft"), Some("bar"), Some("braceright"), Some("asciitilde"), None,
+    Some("Euro"), None, Some("quotesinglbase"), Some("florin"), Some("quotedblbase"), Some("ellipsis"), Some("dagger"), Some("daggerdbl"),
+    Some("circumflex"), Some("perthousand"), Some("Scaron"), Some("guilsinglleft"), Some("OE"), None, Some("Zcaron"), None,
+    None, Some("quoteleft"), Some("quoteright"), Some("quotedblleft"), Some("quotedblright"), Some("bullet"), Some("endash"), Some("emdash"),
+    Some("tilde"), Some("trademark"), Some("scaron"), Some("guilsinglright"), Some("oe"), None, Some("zcaron"), Some("Ydieresis"),
+    Some("space"), Some("exclamdown"), Some("cent"), Some("sterling"), Some("currency"), Some("yen"), Some("brokenbar"), Some("section"),
+    Some("dieresis"), Some("copyright"), Some("ordfeminine"), Some("guillemotleft"), Some("logicalnot"), Some("hyphen"), Some("registered"), Some("macron"),
+    Some("degree"), Some("plusminus"), Some("twosuperior"), Some("threesuperior"), Some("acute"), Some("mu"), Some("paragraph"), Some("periodcentered"),
+    Some("cedilla"), Some("onesuperior"), Some("ordmasculine"), Some("guillemotright"), Some("onequarter"), Some("onehalf"), Some("threequarters"), Some("questiondown"),
+    Some("Agrave"), Some("Aacute"), Some("Acircumflex"), Some("Atilde"), Some("Adieresis"), Some("Aring"), Some("AE"), Some("Ccedilla"),
+    Some("Egrave"), Some("Eacute"), Some("Ecircumflex"), Some("Edieresis"), Some("Igrave"), Some("Iacute"), Some("Icircumflex"), Some("Idieresis"),
+    Some("Eth"), Some("Ntilde"), Some("Ograve"), Some("Oacute"), Some("Ocircumflex"), Some("Otilde"), Some("Odieresis"), Some("multiply"),
+    Some("Oslash"), Some("Ugrave"), Some("Uacute"), Some("Ucircumflex"), Some("Udieresis"), Some("Yacute"), Some("Thorn"), Some("germandbls"),
+    Some("agrave"), Some("aacute"), Some("acircumflex"), Some("atilde"), Some("adieresis"), Some("aring"), Some("ae"), Some("ccedilla"),
+    Some("egrave"), Some("eacute"), Some("ecircumflex"), Some("edieresis"), Some("igrave"), Some("iacute"), Some("icircumflex"), Some("idieresis"),
+    Some("eth"), Some("ntilde"), Some("ograve"), Some("oacute"), Some("ocircumflex"), Some("otilde"), Some("odieresis"), Some("divide"),
+    Some("oslash"), Some("ugrave"), Some("uacute"), Some("ucircumflex"), Some("udieresis"), Some("yacute"), Some("thorn"), Some("ydieresis"),
+];
+// MacRomanEncoding.
+#[rustfmt::skip]
+const MAC_ROMAN_ENCODING: [Option<&str>; 256] = [
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    Some("space"), Some("exclam"), Some("quotedbl"), Some("numbersign"), Some("dollar"), Some("percent"), Some("ampersand"), Some("quotesingle"),
+    Some("parenleft"), Some("parenright"), Some("asterisk"), Some("plus"), Some("comma"), Some("hyphen"), Some("period"), Some("slash"),
+    Some("zero"), Some("one"), Some("two"), Some("three"), Some("four"), Some("five"), Some("six"), Some("seven"),
+    Some("eight"), Some("nine"), Some("colon"), Some("semicolon"), Some("less"), Some("equal"), Some("greater"), Some("question"),
+    Some("at"), Some("A"), Some("B"), Some("C"), Some("D"), Some("E"), Some("F"), Some("G"),
+    Some("H"), Some("I"), Some("J"), Some("K"), Some("L"), Some("M"), Some("N"), Some("O"),
+    Some("P"), Some("Q"), Some("R"), Some("S"), Some("T"), Some("U"), Some("V"), Some("W"),
+    Some("X"), Some("Y"), Some("Z"), Some("bracketleft"), Some("backslash"), Some("bracketright"), Some("asciicircum"), Some("underscore"),
+    Some("grave"), Some("a"), Some("b"), Some("c"), Some("d"), Some("e"), Some("f"), Some("g"),
+    Some("h"), Some("i"), Some("j"), Some("k"), Some("l"), Some("m"), Some("n"), Some("o"),
+    Some("p"), Some("q"), Some("r"), Some("s"), Some("t"), Some("u"), Some("v"), Some("w"),
+    Some("x"), Some("y"), Some("z"), Some("braceleft"), Some("bar"), Some("braceright"), Some("asciitilde"), None,
+    Some("Adieresis"), Some("Aring"), Some("Ccedilla"), Some("Eacute"), Some("Ntilde"), Some("Odieresis"), Some("Udieresis"), Some("aacute"),
+    Some("agrave"), Some("acircumflex"), Some("adieresis"), Some("atilde"), Some("aring"), Some("ccedilla"), Some("eacute"), Some("egrave"),
+    Some("ecircumflex"), Some("edieresis"), Some("iacute"), Some("igrave"), Some("icircumflex"), Some("idieresis"), Some("ntilde"), Some("oacute"),
+    Some("ograve"), Some("ocircumflex"), Some("odieresis"), Some("otilde"), Some("uacute"), Some("ugrave"), Some("ucircumflex"), Some("udieresis"),
+    Some("dagger"), Some("degree"), Some("cent"), Some("sterling"), Some("section"), Some("bullet"), Some("paragraph"), Some("germandbls"),
+    Some("registered"), Some("copyright"), Some("trademark"), Some("acute"), Some("dieresis"), None, Some("AE"), Some("Oslash"),
+    None, Some("plusminus"), None, None, Some("yen"), Some("mu"), None, None,
+    None, None, None, Some("ordfeminine"), Some("ordmasculine"), None, Some("ae"), Some("oslash"),
+    Some("questiondown"), Some("exclamdown"), Some("logicalnot"), None, Some("florin"), None, None, Some("guillemotleft"),
+    Some("guillemotright"), Some("ellipsis"), Some("space"), Some("Agrave"), Some("Atilde"), Some("Otilde"), Some("OE"), Some("oe"),
+    Some("endash"), Some("emdash"), Some("quotedblleft"), Some("quotedblright"), Some("quoteleft"), Some("quoteright"), Some("divide"), None,
+    Some("ydieresis"), Some("Ydieresis"), Some("fraction"), Some("currency"), Some("guilsinglleft"), Some("guilsinglright"), Some("fi"), Some("fl"),
+    Some("daggerdbl"), Some("periodcentered"), Some("quotesinglbase"), Some("quotedblbase"), Some("perthousand"), Some("Acircumflex"), Some("Ecircumflex"), Some("Aacute"),
+    Some("Edieresis"), Some("Egrave"), Some("Iacute"), Some("Icircumflex"), Some("Idieresis"), Some("Igrave"), Some("Oacute"), Some("Ocircumflex"),
+    None, Some("Ograve"), Some("Uacute"), Some("Ucircumflex"), Some("Ugrave"), Some("dotlessi"), Some("circumflex"), Some("tilde"),
+    Some("macron"), Some("breve"), Some("dotaccent"), Some("ring"), Some("cedilla"), Some("hungarumlaut"), Some("ogonek"), Some("caron"),
+];
+// Symbol's built-in encoding.
+#[rustfmt::skip]
+const SYMBOL_ENCODING: [Option<&str>; 256] = [
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    Some("space"), Some("exclam"), Some("universal"), Some("numbersign"), Some("existential"), Some("percent"), Some("ampersand"), Some("suchthat"),
+    Some("parenleft"), Some("parenright"), Some("asteriskmath"), Some("plus"), Some("comma"), Some("minus"), Some("period"), Some("slash"),
+    Some("zero"), Some("one"), Some("two"), Some("three"), Some("four"), Some("five"), Some("six"), Some("seven"),
+    Some("eight"), Some("nine"), Some("colon"), Some("semicolon"), Some("less"), Some("equal"), Some("greater"), Some("question"),
+    Some("congruent"), Some("Alpha"), Some("Beta"), Some("Chi"), Some("Delta"), Some("Epsilon"), Some("Phi"), Some("Gamma"),
+    Some("Eta"), Some("Iota"), Some("theta1"), Some("Kappa"), Some("Lambda"), Some("Mu"), Some("Nu"), Some("Omicron"),
+    Some("Pi"), Some("Theta"), Some("Rho"), Some("Sigma"), Some("Tau"), Some("Upsilon"), Some("sigma1"), Some("Omega"),
+    Some("Xi"), Some("Psi"), Some("Zeta"), Some("bracketleft"), Some("therefore"), Some("bracketright"), Some("perpendicular"), Some("underscore"),
+    Some("radicalex"), Some("alpha"), Some("beta"), Some("chi"), Some("delta"), Some("epsilon"), Some("phi"), Some("gamma"),
+    Some("eta"), Some("iota"), Some("phi1"), Some("kappa"), Some("lambda"), Some("mu"), Some("nu"), Some("omicron"),
+    Some("pi"), Some("theta"), Some("rho"), Some("sigma"), Some("tau"), Some("upsilon"), Some("omega1"), Some("omega"),
+    Some("xi"), Some("psi"), Some("zeta"), Some("braceleft"), Some("bar"), Some("braceright"), Some("similar"), None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    Some("Euro"), Some("Upsilon1"), Some("minute"), Some("lessequal"), Some("fraction"), Some("infinity"), Some("florin"), Some("club"),
+    Some("diamond"), Some("heart"), Some("spade"), Some("arrowboth"), Some("arrowleft"), Some("arrowup"), Some("arrowright"), Some("arrowdown"),
+    Some("degree"), Some("plusminus"), Some("second"), Some("greaterequal"), Some("multiply"), Some("proportional"), Some("partialdiff"), Some("bullet"),
+    Some("divide"), Some("notequal"), Some("equivalence"), Some("approxequal"), Some("ellipsis"), Some("arrowvertex"), Some("arrowhorizex"), Some("carriagereturn"),
+    Some("aleph"), Some("Ifraktur"), Some("Rfraktur"), Some("weierstrass"), Some("circlemultiply"), Some("circleplus"), Some("emptyset"), Some("intersection"),
+    Some("union"), Some("propersuperset"), Some("reflexsuperset"), Some("notsubset"), Some("propersubset"), Some("reflexsubset"), Some("element"), Some("notelement"),
+    Some("angle"), Some("gradient"), Some("registerserif"), Some("copyrightserif"), Some("trademarkserif"), Some("product"), Some("radical"), Some("dotmath"),
+    Some("logicalnot"), Some("logicaland"), Some("logicalor"), Some("arrowdblboth"), Some("arrowdblleft"), Some("arrowdblup"), Some("arrowdblright"), Some("arrowdbldown"),
+    Some("lozenge"), Some("angleleft"), Some("registersans"), Some("copyrightsans"), Some("trademarksans"), Some("summation"), Some("parenlefttp"), Some("parenleftex"),
+    Some("parenleftbt"), Some("bracketlefttp"), Some("bracketleftex"), Some("bracketleftbt"), Some("bracelefttp"), Some("braceleftmid"), Some("braceleftbt"), Some("braceex"),
+    None, Some("angleright"), Some("integral"), Some("integraltp"), Some("integralex"), Some("integralbt"), Some("parenrighttp"), Some("parenrightex"),
+    Some("parenrightbt"), Some("bracketrighttp"), Some("bracketrightex"), Some("bracketrightbt"), Some("bracerighttp"), Some("bracerightmid"), Some("bracerightbt"), None,
+];
+// ZapfDingbats' built-in encoding.
+#[rustfmt::skip]
+const ZAPF_DINGBATS_ENCODING: [Option<&str>; 256] = [
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    Some("space"), Some("a1"), Some("a2"), Some("a202"), Some("a3"), Some("a4"), Some("a5"), Some("a119"),
+    Some("a118"), Some("a117"), Some("a11"), Some("a12"), Some("a13"), Some("a14"), Some("a15"), Some("a16"),
+    Some("a105"), Some("a17"), Some("a18"), Some("a19"), Some("a20"), Some("a21"), Some("a22"), Some("a23"),
+    Some("a24"), Some("a25"), Some("a26"), Some("a27"), Some("a28"), Some("a6"), Some("a7"), Some("a8"),
+    Some("a9"), Some("a10"), Some("a29"), Some("a30"), Some("a31"), Some("a32"), Some("a33"), Some("a34"),
+    Some("a35"), Some("a36"), Some("a37"), Some("a38"), Some("a39"), Some("a40"), Some("a41"), Some("a42"),
+    Some("a43"), Some("a44"), Some("a45"), Some("a46"), Some("a47"), Some("a48"), Some("a49"), Some("a50"),
+    Some("a51"), Some("a52"), Some("a53"), Some("a54"), Some("a55"), Some("a56"), Some("a57"), Some("a58"),
+    Some("a59"), Some("a60"), Some("a61"), Some("a62"), Some("a63"), Some("a64"), Some("a65"), Some("a66"),
+    Some("a67"), Some("a68"), Some("a69"), Some("a70"), Some("a71"), Some("a72"), Some("a73"), Some("a74"),
+    Some("a203"), Some("a75"), Some("a204"), Some("a76"), Some("a77"), Some("a78"), Some("a79"), Some("a81"),
+    Some("a82"), Some("a83"), Some("a84"), Some("a97"), Some("a98"), Some("a99"), Some("a100"), None,
+    Some("a89"), Some("a90"), Some("a93"), Some("a94"), Some("a91"), Some("a92"), Some("a205"), Some("a85"),
+    Some("a206"), Some("a86"), Some("a87"), Some("a88"), Some("a95"), Some("a96"), None, None,
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    None, Some("a101"), Some("a102"), Some("a103"), Some("a104"), Some("a106"), Some("a107"), Some("a108"),
+    Some("a112"), Some("a111"), Some("a110"), Some("a109"), Some("a120"), Some("a121"), Some("a122"), Some("a123"),
+    Some("a124"), Some("a125"), Some("a126"), Some("a127"), Some("a128"), Some("a129"), Some("a130"), Some("a131"),
+    Some("a132"), Some("a133"), Some("a134"), Some("a135"), Some("a136"), Some("a137"), Some("a138"), Some("a139"),
+    Some("a140"), Some("a141"), Some("a142"), Some("a143"), Some("a144"), Some("a145"), Some("a146"), Some("a147"),
+    Some("a148"), Some("a149"), Some("a150"), Some("a151"), Some("a152"), Some("a153"), Some("a154"), Some("a155"),
+    Some("a156"), Some("a157"), Some("a158"), Some("a159"), Some("a160"), Some("a161"), Some("a163"), Some("a164"),
+    Some("a196"), Some("a165"), Some("a192"), Some("a166"), Some("a167"), Some("a168"), Some("a169"), Some("a170"),
+    Some("a171"), Some("a172"), Some("a173"), Some("a162"), Some("a174"), Some("a175"), Some("a176"), Some("a177"),
+    Some("a178"), Some("a179"), Some("a193"), Some("a180"), Some("a199"), Some("a181"), Some("a200"), Some("a182"),
+    None, Some("a201"), Some("a183"), Some("a184"), Some("a197"), Some("a185"), Some("a194"), Some("a198"),
+    Some("a186"), Some("a195"), Some("a187"), Some("a188"), Some("a189"), Some("a190"), Some("a191"), None,
+];
+// ZapfDingbats' glyph names (which other fonts may use for other glyphs), and their characters,
+// sorted by name.
+const DINGBAT_NAMES: [(&str, char); 201] = [
+    ("a1", '\u{2701}'),
+    ("a10", '\u{2721}'),
+    ("a100", '\u{275E}'),
+    ("a101", '\u{2761}'),
+    ("a102", '\u{2762}'),
+    ("a103", '\u{2763}'),
+    ("a104", '\u{2764}'),
+    ("a105", '\u{2710}'),
+    ("a106", '\u{2765}'),
+    ("a107", '\u{2766}'),
+    ("a108", '\u{2767}'),
+    ("a109", '\u{2660}'),
+    ("a11", '\u{261B}'),
+    ("a110", '\u{2665}'),
+    ("a111", '\u{2666}'),
+    ("a112", '\u{2663}'),
+    ("a117", '\u{2709}'),
+    ("a118", '\u{2708}'),
+    ("a119", '\u{2707}'),
+    ("a12", '\u{261E}'),
+    ("a120", '\u{2460}'),
+    ("a121", '\u{2461}'),
+    ("a122", '\u{2462}'),
+    ("a123", '\u{2463}'),
+    ("a124", '\u{2464}'),
+    ("a125", '\u{2465}'),
+    ("a126", '\u{2466}'),
+    ("a127", '\u{2467}'),
+    ("a128", '\u{2468}'),
+    ("a129", '\u{2469}'),
+    ("a13", '\u{270C}'),
+    ("a130", '\u{2776}'),
+    ("a131", '\u{2777}'),
+    ("a132", '\u{2778}'),
+    ("a133", '\u{2779}'),
+    ("a134", '\u{277A}'),
+    ("a135", '\u{277B}'),
+    ("a136", '\u{277C}'),
+    ("a137", '\u{277D}'),
+    ("a138", '\u{277E}'),
+    ("a139", '\u{277F}'),
+    ("a14", '\u{270D}'),
+    ("a140", '\u{2780}'),
+    ("a141", '\u{2781}'),
+    ("a142", '\u{2782}'),
+    ("a143", '\u{2783}'),
+    ("a144", '\u{2784}'),
+    ("a145", '\u{2785}'),
+    ("a146", '\u{2786}'),
+    ("a147", '\u{2787}'),
+    ("a148", '\u{2788}'),
+    ("a149", '\u{2789}'),
+    ("a15", '\u{270E}'),
+    ("a150", '\u{278A}'),
+    ("a151", '\u{278B}'),
+    ("a152", '\u{278C}'),
+    ("a153", '\u{278D}'),
+    ("a154", '\u{278E}'),
+    ("a155", '\u{278F}'),
+    ("a156", '\u{2790}'),
+    ("a157", '\u{2791}'),
+    ("a158", '\u{2792}'),
+    ("a159", '\u{2793}'),
+    ("a16", '\u{270F}'),
+    ("a160", '\u{2794}'),
+    ("a161", '\u{2192}'),
+    ("a162", '\u{27A3}'),
+    ("a163", '\u{2194}'),
+    ("a164", '\u{2195}'),
+    ("a165", '\u{2799}'),
+    ("a166", '\u{279B}'),
+    ("a167", '\u{279C}'),
+    ("a168", '\u{279D}'),
+    ("a169", '\u{279E}'),
+    ("a17", '\u{2711}'),
+    ("a170", '\u{279F}'),
+    ("a171", '\u{27A0}'),
+    ("a172", '\u{27A1}'),
+    ("a173", '\u{27A2}'),
+    ("a174", '\u{27A4}'),
+    ("a175", '\u{27A5}'),
+    ("a176", '\u{27A6}'),
+    ("a177", '\u{27A7}'),
+    ("a178", '\u{27A8}'),
+    ("a179", '\u{27A9}'),
+    ("a18", '\u{2712}'),
+    ("a180", '\u{27AB}'),
+    ("a181", '\u{27AD}'),
+    ("a182", '\u{27AF}'),
+    ("a183", '\u{27B2}'),
+    ("a184", '\u{27B3}'),
+    ("a185", '\u{27B5}'),
+    ("a186", '\u{27B8}'),
+    ("a187", '\u{27BA}'),
+    ("a188", '\u{27BB}'),
+    ("a189", '\u{27BC}'),
+    ("a19", '\u{2713}'),
+    ("a190", '\u{27BD}'),
+    ("a191", '\u{27BE}'),
+    ("a192", '\u{279A}'),
+    ("a193", '\u{27AA}'),
+    ("a194", '\u{27B6}'),
+    ("a195", '\u{27B9}'),
+    ("a196", '\u{2798}'),
+    ("a197", '\u{27B4}'),
+    ("a198", '\u{27B7}'),
+    ("a199", '\u{27AC}'),
+    ("a2", '\u{2702}'),
+    ("a20", '\u{2714}'),
+    ("a200", '\u{27AE}'),
+    ("a201", '\u{27B1}'),
+    ("a202", '\u{2703}'),
+    ("a203", '\u{2750}'),
+    ("a204", '\u{2752}'),
+    ("a205", '\u{276E}'),
+    ("a206", '\u{2770}'),
+    ("a21", '\u{2715}'),
+    ("a22", '\u{2716}'),
+    ("a23", '\u{2717}'),
+    ("a24", '\u{2718}'),
+    ("a25", '\u{2719}'),
+    ("a26", '\u{271A}'),
+    ("a27", '\u{271B}'),
+    ("a28", '\u{271C}'),
+    ("a29", '\u{2722}'),
+    ("a3", '\u{2704}'),
+    ("a30", '\u{2723}'),
+    ("a31", '\u{2724}'),
+    ("a32", '\u{2725}'),
+    ("a33", '\u{2726}'),
+    ("a34", '\u{2727}'),
+    ("a35", '\u{2605}'),
+    ("a36", '\u{2729}'),
+    ("a37", '\u{272A}'),
+    ("a38", '\u{272B}'),
+    ("a39", '\u{272C}'),
+    ("a4", '\u{260E}'),
+    ("a40", '\u{272D}'),
+    ("a41", '\u{272E}'),
+    ("a42", '\u{272F}'),
+    ("a43", '\u{2730}'),
+    ("a44", '\u{2731}'),
+    ("a45", '\u{2732}'),
+    ("a46", '\u{2733}'),
+    ("a47", '\u{2734}'),
+    ("a48", '\u{2735}'),
+    ("a49", '\u{2736}'),
+    ("a5", '\u{2706}'),
+    ("a50", '\u{2737}'),
+    ("a51", '\u{2738}'),
+    ("a52", '\u{2739}'),
+    ("a53", '\u{273A}'),
+    ("a54", '\u{273B}'),
+    ("a55", '\u{273C}'),
+    ("a56", '\u{273D}'),
+    ("a57", '\u{273E}'),
+    ("a58", '\u{273F}'),
+    ("a59", '\u{2740}'),
+    ("a6", '\u{271D}'),
+    ("a60", '\u{2741}'),
+    ("a61", '\u{2742}'),
+    ("a62", '\u{2743}'),
+    ("a63", '\u{2744}'),
+    ("a64", '\u{2745}'),
+    ("a65", '\u{2746}'),
+    ("a66", '\u{2747}'),
+    ("a67", '\u{2748}'),
+    ("a68", '\u{2749}'),
+    ("a69", '\u{274A}'),
+    ("a7", '\u{271E}'),
+    ("a70", '\u{274B}'),
+    ("a71", '\u{25CF}'),
+    ("a72", '\u{274D}'),
+    ("a73", '\u{25A0}'),
+    ("a74", '\u{274F}'),
+    ("a75", '\u{2751}'),
+    ("a76", '\u{25B2}'),
+    ("a77", '\u{25BC}'),
+    ("a78", '\u{25C6}'),
+    ("a79", '\u{2756}'),
+    ("a8", '\u{271F}'),
+    ("a81", '\u{25D7}'),
+    ("a82", '\u{2758}'),
+    ("a83", '\u{2759}'),
+    ("a84", '\u{275A}'),
+    ("a85", '\u{276F}'),
+    ("a86", '\u{2771}'),
+    ("a87", '\u{2772}'),
+    ("a88", '\u{2773}'),
+    ("a89", '\u{2768}'),
+    ("a9", '\u{2720}'),
+    ("a90", '\u{2769}'),
+    ("a91", '\u{276C}'),
+    ("a92", '\u{276D}'),
+    ("a93", '\u{276A}'),
+    ("a94", '\u{276B}'),
+    ("a95", '\u{2774}'),
+    ("a96", '\u{2775}'),
+    ("a97", '\u{275B}'),
+    ("a98", '\u{275C}'),
+    ("a99", '\u{275D}'),
+];
+
+#[test]
+fn test_glyph_name_text() {
+    let text = |name: &str| glyph_name_text(name.as_bytes());
+    assert_eq!(text("A").as_deref(), Some("A"));
+    assert_eq!(text("eacute").as_deref(), Some("\u{E9}"));
+    assert_eq!(text("quoteright").as_deref(), Some("\u{2019}"));
+    assert_eq!(text("a.swash").as_deref(), Some("a"));
+    assert_eq!(text("f_f_i").as_deref(), Some("ffi"));
+    assert_eq!(text("uni00E90041").as_deref(), Some("\u{E9}A"));
+    assert_eq!(text("u1F600").as_deref(), Some("\u{1F600}"));
+    assert_eq!(text("uniD83D"), None);
+    assert_eq!(text("uni00E"), None);
+    assert_eq!(text("alpha").as_deref(), Some("\u{3B1}"));
+    assert_eq!(text("afii10017").as_deref(), Some("\u{410}"));
+    assert_eq!(text("afii10071").as_deref(), Some("\u{451}"));
+    assert_eq!(text("Scommaaccent").as_deref(), Some("\u{218}"));
+    assert_eq!(text("arrowright").as_deref(), Some("\u{2192}"));
+    assert_eq!(text("g123"), None);
+    assert_eq!(text("a12"), None);
+    assert_eq!(text(".notdef"), None);
+    assert_eq!(dingbat_text(b"a12").as_deref(), Some("\u{261E}"));
+    assert_eq!(dingbat_text(b"alpha"), None);
+}
+
+#[test]
+fn test_predefined_encodings() {
+    let standard = predefined_encoding(b"StandardEncoding").unwrap();
+    let win_ansi = predefined_encoding(b"WinAnsiEncoding").unwrap();
+    let mac_roman = predefined_encoding(b"MacRomanEncoding").unwrap();
+    assert_eq!(standard[0x27], Some("quoteright"));
+    assert_eq!(win_ansi[0x27], Some("quotesingle"));
+    assert_eq!(standard[0xE1], Some("AE"));
+    assert_eq!(win_ansi[0x80], Some("Euro"));
+    assert_eq!(win_ansi[0x81], None);
+    assert_eq!(win_ansi[0xE9], Some("eacute"));
+    assert_eq!(mac_roman[0x8E], Some("eacute"));
+    assert_eq!(mac_roman[0xDB], Some("currency"));
+    let symbol = builtin_encoding("Symbol").unwrap();
+    let zapf_dingbats = builtin_encoding("ZapfDingbats").unwrap();
+    assert_eq!(symbol[usize::from(b'a')], Some("alpha"));
+    assert_eq!(symbol[0xA0], Some("Euro"));
+    assert_eq!(zapf_dingbats[0x21], Some("a1"));
+    assert_eq!(zapf_dingbats[0xFE], Some("a191"));
+    assert_eq!(builtin_encoding("Helvetica"), None);
+    for encoding in [standard, win_ansi, mac_roman, symbol] {
+        for name in encoding.iter().flatten() {
+            assert!(GLYPH_NAMES.iter().any(|(n, _)| n == name), "{}", name);
+        }
+    }
+    for name in zapf_dingbats
+        .iter()
+        .flatten()
+        .filter(|&&name| name != "space")
+    {
+        assert!(DINGBAT_NAMES.iter().any(|(n, _)| n == name), "{}", name);
+    }
+    assert!(GLYPH_NAMES.windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(DINGBAT_NAMES.windows(2).all(|w| w[0].0 < w[1].0));
+    assert_eq!(predefined_encoding(b"Identity-H"), None);
+}
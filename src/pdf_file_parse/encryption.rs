@@ -0,0 +1,527 @@
+// ==============
+// 7.6 Encryption
+// ==============
+// An encrypted file has an /Encrypt dictionary in its trailer. With the standard security handler
+// (/Filter /Standard), a file key is derived from a password (the "user" password, which is often
+// empty, or the "owner" password) and the values in that dictionary, and from the file key, the key
+// for each object. Every string and stream in the file is then encrypted (with RC4 or AES) with the
+// key of the indirect object it is in -- except for the strings in the /Encrypt dictionary itself,
+// cross-reference streams, and anything inside an object stream (as the object stream is encrypted).
+
+use super::*;
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::{Aes128, Aes256, Block};
+use md5::{Digest, Md5};
+use sha2::{Sha256, Sha384, Sha512};
+
+/// Why the contents of an encrypted file could not be decrypted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptionError {
+    /// A security handler, revision or cipher that is not supported.
+    Unsupported(String),
+    /// The /Encrypt dictionary (or the /ID in the trailer) is not of the form the spec requires.
+    BadEncryptDict(String),
+    /// The password is neither the user password nor the owner password.
+    IncorrectPassword,
+    /// Encrypted data that is not of a valid length for the cipher.
+    BadData(String),
+}
+
+impl std::fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncryptionError::Unsupported(what) => write!(f, "unsupported encryption: {}", what),
+            EncryptionError::BadEncryptDict(message) => {
+                write!(f, "bad /Encrypt dictionary: {}", message)
+            }
+            EncryptionError::IncorrectPassword => write!(f, "incorrect password"),
+            EncryptionError::BadData(message) => write!(f, "bad encrypted data: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for EncryptionError {}
+
+fn bad_dict(message: impl Into<String>) -> EncryptionError {
+    EncryptionError::BadEncryptDict(message.into())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cipher {
+    Identity, // Not encrypted
+    Rc4,
+    AesV2, // AES-128, with a key for each object
+    AesV3, // AES-256, with the file key for everything
+}
+
+// The standard security handler, with the file key for some password.
+pub(super) struct SecurityHandler {
+    key: Vec<u8>,
+    string_cipher: Cipher,
+    stream_cipher: Cipher,
+    encrypt_metadata: bool,
+    // The /Encrypt dictionary's own strings are not encrypted.
+    encrypt_dict_object_number: Option<u32>,
+}
+
+// "A 32-byte string used in padding passwords."
+const PADDING: [u8; 32] = [
+    0x28, 0xBF, 0x4E, 0x5E, 0x4E, 0x75, 0x8A, 0x41, 0x64, 0x00, 0x4E, 0x56, 0xFF, 0xFA, 0x01, 0x08,
+    0x2E, 0x2E, 0x00, 0xB6, 0xD0, 0x68, 0x3E, 0x80, 0x2F, 0x0C, 0xA9, 0xFE, 0x64, 0x53, 0x69, 0x7A,
+];
+
+// The password, truncated or padded to 32 bytes.
+fn padded(password: &[u8]) -> Vec<u8> {
+    password.iter().chain(&PADDING).take(32).copied().collect()
+}
+
+fn rc4(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut s: Vec<u8> = (0..=255).collect();
+    let mut j: u8 = 0;
+    for i in 0..256 {
+        j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+        s.swap(i, j as usize);
+    }
+    let (mut i, mut j) = (0u8, 0u8);
+    data.iter()
+        .map(|&b| {
+            i = i.wrapping_add(1);
+            j = j.wrapping_add(s[i as usize]);
+            s.swap(i as usize, j as usize);
+            b ^ s[s[i as usize].wrapping_add(s[j as usize]) as usize]
+        })
+        .collect()
+}
+
+fn xor_key(key: &[u8], i: u8) -> Vec<u8> {
+    key.iter().map(|b| b ^ i).collect()
+}
+
+// AES in CBC mode, with a 16-byte or 32-byte key, without removing any padding.
+fn aes_cbc_decrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    if !data.len().is_multiple_of(16) {
+        return Err(EncryptionError::BadData(format!(
+            "{} bytes of AES data (not a multiple of 16)",
+            data.len()
+        )));
+    }
+    let decrypt_block: Box<dyn Fn(&mut Block)> = match key.len() {
+        16 => {
+            let cipher = Aes128::new_from_slice(key).unwrap();
+            Box::new(move |block| cipher.decrypt_block(block))
+        }
+        _ => {
+            let cipher = Aes256::new_from_slice(key)
+                .map_err(|_| EncryptionError::BadData(format!("a {}-byte AES key", key.len())))?;
+            Box::new(move |block| cipher.decrypt_block(block))
+        }
+    };
+    let mut out = Vec::with_capacity(data.len());
+    let mut previous = iv;
+    for chunk in data.chunks_exact(16) {
+        let mut block = *Block::from_slice(chunk);
+        decrypt_block(&mut block);
+        out.extend(block.iter().zip(previous).map(|(b, p)| b ^ p));
+        previous = chunk;
+    }
+    Ok(out)
+}
+
+// AES-128 in CBC mode, for data that is a multiple of 16 bytes.
+fn aes_128_cbc_encrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Vec<u8> {
+    let cipher = Aes128::new_from_slice(key).unwrap();
+    let mut out: Vec<u8> = Vec::with_capacity(data.len());
+    let mut previous = Block::clone_from_slice(iv);
+    for chunk in data.chunks_exact(16) {
+        let mut block = Block::clone_from_slice(chunk);
+        block.iter_mut().zip(&previous).for_each(|(b, p)| *b ^= p);
+        cipher.encrypt_block(&mut block);
+        out.extend_from_slice(&block);
+        previous = block;
+    }
+    out
+}
+
+// Algorithm 2.B: the hash used by revision 6.
+fn hash_r6(password: &[u8], salt: &[u8], user_key: &[u8]) -> Vec<u8> {
+    let mut k: Vec<u8> = Sha256::new()
+        .chain_update(password)
+        .chain_update(salt)
+        .chain_update(user_key)
+        .finalize()
+        .to_vec();
+    let mut round = 0;
+    loop {
+        let k1 = [password, &k, user_key].concat().repeat(64);
+        let e = aes_128_cbc_encrypt(&k[..16], &k[16..32], &k1);
+        // The first 16 bytes of E as a number, modulo 3 (which, as 256 % 3 == 1, is the sum of the bytes modulo 3).
+        k = match e[..16].iter().map(|&b| u32::from(b)).sum::<u32>() % 3 {
+            0 => Sha256::digest(&e).to_vec(),
+            1 => Sha384::digest(&e).to_vec(),
+            _ => Sha512::digest(&e).to_vec(),
+        };
+        round += 1;
+        if round >= 64 && u32::from(*e.last().unwrap()) <= round - 32 {
+            break;
+        }
+    }
+    k.truncate(32);
+    k
+}
+
+// The values from the /Encrypt dictionary that the key derivation needs.
+struct EncryptDict<'d> {
+    revision: i64,
+    key_length: usize, // In bytes
+    o: Vec<u8>,
+    u: Vec<u8>,
+    p: i64,
+    encrypt_metadata: bool,
+    dict: &'d DictionaryObject<'d>,
+}
+
+impl EncryptDict<'_> {
+    fn bytes(&self, key: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        self.dict
+            .get(key)
+            .and_then(|s| s.as_string())
+            .map(|s| s.decoded_bytes())
+            .ok_or_else(|| bad_dict(format!("no /{} string", String::from_utf8_lossy(key))))
+    }
+
+    // Algorithm 2: the file key from the (user) password, for revisions 2 to 4.
+    fn file_key(&self, password: &[u8], id: &[u8]) -> Vec<u8> {
+        let mut hasher = Md5::new()
+            .chain_update(padded(password))
+            .chain_update(&self.o)
+            .chain_update((self.p as u32).to_le_bytes())
+            .chain_update(id);
+        if self.revision >= 4 && !self.encrypt_metadata {
+            hasher.update([0xFF; 4]);
+        }
+        let mut key = hasher.finalize().to_vec();
+        if self.revision >= 3 {
+            for _ in 0..50 {
+                key = Md5::digest(&key[..self.key_length]).to_vec();
+            }
+        }
+        key.truncate(self.key_length);
+        key
+    }
+
+    // Algorithms 4 and 5: whether `key` is the file key, by checking it against /U.
+    fn is_user_key(&self, key: &[u8], id: &[u8]) -> bool {
+        if self.revision == 2 {
+            return rc4(key, &PADDING) == self.u;
+        }
+        let mut u = rc4(
+            key,
+            &Md5::new().chain_update(PADDING).chain_update(id).finalize(),
+        );
+        for i in 1..=19 {
+            u = rc4(&xor_key(key, i), &u);
+        }
+        self.u.len() >= 16 && u[..16] == self.u[..16]
+    }
+
+    // Algorithm 7: the user password, from the owner password and /O.
+    fn user_password_from_owner(&self, owner_password: &[u8]) -> Vec<u8> {
+        let mut key = Md5::digest(padded(owner_password)).to_vec();
+        if self.revision >= 3 {
+            for _ in 0..50 {
+                key = Md5::digest(&key).to_vec();
+            }
+        }
+        key.truncate(self.key_length);
+        if self.revision == 2 {
+            rc4(&key, &self.o)
+        } else {
+            (0..=19)
+                .rev()
+                .fold(self.o.clone(), |data, i| rc4(&xor_key(&key, i), &data))
+        }
+    }
+
+    // Algorithms 2.A, 11 and 12: the file key for revisions 5 and 6, in which /U and /O are a
+    // 32-byte hash, an 8-byte validation salt and an 8-byte key salt, and the file key is encrypted
+    // (with a key from the password and the key salt) in /UE or /OE.
+    fn file_key_aes_256(&self, password: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        if self.o.len() < 48 || self.u.len() < 48 {
+            return Err(bad_dict("/O and /U of at least 48 bytes"));
+        }
+        // "The UTF-8 password string shall be generated from Unicode input by processing the input
+        // string with the SASLprep profile"; we leave that to the caller.
+        let password = &password[..password.len().min(127)];
+        let hash = |salt: &[u8], user_key: &[u8]| -> Vec<u8> {
+            if self.revision == 5 {
+                Sha256::new()
+                    .chain_update(password)
+                    .chain_update(salt)
+                    .chain_update(user_key)
+                    .finalize()
+                    .to_vec()
+            } else {
+                hash_r6(password, salt, user_key)
+            }
+        };
+        let u = &self.u[..48];
+        let (intermediate_key, encrypted_key) = if hash(&self.o[32..40], u) == self.o[..32] {
+            (hash(&self.o[40..48], u), self.bytes(b"OE")?)
+        } else if hash(&self.u[32..40], &[]) == self.u[..32] {
+            (hash(&self.u[40..48], &[]), self.bytes(b"UE")?)
+        } else {
+            return Err(EncryptionError::IncorrectPassword);
+        };
+        if encrypted_key.len() != 32 {
+            return Err(bad_dict("/OE and /UE of 32 bytes"));
+        }
+        aes_cbc_decrypt(&intermediate_key, &[0; 16], &encrypted_key)
+    }
+}
+
+impl SecurityHandler {
+    // The handler for the file whose trailer has `encrypt` as its /Encrypt dictionary (object
+    // `encrypt_dict_object_number`, if it was a reference), and `id` as the first string in its /ID.
+    pub(super) fn new(
+        encrypt: &DictionaryObject,
+        encrypt_dict_object_number: Option<u32>,
+        id: &[u8],
+        password: &[u8],
+    ) -> Result<SecurityHandler, EncryptionError> {
+        let integer = |key: &[u8]| encrypt.get(key).and_then(|v| v.as_integer());
+        let name = |key: &[u8]| encrypt.get(key).and_then(|v| v.as_name());
+        if !name(b"Filter").is_some_and(|f| f.is(b"Standard")) {
+            return Err(EncryptionError::Unsupported(
+                "a security handler other than /Standard".to_string(),
+            ));
+        }
+        let version = integer(b"V").unwrap_or(0);
+        let revision = integer(b"R").ok_or_else(|| bad_dict("no /R integer"))?;
+        let key_bytes = |bits: i64| -> Result<usize, EncryptionError> {
+            if !(40..=128).contains(&bits) || bits % 8 != 0 {
+                return Err(bad_dict(format!(
+                    "/Length {} (should be a multiple of 8 from 40 to 128)",
+                    bits
+                )));
+            }
+            Ok(bits as usize / 8)
+        };
+        let key_length = match version {
+            1 => 5,
+            2 => key_bytes(integer(b"Length").unwrap_or(40))?,
+            // The length of the standard crypt filter's key, which is in bits per the spec, but is
+            // usually written in bytes (e.g. `/Length 16`).
+            4 => match encrypt
+                .get(b"CF")
+                .and_then(|cf| cf.as_dictionary())
+                .and_then(|cf| cf.get(b"StdCF"))
+                .and_then(|f| f.as_dictionary())
+                .and_then(|f| f.get(b"Length"))
+                .and_then(|length| length.as_integer())
+            {
+                None => 16,
+                Some(bytes @ 5..=16) => bytes as usize,
+                Some(bits) => key_bytes(bits)?,
+            },
+            5 => 32,
+            _ => return Err(EncryptionError::Unsupported(format!("/V {}", version))),
+        };
+        if !(2..=6).contains(&revision) {
+            return Err(EncryptionError::Unsupported(format!("/R {}", revision)));
+        }
+        // Revisions 5 and 6 (with AES-256 and a 32-byte key) go with /V 5, and only with it.
+        if (version == 5) != (revision >= 5) {
+            return Err(bad_dict(format!("/R {} with /V {}", revision, version)));
+        }
+        let encrypt_metadata = !matches!(
+            encrypt.get(b"EncryptMetadata"),
+            Some(ObjectOrReference::Object(Object::Boolean(
+                BooleanObject::False,
+                _
+            )))
+        );
+        let mut dict = EncryptDict {
+            revision,
+            key_length,
+            o: vec![],
+            u: vec![],
+            p: integer(b"P").ok_or_else(|| bad_dict("no /P integer"))?,
+            encrypt_metadata,
+            dict: encrypt,
+        };
+        dict.o = dict.bytes(b"O")?;
+        dict.u = dict.bytes(b"U")?;
+
+        let key = if revision >= 5 {
+            dict.file_key_aes_256(password)?
+        } else {
+            let user_key = dict.file_key(password, id);
+            if dict.is_user_key(&user_key, id) {
+                user_key
+            } else {
+                let user_password = dict.user_password_from_owner(password);
+                let owner_key = dict.file_key(&user_password, id);
+                if !dict.is_user_key(&owner_key, id) {
+                    return Err(EncryptionError::IncorrectPassword);
+                }
+                owner_key
+            }
+        };
+
+        // With /V 4 and 5, strings and streams can each use a different "crypt filter", from /CF.
+        let (string_cipher, stream_cipher) = if version >= 4 {
+            let crypt_filter = |key: &[u8]| -> Result<Cipher, EncryptionError> {
+                match name(key) {
+                    None => Ok(Cipher::Identity),
+                    Some(n) if n.is(b"Identity") => Ok(Cipher::Identity),
+                    Some(n) => {
+                        let cfm = encrypt
+                            .get(b"CF")
+                            .and_then(|cf| cf.as_dictionary())
+                            .and_then(|cf| cf.get(&n.decoded_bytes()))
+                            .and_then(|f| f.as_dictionary())
+                            .ok_or_else(|| {
+                                bad_dict(format!(
+                                    "no crypt filter /{}",
+                                    String::from_utf8_lossy(&n.decoded_bytes())
+                                ))
+                            })?
+                            .get(b"CFM")
+                            .and_then(|m| m.as_name());
+                        match cfm {
+                            None => Ok(Cipher::Identity),
+                            Some(m) if m.is(b"None") => Ok(Cipher::Identity),
+                            Some(m) if m.is(b"V2") => Ok(Cipher::Rc4),
+                            Some(m) if m.is(b"AESV2") => Ok(Cipher::AesV2),
+                            Some(m) if m.is(b"AESV3") => Ok(Cipher::AesV3),
+                            Some(m) => Err(EncryptionError::Unsupported(format!(
+                                "/CFM /{}",
+                                String::from_utf8_lossy(&m.decoded_bytes())
+                            ))),
+                        }
+                    }
+                }
+            };
+            (crypt_filter(b"StrF")?, crypt_filter(b"StmF")?)
+        } else {
+            (Cipher::Rc4, Cipher::Rc4)
+        };
+
+        Ok(SecurityHandler {
+            key,
+            string_cipher,
+            stream_cipher,
+            encrypt_metadata,
+            encrypt_dict_object_number,
+        })
+    }
+
+    // Algorithm 1: the key for object `number`.
+    fn object_key(&self, number: u32, generation: u16, cipher: Cipher) -> Vec<u8> {
+        let mut hasher = Md5::new()
+            .chain_update(&self.key)
+            .chain_update(&number.to_le_bytes()[..3])
+            .chain_update(generation.to_le_bytes());
+        if cipher == Cipher::AesV2 {
+            hasher.update(b"sAlT");
+        }
+        let mut key = hasher.finalize().to_vec();
+        key.truncate((self.key.len() + 5).min(16));
+        key
+    }
+
+    fn decrypt(
+        &self,
+        cipher: Cipher,
+        number: u32,
+        generation: u16,
+        data: &[u8],
+    ) -> Result<Vec<u8>, EncryptionError> {
+        match cipher {
+            Cipher::Identity => Ok(data.to_vec()),
+            Cipher::Rc4 => Ok(rc4(&self.object_key(number, generation, cipher), data)),
+            Cipher::AesV2 | Cipher::AesV3 => {
+                // The first block is the initialization vector.
+                if data.is_empty() {
+                    return Ok(vec![]);
+                }
+                if data.len() < 16 {
+                    return Err(EncryptionError::BadData(format!(
+                        "{} bytes of AES data (fewer than 16)",
+                        data.len()
+                    )));
+                }
+                let key = match cipher {
+                    Cipher::AesV2 => self.object_key(number, generation, cipher),
+                    _ => self.key.clone(),
+                };
+                let mut out = aes_cbc_decrypt(&key, &data[..16], &data[16..])?;
+                // Remove the PKCS#5 padding, if it is valid.
+                if let Some(&n) = out.last() {
+                    let n = usize::from(n);
+                    if (1..=16).contains(&n)
+                        && n <= out.len()
+                        && out[out.len() - n..].iter().all(|&b| usize::from(b) == n)
+                    {
+                        out.truncate(out.len() - n);
+                    }
+                }
+                Ok(out)
+            }
+        }
+    }
+
+    // The plaintext of a string (given as bytes) in object `number`.
+    pub(super) fn decrypt_string(
+        &self,
+        number: u32,
+        generation: u16,
+        data: &[u8],
+    ) -> Result<Vec<u8>, EncryptionError> {
+        if self.encrypt_dict_object_number == Some(number) {
+            return Ok(data.to_vec());
+        }
+        self.decrypt(self.string_cipher, number, generation, data)
+    }
+
+    // The plaintext of `stream` (whose data is `data`), which is object `number`.
+    pub(super) fn decrypt_stream(
+        &self,
+        number: u32,
+        generation: u16,
+        stream: &StreamObject,
+        data: &[u8],
+    ) -> Result<Vec<u8>, EncryptionError> {
+        let has_type = |t: &[u8]| {
+            stream
+                .dict
+                .get(b"Type")
+                .and_then(|n| n.as_name())
+                .is_some_and(|n| n.is(t))
+        };
+        // A stream with its own crypt filter (in /Filter) is decrypted by that filter, not here.
+        let first_filter_is_crypt = match stream.dict.get(b"Filter") {
+            Some(f) => f
+                .as_name()
+                .or_else(|| f.as_array().and_then(|a| a.elements().next()?.as_name()))
+                .is_some_and(|n| n.is(b"Crypt")),
+            None => false,
+        };
+        if has_type(b"XRef")
+            || (has_type(b"Metadata") && !self.encrypt_metadata)
+            || first_filter_is_crypt
+        {
+            return Ok(data.to_vec());
+        }
+        self.decrypt(self.stream_cipher, number, generation, data)
+    }
+}
+
+#[test]
+fn test_rc4() {
+    // From RFC 6229 (key 0x0102030405).
+    assert_eq!(
+        rc4(&[1, 2, 3, 4, 5], &[0; 8]),
+        [0xb2, 0x39, 0x63, 0x05, 0xf0, 0x3d, 0xc0, 0x27]
+    );
+}
@@ -0,0 +1,738 @@
+// ===========
+// 7.4 Filters
+// ===========
+// A stream's data is usually encoded: its dictionary has a /Filter entry, which is either the name
+// of one filter or an array of names, and an optional /DecodeParms entry, which is (correspondingly)
+// a dictionary of parameters or an array of them (with `null` for a filter that has none).
+// The filters are applied in order to decode the data, e.g.
+//     << /Filter [/ASCII85Decode /FlateDecode] >>
+// means the data was compressed with Flate and the result encoded as ASCII base-85.
+
+use super::encryption::EncryptionError;
+use super::*;
+
+/// Why a stream's data could not be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// /Filter or /DecodeParms is not of the form the spec requires.
+    BadFilterSpec(String),
+    /// A filter that is valid but not supported (e.g. /DCTDecode).
+    Unsupported(String),
+    /// The data is not valid input for the filter.
+    BadData {
+        filter: &'static str,
+        message: String,
+    },
+    /// The stream is encrypted, and could not be decrypted.
+    Encryption(EncryptionError),
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::BadFilterSpec(message) => {
+                write!(f, "bad /Filter or /DecodeParms: {}", message)
+            }
+            DecodeError::Unsupported(what) => write!(f, "unsupported: {}", what),
+            DecodeError::BadData { filter, message } => {
+                write!(f, "invalid /{} data: {}", filter, message)
+            }
+            DecodeError::Encryption(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+fn bad_data(filter: &'static str, message: impl Into<String>) -> DecodeError {
+    DecodeError::BadData {
+        filter,
+        message: message.into(),
+    }
+}
+
+impl StreamObject<'_> {
+    /// The data in the stream, with each of the filters in /Filter undone (in order).
+    pub fn decoded(&self) -> Result<Cow<'_, [u8]>, DecodeError> {
+        self.undo_filters(Cow::Borrowed(self.data()))
+    }
+
+    // `data` (this stream's data, perhaps decrypted) with each of the filters in /Filter undone.
+    pub(super) fn undo_filters<'d>(
+        &self,
+        data: Cow<'d, [u8]>,
+    ) -> Result<Cow<'d, [u8]>, DecodeError> {
+        let mut data = data;
+        for (filter, parms) in self.filters()? {
+            data = Cow::Owned(decode(&filter, parms, &data)?);
+        }
+        Ok(data)
+    }
+
+    // The filters, each with its /DecodeParms dictionary (if any).
+    fn filters(&self) -> Result<Vec<(String, Option<&DictionaryObject<'_>>)>, DecodeError> {
+        let names: Vec<&ObjectOrReference> = match self.dict.get(b"Filter") {
+            None => return Ok(vec![]),
+            Some(f) if f.as_name().is_some() => vec![f],
+            Some(f) => f
+                .as_array()
+                .ok_or_else(|| {
+                    DecodeError::BadFilterSpec("/Filter is not a name or an array".to_string())
+                })?
+                .elements()
+                .collect(),
+        };
+        let names = names
+            .into_iter()
+            .map(|n| {
+                n.as_name()
+                    .map(|n| String::from_utf8_lossy(&n.decoded_bytes()).into_owned())
+            })
+            .collect::<Option<Vec<String>>>()
+            .ok_or_else(|| {
+                DecodeError::BadFilterSpec("/Filter has an element that is not a name".to_string())
+            })?;
+        let is_null =
+            |p: &ObjectOrReference| matches!(p, ObjectOrReference::Object(Object::Null(_)));
+        let parms: Vec<Option<&DictionaryObject>> = match self.dict.get(b"DecodeParms") {
+            None => vec![None; names.len()],
+            Some(p) if is_null(p) => vec![None; names.len()],
+            Some(p) if p.as_dictionary().is_some() => vec![p.as_dictionary()],
+            Some(p) => p
+                .as_array()
+                .ok_or_else(|| {
+                    DecodeError::BadFilterSpec(
+                        "/DecodeParms is not a dictionary or an array".to_string(),
+                    )
+                })?
+                .elements()
+                .map(|p| {
+                    if is_null(p) {
+                        Ok(None)
+                    } else {
+                        p.as_dictionary().map(Some).ok_or_else(|| {
+                            DecodeError::BadFilterSpec(
+                                "/DecodeParms has an element that is not a dictionary or null"
+                                    .to_string(),
+                            )
+                        })
+                    }
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        if parms.len() != names.len() {
+            return Err(DecodeError::BadFilterSpec(format!(
+                "{} filters but {} entries in /DecodeParms",
+                names.len(),
+                parms.len()
+            )));
+        }
+        Ok(names.into_iter().zip(parms).collect())
+    }
+}
+
+// The parameter `key` in `parms`, if it is present, as an integer.
+fn integer_parm(parms: Option<&DictionaryObject>, key: &[u8]) -> Result<Option<i64>, DecodeError> {
+    match parms.and_then(|p| p.get(key)) {
+        None => Ok(None),
+        Some(value) => value.as_integer().map(Some).ok_or_else(|| {
+            DecodeError::BadFilterSpec(format!(
+                "/{} in /DecodeParms is not an integer",
+                String::from_utf8_lossy(key)
+            ))
+        }),
+    }
+}
+
+// Undoes one filter.
+fn decode(
+    filter: &str,
+    parms: Option<&DictionaryObject>,
+    data: &[u8],
+) -> Result<Vec<u8>, DecodeError> {
+    // Abbreviated names are allowed in inline images; accept them everywhere.
+    match filter {
+        "FlateDecode" | "Fl" => {
+            let predictor = Predictor::from_parms(parms)?;
+            predictor.undo("FlateDecode", flate_decode(data)?)
+        }
+        "LZWDecode" | "LZW" => {
+            let predictor = Predictor::from_parms(parms)?;
+            let early_change = match integer_parm(parms, b"EarlyChange")? {
+                None | Some(1) => true,
+                Some(0) => false,
+                Some(n) => {
+                    return Err(DecodeError::BadFilterSpec(format!(
+                        "/EarlyChange is {} (should be 0 or 1)",
+                        n
+                    )))
+                }
+            };
+            predictor.undo("LZWDecode", lzw_decode(data, early_change)?)
+        }
+        "ASCIIHexDecode" | "AHx" => ascii_hex_decode(data),
+        "ASCII85Decode" | "A85" => ascii85_decode(data),
+        "RunLengthDecode" | "RL" => run_length_decode(data),
+        // The Identity crypt filter does nothing; any other one is about encryption.
+        "Crypt"
+            if parms
+                .and_then(|p| p.get(b"Name"))
+                .and_then(|n| n.as_name())
+                .is_none_or(|n| n.is(b"Identity")) =>
+        {
+            Ok(data.to_vec())
+        }
+        "Crypt" | "DCTDecode" | "DCT" | "JPXDecode" | "JBIG2Decode" | "CCITTFaxDecode" | "CCF" => {
+            Err(DecodeError::Unsupported(format!("the /{} filter", filter)))
+        }
+        _ => Err(DecodeError::BadFilterSpec(format!(
+            "/{} is not a standard filter",
+            filter
+        ))),
+    }
+}
+
+// 7.4.4.4 LZW and Flate Predictor Functions
+// The data may have been transformed before compression, to compress better: with /Predictor 2
+// (TIFF), each component is stored as the difference from the same component of the pixel to its
+// left; with /Predictor 10 to 15 (PNG), each row starts with a byte saying how that row was
+// transformed (so the specific value from 10 to 15 does not matter when decoding).
+struct Predictor {
+    predictor: i64,
+    colors: usize,             // Components per pixel
+    bits_per_component: usize, // 1, 2, 4, 8 or 16
+    columns: usize,            // Pixels per row
+}
+
+impl Predictor {
+    fn from_parms(parms: Option<&DictionaryObject>) -> Result<Predictor, DecodeError> {
+        let positive = |key: &[u8], default: i64| -> Result<usize, DecodeError> {
+            let value = integer_parm(parms, key)?.unwrap_or(default);
+            usize::try_from(value)
+                .ok()
+                .filter(|&v| v > 0)
+                .ok_or_else(|| {
+                    DecodeError::BadFilterSpec(format!(
+                        "/{} is {} (should be positive)",
+                        String::from_utf8_lossy(key),
+                        value
+                    ))
+                })
+        };
+        let predictor = integer_parm(parms, b"Predictor")?.unwrap_or(1);
+        if !matches!(predictor, 1 | 2 | 10..=15) {
+            return Err(DecodeError::BadFilterSpec(format!(
+                "/Predictor is {} (should be 1, 2, or 10 to 15)",
+                predictor
+            )));
+        }
+        let bits_per_component = positive(b"BitsPerComponent", 8)?;
+        if !matches!(bits_per_component, 1 | 2 | 4 | 8 | 16) {
+            return Err(DecodeError::BadFilterSpec(format!(
+                "/BitsPerComponent is {} (should be 1, 2, 4, 8 or 16)",
+                bits_per_component
+            )));
+        }
+        Ok(Predictor {
+            predictor,
+            colors: positive(b"Colors", 1)?,
+            bits_per_component,
+            columns: positive(b"Columns", 1)?,
+        })
+    }
+
+    fn undo(&self, filter: &'static str, data: Vec<u8>) -> Result<Vec<u8>, DecodeError> {
+        let bits_per_pixel = self
+            .colors
+            .checked_mul(self.bits_per_component)
+            .and_then(|b| b.checked_mul(self.columns))
+            .ok_or_else(|| DecodeError::BadFilterSpec("/Columns is too large".to_string()))?;
+        let row_len = bits_per_pixel.div_ceil(8);
+        // The distance between corresponding bytes of adjacent pixels (for PNG), at least 1.
+        let bytes_per_pixel = (self.colors * self.bits_per_component).div_ceil(8);
+        match self.predictor {
+            1 => Ok(data),
+            2 => Ok(self.undo_tiff(data, row_len)),
+            _ => undo_png(filter, &data, row_len, bytes_per_pixel),
+        }
+    }
+
+    // A partial row at the end is decoded as far as it goes.
+    fn undo_tiff(&self, mut data: Vec<u8>, row_len: usize) -> Vec<u8> {
+        let bpc = self.bits_per_component;
+        for row in data.chunks_mut(row_len) {
+            match bpc {
+                8 => {
+                    for i in self.colors..row.len() {
+                        row[i] = row[i].wrapping_add(row[i - self.colors]);
+                    }
+                }
+                16 => {
+                    let components = row.len() / 2;
+                    for i in self.colors..components {
+                        let left = u16::from_be_bytes([
+                            row[2 * (i - self.colors)],
+                            row[2 * (i - self.colors) + 1],
+                        ]);
+                        let this = u16::from_be_bytes([row[2 * i], row[2 * i + 1]]);
+                        row[2 * i..2 * i + 2]
+                            .copy_from_slice(&this.wrapping_add(left).to_be_bytes());
+                    }
+                }
+                _ => {
+                    // Components of 1, 2 or 4 bits, packed most significant first.
+                    let mask = (1u8 << bpc) - 1;
+                    let get = |row: &[u8], i: usize| {
+                        (row[i * bpc / 8] >> (8 - bpc - (i * bpc) % 8)) & mask
+                    };
+                    let components = (row.len() * 8 / bpc).min(self.colors * self.columns);
+                    for i in self.colors..components {
+                        let value = get(row, i).wrapping_add(get(row, i - self.colors)) & mask;
+                        let shift = 8 - bpc - (i * bpc) % 8;
+                        let byte = &mut row[i * bpc / 8];
+                        *byte = (*byte & !(mask << shift)) | (value << shift);
+                    }
+                }
+            }
+        }
+        data
+    }
+}
+
+// Each row is a filter-type byte followed by `row_len` bytes; a partial row at the end is decoded
+// as far as it goes. Bytes "to the left of" the first pixel, and "above" the first row, are 0.
+fn undo_png(
+    filter: &'static str,
+    data: &[u8],
+    row_len: usize,
+    bytes_per_pixel: usize,
+) -> Result<Vec<u8>, DecodeError> {
+    // (A row can't be longer than the data, and a bad /Columns could make it far too long to
+    // allocate.)
+    if data.is_empty() {
+        return Ok(vec![]);
+    }
+    if row_len > data.len() {
+        return Err(DecodeError::BadFilterSpec(format!(
+            "/Columns is too large: a row would be {} bytes, but there are only {}",
+            row_len,
+            data.len()
+        )));
+    }
+    let mut out: Vec<u8> = Vec::with_capacity(data.len());
+    let mut previous_row = vec![0u8; row_len];
+    for encoded in data.chunks(row_len + 1) {
+        let (&filter_type, encoded) = encoded.split_first().unwrap();
+        let start = out.len();
+        for (i, &x) in encoded.iter().enumerate() {
+            let a = if i >= bytes_per_pixel {
+                out[start + i - bytes_per_pixel]
+            } else {
+                0
+            };
+            let b = previous_row[i];
+            let c = if i >= bytes_per_pixel {
+                previous_row[i - bytes_per_pixel]
+            } else {
+                0
+            };
+            let predicted = match filter_type {
+                0 => 0,                                         // None
+                1 => a,                                         // Sub
+                2 => b,                                         // Up
+                3 => ((u16::from(a) + u16::from(b)) / 2) as u8, // Average
+                4 => paeth(a, b, c),
+                _ => {
+                    return Err(bad_data(
+                        filter,
+                        format!("PNG row filter type {} (should be 0 to 4)", filter_type),
+                    ))
+                }
+            };
+            out.push(x.wrapping_add(predicted));
+        }
+        previous_row[..encoded.len()].copy_from_slice(&out[start..]);
+    }
+    Ok(out)
+}
+
+// The one of left, above and upper-left that is closest to left + above - upper-left.
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let (pa, pb, pc) = (
+        (p - i16::from(a)).abs(),
+        (p - i16::from(b)).abs(),
+        (p - i16::from(c)).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// 7.4.4 LZWDecode and FlateDecode Filters
+fn flate_decode(data: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let mut out = vec![];
+    io::Read::read_to_end(&mut flate2::read::ZlibDecoder::new(data), &mut out)
+        .map_err(|e| bad_data("FlateDecode", e.to_string()))?;
+    Ok(out)
+}
+
+// Codes are 9 to 12 bits, most significant bit first. Code 256 clears the table, and 257 ends the data.
+// With "early change", the code width goes up one code before the table needs it to.
+fn lzw_decode(data: &[u8], early_change: bool) -> Result<Vec<u8>, DecodeError> {
+    const CLEAR_TABLE: usize = 256;
+    const EOD: usize = 257;
+    let initial_table = || -> Vec<Vec<u8>> {
+        let mut table: Vec<Vec<u8>> = (0..=255).map(|b| vec![b]).collect();
+        table.extend([vec![], vec![]]); // The two special codes
+        table
+    };
+    let mut table = initial_table();
+    let mut width = 9;
+    let mut previous: Option<Vec<u8>> = None;
+    let mut out = vec![];
+    let (mut buffer, mut bits_in_buffer) = (0u32, 0);
+    let mut bytes = data.iter();
+    loop {
+        while bits_in_buffer < width {
+            match bytes.next() {
+                Some(&b) => {
+                    buffer = (buffer << 8) | u32::from(b);
+                    bits_in_buffer += 8;
+                }
+                // Running out of data (without an EOD) is fine.
+                None => return Ok(out),
+            }
+        }
+        let code = ((buffer >> (bits_in_buffer - width)) & ((1 << width) - 1)) as usize;
+        bits_in_buffer -= width;
+        if code == CLEAR_TABLE {
+            table = initial_table();
+            width = 9;
+            previous = None;
+            continue;
+        }
+        if code == EOD {
+            return Ok(out);
+        }
+        let entry = match (table.get(code), &previous) {
+            (Some(entry), _) => entry.clone(),
+            (None, Some(p)) if code == table.len() => {
+                let mut entry = p.clone();
+                entry.push(p[0]);
+                entry
+            }
+            _ => return Err(bad_data("LZWDecode", format!("unexpected code {}", code))),
+        };
+        out.extend_from_slice(&entry);
+        if let Some(mut p) = previous.take() {
+            if table.len() < 4096 {
+                p.push(entry[0]);
+                table.push(p);
+            }
+        }
+        previous = Some(entry);
+        if table.len() + usize::from(early_change) >= 1 << width && width < 12 {
+            width += 1;
+        }
+    }
+}
+
+// 7.4.2 ASCIIHexDecode Filter
+fn ascii_hex_decode(data: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let mut digits = vec![];
+    for &c in data {
+        match c {
+            b'>' => break,
+            c if is_white_space_char(c) => {}
+            c if c.is_ascii_hexdigit() => digits.push((c as char).to_digit(16).unwrap() as u8),
+            c => {
+                return Err(bad_data(
+                    "ASCIIHexDecode",
+                    format!("byte {:?} is not a hex digit", c as char),
+                ))
+            }
+        }
+    }
+    // "If the filter encounters the EOD marker after reading an odd number of hexadecimal digits,
+    // it shall behave as if a 0 (zero) followed the last digit."
+    if digits.len() % 2 == 1 {
+        digits.push(0);
+    }
+    Ok(digits.chunks(2).map(|d| (d[0] << 4) | d[1]).collect())
+}
+
+// 7.4.3 ASCII85Decode Filter
+// Each group of 5 characters from `!` to `u` is 4 bytes in base 85; `z` is 4 zero bytes,
+// and `~>` ends the data. A final partial group of n characters is n - 1 bytes.
+fn ascii85_decode(data: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let mut out = vec![];
+    let mut group: Vec<u32> = Vec::with_capacity(5);
+    let push_group = |group: &[u32], out: &mut Vec<u8>| -> Result<(), DecodeError> {
+        // Pad with `u`s, then keep one byte fewer than the number of characters.
+        let value = (0..5).fold(0u64, |v, i| {
+            v * 85 + u64::from(*group.get(i).unwrap_or(&84))
+        });
+        let value = u32::try_from(value)
+            .map_err(|_| bad_data("ASCII85Decode", "a group's value is more than 2^32"))?;
+        out.extend_from_slice(&value.to_be_bytes()[..group.len() - 1]);
+        Ok(())
+    };
+    for &c in data {
+        match c {
+            b'~' => break,
+            c if is_white_space_char(c) => {}
+            b'z' if group.is_empty() => out.extend_from_slice(&[0; 4]),
+            b'!'..=b'u' => {
+                group.push(u32::from(c - b'!'));
+                if group.len() == 5 {
+                    push_group(&group, &mut out)?;
+                    group.clear();
+                }
+            }
+            c => {
+                return Err(bad_data(
+                    "ASCII85Decode",
+                    format!("unexpected byte {:?}", c as char),
+                ))
+            }
+        }
+    }
+    match group.len() {
+        0 => {}
+        1 => return Err(bad_data("ASCII85Decode", "a final group of one character")),
+        _ => push_group(&group, &mut out)?,
+    }
+    Ok(out)
+}
+
+// 7.4.5 RunLengthDecode Filter
+// A length byte n from 0 to 127 is followed by n + 1 bytes to copy; from 129 to 255, by one byte
+// to repeat 257 - n times. 128 ends the data.
+fn run_length_decode(data: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let mut out = vec![];
+    let mut rest = data;
+    while let Some((&n, after)) = rest.split_first() {
+        match n {
+            128 => break,
+            0..=127 => {
+                let count = usize::from(n) + 1;
+                if after.len() < count {
+                    return Err(bad_data("RunLengthDecode", "a run that ends early"));
+                }
+                out.extend_from_slice(&after[..count]);
+                rest = &after[count..];
+            }
+            _ => {
+                let &b = after
+                    .first()
+                    .ok_or_else(|| bad_data("RunLengthDecode", "a run that ends early"))?;
+                out.extend(std::iter::repeat_n(b, 257 - usize::from(n)));
+                rest = &after[1..];
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+fn stream_with(dict: &str, content: &[u8]) -> Vec<u8> {
+    let mut bytes = format!("{}\nstream\n", dict).into_bytes();
+    bytes.extend_from_slice(content);
+    bytes.extend_from_slice(b"endstream");
+    bytes
+}
+
+#[cfg(test)]
+fn decoded(bytes: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    match object(bytes).unwrap().1 {
+        Object::Stream(s) => s.decoded().map(|d| d.into_owned()),
+        _ => panic!("not a stream"),
+    }
+}
+
+#[test]
+fn test_filters() {
+    assert_eq!(decoded(&stream_with("<< >>", b"raw")).unwrap(), b"raw");
+    assert_eq!(
+        decoded(&stream_with(
+            "<< /Filter /ASCIIHexDecode >>",
+            b"48 65 6C6c6F>"
+        ))
+        .unwrap(),
+        b"Hello"
+    );
+    assert_eq!(
+        decoded(&stream_with("<< /Filter /AHx >>", b"486>")).unwrap(),
+        [0x48, 0x60]
+    );
+    assert_eq!(
+        decoded(&stream_with(
+            "<< /Filter /ASCII85Decode >>",
+            b"87cURD]i,\"Ebo7~>"
+        ))
+        .unwrap(),
+        b"Hello World"
+    );
+    assert_eq!(
+        decoded(&stream_with("<< /Filter /A85 >>", b"z@:E^~>")).unwrap(),
+        b"\0\0\0\0abc"
+    );
+    // The example in the spec (7.4.4.2).
+    assert_eq!(
+        decoded(&stream_with(
+            "<< /Filter /LZWDecode >>",
+            &[0x80, 0x0B, 0x60, 0x50, 0x22, 0x0C, 0x0C, 0x85, 0x01]
+        ))
+        .unwrap(),
+        [45, 45, 45, 45, 45, 65, 45, 45, 45, 66]
+    );
+    assert_eq!(
+        decoded(&stream_with(
+            "<< /Filter /RunLengthDecode >>",
+            &[2, b'a', b'b', b'c', 254, b'x', 128]
+        ))
+        .unwrap(),
+        b"abcxxx"
+    );
+}
+
+#[test]
+fn test_filter_chain() {
+    let mut encoder = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(b"BT /F1 12 Tf ET").unwrap();
+    let compressed = encoder.finish().unwrap();
+    let hex: String = compressed.iter().map(|b| format!("{:02x}", b)).collect();
+    let bytes = stream_with(
+        "<< /Filter [/ASCIIHexDecode /FlateDecode] /DecodeParms [null null] >>",
+        format!("{}>", hex).as_bytes(),
+    );
+    assert_eq!(decoded(&bytes).unwrap(), b"BT /F1 12 Tf ET");
+}
+
+#[test]
+fn test_filter_errors() {
+    assert_eq!(
+        decoded(&stream_with("<< /Filter /DCTDecode >>", b"")).unwrap_err(),
+        DecodeError::Unsupported("the /DCTDecode filter".to_string())
+    );
+    assert_eq!(
+        decoded(&stream_with("<< /Filter /Bogus >>", b""))
+            .unwrap_err()
+            .to_string(),
+        "bad /Filter or /DecodeParms: /Bogus is not a standard filter"
+    );
+    assert_eq!(
+        decoded(&stream_with(
+            "<< /Filter [/AHx /AHx] /DecodeParms [null] >>",
+            b""
+        ))
+        .unwrap_err(),
+        DecodeError::BadFilterSpec("2 filters but 1 entries in /DecodeParms".to_string())
+    );
+    assert_eq!(
+        decoded(&stream_with("<< /Filter /AHx >>", b"4G>"))
+            .unwrap_err()
+            .to_string(),
+        "invalid /ASCIIHexDecode data: byte 'G' is not a hex digit"
+    );
+    assert!(matches!(
+        decoded(&stream_with("<< /Filter /FlateDecode >>", b"not zlib")),
+        Err(DecodeError::BadData {
+            filter: "FlateDecode",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_png_predictor() {
+    // One byte per pixel, two pixels per row; one row with each PNG filter type.
+    let rows: &[u8] = &[1, 10, 5, 2, 1, 1, 3, 4, 3, 4, 1, 2, 0, 7, 7];
+    assert_eq!(
+        Predictor::from_parms(None)
+            .unwrap()
+            .undo("FlateDecode", rows.to_vec()),
+        Ok(rows.to_vec())
+    );
+    let (_, dict) = object_dictionary(b"<< /Predictor 12 /Columns 2 >>").unwrap();
+    let predictor = Predictor::from_parms(Some(&dict)).unwrap();
+    assert_eq!(
+        predictor.undo("FlateDecode", rows.to_vec()).unwrap(),
+        [10, 15, 11, 16, 9, 15, 10, 17, 7, 7]
+    );
+    assert_eq!(
+        predictor
+            .undo("FlateDecode", vec![5, 0, 0])
+            .unwrap_err()
+            .to_string(),
+        "invalid /FlateDecode data: PNG row filter type 5 (should be 0 to 4)"
+    );
+
+    let (_, dict) = object_dictionary(b"<< /Predictor 12 /Columns 4000000000000 >>").unwrap();
+    let predictor = Predictor::from_parms(Some(&dict)).unwrap();
+    assert_eq!(
+        predictor.undo("FlateDecode", rows.to_vec()),
+        Err(DecodeError::BadFilterSpec(
+            "/Columns is too large: a row would be 4000000000000 bytes, but there are only 15"
+                .to_string()
+        ))
+    );
+    assert_eq!(predictor.undo("FlateDecode", vec![]), Ok(vec![]));
+
+    // Three components of 16 bits each, so Sub looks 6 bytes back.
+    let (_, dict) =
+        object_dictionary(b"<< /Predictor 15 /Colors 3 /BitsPerComponent 16 /Columns 2 >>")
+            .unwrap();
+    let predictor = Predictor::from_parms(Some(&dict)).unwrap();
+    let row = [1, 0, 1, 0, 2, 0, 3, 0, 1, 0, 1, 0, 1];
+    assert_eq!(
+        predictor.undo("LZWDecode", row.to_vec()).unwrap(),
+        [0, 1, 0, 2, 0, 3, 0, 2, 0, 3, 0, 4]
+    );
+}
+
+#[test]
+fn test_tiff_predictor() {
+    let (_, dict) = object_dictionary(b"<< /Predictor 2 /Colors 2 /Columns 3 >>").unwrap();
+    let predictor = Predictor::from_parms(Some(&dict)).unwrap();
+    assert_eq!(
+        predictor
+            .undo("FlateDecode", vec![1, 2, 1, 1, 1, 255, 9, 9, 0, 0, 0, 0])
+            .unwrap(),
+        [1, 2, 2, 3, 3, 2, 9, 9, 9, 9, 9, 9]
+    );
+
+    // Four-bit components: 1 1 1 15 becomes 1 2 3 2 (as 3 + 15 wraps around).
+    let (_, dict) =
+        object_dictionary(b"<< /Predictor 2 /BitsPerComponent 4 /Columns 4 >>").unwrap();
+    let predictor = Predictor::from_parms(Some(&dict)).unwrap();
+    assert_eq!(
+        predictor.undo("FlateDecode", vec![0x11, 0x1F]).unwrap(),
+        [0x12, 0x32]
+    );
+
+    // Sixteen-bit components.
+    let (_, dict) =
+        object_dictionary(b"<< /Predictor 2 /BitsPerComponent 16 /Columns 2 >>").unwrap();
+    let predictor = Predictor::from_parms(Some(&dict)).unwrap();
+    assert_eq!(
+        predictor
+            .undo("FlateDecode", vec![0x01, 0xFF, 0x00, 0x02])
+            .unwrap(),
+        [0x01, 0xFF, 0x02, 0x01]
+    );
+
+    let (_, dict) = object_dictionary(b"<< /Predictor 3 >>").unwrap();
+    assert_eq!(
+        Predictor::from_parms(Some(&dict))
+            .err()
+            .unwrap()
+            .to_string(),
+        "bad /Filter or /DecodeParms: /Predictor is 3 (should be 1, 2, or 10 to 15)"
+    );
+}
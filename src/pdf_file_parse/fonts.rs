@@ -0,0 +1,453 @@
+// =================
+// 9.5-9.10 Fonts
+// =================
+// What extracting text needs to know about a font: how a string splits into character codes,
+// how far each code's glyph moves the text position, what text it stands for, and (for a
+// bounding box) how far glyphs reach above and below the baseline.
+// A simple font (Type 1, TrueType, Type 3) has single-byte codes, /Widths for codes from
+// /FirstChar on (or, for the standard 14 fonts, perhaps none), and an /Encoding that names the
+// glyph for each code. A composite (Type 0) font has an /Encoding CMap instead, which splits
+// strings into codes (of one or more bytes) and maps them to CIDs, and its widths are by CID, in
+// its descendant CIDFont's /W (with /DW for the rest). Either kind can have a /ToUnicode CMap,
+// which says outright what text each code stands for; that wins over anything the glyph names
+// say (9.10.2).
+
+use super::cmap::{parse_cmap, CMap};
+use super::encodings::{
+    builtin_encoding, dingbat_text, glyph_name_text, predefined_encoding, STANDARD_ENCODING,
+};
+#[cfg(test)]
+use super::pages::file_with_objects;
+use super::standard_fonts::standard_font;
+#[cfg(test)]
+use super::text::stream_object;
+use super::*;
+
+// A font, as far as text extraction is concerned.
+pub(super) struct Font {
+    // /BaseFont.
+    pub(super) name: String,
+    // For composite fonts: the /Encoding CMap, if it's known (it can be a predefined CMap that
+    // isn't known here, in which case codes are split per /ToUnicode, or else are 2 bytes long).
+    composite: bool,
+    cmap: Option<CMap>,
+    // For simple fonts: the glyph name for each code, from the base encoding and /Differences.
+    encoding: Option<&'static [Option<&'static str>; 256]>,
+    differences: BTreeMap<u8, Vec<u8>>,
+    // Whether this is ZapfDingbats, whose glyph names stand for dingbats.
+    dingbats: bool,
+    // Widths in glyph space, by code (or CID); `default_width` for any other, which is a guess if
+    // `default_width_is_guess` (for a standard 14 font without /Widths).
+    widths: BTreeMap<u32, f64>,
+    default_width: f64,
+    default_width_is_guess: bool,
+    // The heights of the ascenders and descenders, in glyph space.
+    ascent: f64,
+    descent: f64,
+    // From glyph space to text space: 1/1000, except for Type 3 fonts (/FontMatrix).
+    scale: f64,
+    to_unicode: Option<CMap>,
+}
+
+impl Font {
+    // The font described by font dictionary `dict`.
+    pub(super) fn new<'d, 'a>(document: &'d Document<'a>, dict: &'d DictionaryObject<'a>) -> Font {
+        let get = |dict: &'d DictionaryObject<'a>, key: &[u8]| -> Option<&'d Object<'a>> {
+            document.resolved(dict.get(key)?)
+        };
+        let number = |object: Option<&Object>| object.and_then(|o| o.as_number());
+        let subtype = match get(dict, b"Subtype") {
+            Some(Object::Name(name)) => name.decoded_bytes(),
+            _ => vec![],
+        };
+        let base_font = match get(dict, b"BaseFont") {
+            Some(Object::Name(name)) => name.decoded_bytes(),
+            _ => vec![],
+        };
+        let standard = standard_font(&base_font);
+        let mut font = Font {
+            name: String::from_utf8_lossy(&base_font).into_owned(),
+            composite: false,
+            cmap: None,
+            encoding: None,
+            differences: BTreeMap::new(),
+            dingbats: standard.is_some_and(|standard| standard.name == "ZapfDingbats"),
+            widths: BTreeMap::new(),
+            default_width: 0.0,
+            default_width_is_guess: false,
+            ascent: 0.0,
+            descent: 0.0,
+            scale: 0.001,
+            to_unicode: dict
+                .get(b"ToUnicode")
+                .and_then(|to_unicode| load_cmap(document, to_unicode, 0)),
+        };
+
+        if subtype == b"Type0" {
+            font.composite = true;
+            font.cmap = dict
+                .get(b"Encoding")
+                .and_then(|encoding| load_cmap(document, encoding, 0));
+            let descendant = match get(dict, b"DescendantFonts") {
+                Some(Object::Array(array)) => {
+                    match array.elements().next().map(|d| document.resolved(d)) {
+                        Some(Some(Object::Dictionary(descendant))) => Some(descendant),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            font.default_width = 1000.0;
+            if let Some(descendant) = descendant {
+                font.default_width = number(get(descendant, b"DW")).unwrap_or(1000.0);
+                if let Some(Object::Array(w)) = get(descendant, b"W") {
+                    font.widths = cid_widths(document, w);
+                }
+            }
+            font.set_ascent_and_descent(document, descendant.unwrap_or(dict));
+            return font;
+        }
+
+        // A simple font.
+        if subtype == b"Type3" {
+            if let Some(Object::Array(matrix)) = get(dict, b"FontMatrix") {
+                let a = matrix.elements().next().and_then(|a| document.resolved(a));
+                font.scale = number(a).unwrap_or(0.001);
+            }
+        }
+        // Symbol and ZapfDingbats have their own built-in encodings.
+        font.encoding = Some(
+            standard
+                .and_then(|standard| builtin_encoding(standard.name))
+                .unwrap_or(&STANDARD_ENCODING),
+        );
+        match get(dict, b"Encoding") {
+            Some(Object::Name(name)) => {
+                if let Some(encoding) = predefined_encoding(&name.decoded_bytes()) {
+                    font.encoding = Some(encoding);
+                }
+            }
+            Some(Object::Dictionary(encoding)) => {
+                if let Some(Object::Name(name)) = get(encoding, b"BaseEncoding") {
+                    if let Some(encoding) = predefined_encoding(&name.decoded_bytes()) {
+                        font.encoding = Some(encoding);
+                    }
+                }
+                // [code name name ... code name ...]: names for consecutive codes.
+                if let Some(Object::Array(differences)) = get(encoding, b"Differences") {
+                    let mut code = None;
+                    for element in differences.elements() {
+                        match document.resolved(element) {
+                            Some(Object::Name(name)) => {
+                                if let Some(c) = code.and_then(|c: i64| u8::try_from(c).ok()) {
+                                    font.differences.insert(c, name.decoded_bytes());
+                                }
+                                code = code.map(|c| c + 1);
+                            }
+                            Some(object) => code = object.as_number().map(|c| c as i64),
+                            None => code = None,
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        let first_char = number(get(dict, b"FirstChar"))
+            .unwrap_or(0.0)
+            .clamp(0.0, f64::from(u32::MAX)) as u32;
+        let missing_width = match get(dict, b"FontDescriptor") {
+            Some(Object::Dictionary(descriptor)) => number(get(descriptor, b"MissingWidth")),
+            _ => None,
+        };
+        match get(dict, b"Widths") {
+            Some(Object::Array(widths)) => {
+                for (i, width) in widths.elements().enumerate() {
+                    // (Any past the largest code there can be are skipped.)
+                    let code = u32::try_from(i)
+                        .ok()
+                        .and_then(|i| first_char.checked_add(i));
+                    if let (Some(code), Some(width)) = (code, number(document.resolved(width))) {
+                        font.widths.insert(code, width);
+                    }
+                }
+                font.default_width = missing_width.unwrap_or(0.0);
+            }
+            // (As with the standard 14 fonts, whose widths a reader is meant to know; 500 is a
+            // guess at the average, for the glyphs whose widths aren't known.)
+            _ => {
+                if let Some(standard) = standard {
+                    for code in 0..=255 {
+                        if let Some(width) = standard.width(code, font.glyph_name(code)) {
+                            font.widths.insert(code.into(), width);
+                        }
+                    }
+                }
+                font.default_width = missing_width.unwrap_or(500.0);
+                font.default_width_is_guess = missing_width.is_none();
+            }
+        }
+        font.set_ascent_and_descent(document, dict);
+        font
+    }
+
+    // Sets the ascent and descent from the font descriptor of `dict` (the font dictionary, or a
+    // composite font's descendant), or else the metrics of a standard font, or else /FontBBox.
+    fn set_ascent_and_descent<'d, 'a>(
+        &mut self,
+        document: &'d Document<'a>,
+        dict: &'d DictionaryObject<'a>,
+    ) {
+        let get = |dict: &'d DictionaryObject<'a>, key: &[u8]| -> Option<&'d Object<'a>> {
+            document.resolved(dict.get(key)?)
+        };
+        let descriptor = match get(dict, b"FontDescriptor") {
+            Some(Object::Dictionary(descriptor)) => Some(descriptor),
+            _ => None,
+        };
+        let number = |key: &[u8]| get(descriptor?, key)?.as_number().filter(|&n| n != 0.0);
+        let bbox = [descriptor, Some(dict)]
+            .into_iter()
+            .flatten()
+            .find_map(|d| document.rectangle(d.get(b"FontBBox")?));
+        let standard = standard_font(self.name.as_bytes());
+        // (A font with none of these is given the usual proportions.)
+        (self.ascent, self.descent) = match (number(b"Ascent"), number(b"Descent"), standard, bbox)
+        {
+            (Some(ascent), Some(descent), _, _) => (ascent, descent),
+            (_, _, Some(standard), _) => (standard.ascent, standard.descent),
+            (_, _, None, Some([_, lly, _, ury])) if ury > lly => (ury, lly),
+            _ => (750.0, -250.0),
+        };
+    }
+
+    // The codes in string `bytes`.
+    pub(super) fn codes<'s>(&'s self, mut bytes: &'s [u8]) -> impl Iterator<Item = &'s [u8]> + 's {
+        std::iter::from_fn(move || {
+            if bytes.is_empty() {
+                return None;
+            }
+            let (code, rest) = bytes.split_at(self.code_length(bytes));
+            bytes = rest;
+            Some(code)
+        })
+    }
+
+    // The length of the code that `bytes` (which is not empty) starts with.
+    fn code_length(&self, bytes: &[u8]) -> usize {
+        if !self.composite {
+            return 1;
+        }
+        let cmap = [&self.cmap, &self.to_unicode]
+            .into_iter()
+            .flatten()
+            .find(|cmap| cmap.codespace_ranges().next().is_some());
+        match cmap {
+            Some(cmap) => cmap.code_length(bytes),
+            None => bytes.len().min(2),
+        }
+    }
+
+    // How far the glyph for `code` moves the text position, in text space (for a font size of 1).
+    pub(super) fn width(&self, code: &[u8]) -> f64 {
+        self.widths
+            .get(&self.width_key(code))
+            .copied()
+            .unwrap_or(self.default_width)
+            * self.scale
+    }
+
+    // Whether `width` is only a guess for `code`: its font is a standard 14 font without /Widths,
+    // and the glyph's width isn't among the metrics in `standard_fonts`.
+    pub(super) fn width_is_guess(&self, code: &[u8]) -> bool {
+        self.default_width_is_guess && !self.widths.contains_key(&self.width_key(code))
+    }
+
+    // The key in `widths` for `code`: the code itself, or for a composite font, its CID.
+    fn width_key(&self, code: &[u8]) -> u32 {
+        let value = code
+            .iter()
+            .fold(0, |value, &byte| value << 8 | u32::from(byte));
+        match &self.cmap {
+            Some(cmap) => cmap.cid(code).unwrap_or(value),
+            None => value,
+        }
+    }
+
+    // The text that `code` stands for, if that's known.
+    pub(super) fn text(&self, code: &[u8]) -> Option<String> {
+        if let Some(text) = self.to_unicode.as_ref().and_then(|cmap| cmap.unicode(code)) {
+            return Some(text.to_string());
+        }
+        let &[code] = code else {
+            return None;
+        };
+        let name = self.glyph_name(code)?;
+        if self.dingbats {
+            if let Some(text) = dingbat_text(name) {
+                return Some(text);
+            }
+        }
+        glyph_name_text(name)
+    }
+
+    // The name of the glyph for (single-byte) `code`, per the encoding.
+    fn glyph_name(&self, code: u8) -> Option<&[u8]> {
+        match self.differences.get(&code) {
+            Some(name) => Some(name),
+            None => Some(self.encoding?[usize::from(code)]?.as_bytes()),
+        }
+    }
+
+    // How far the glyphs reach above and below the baseline, in text space (for a font size of 1).
+    pub(super) fn ascent(&self) -> f64 {
+        self.ascent * self.scale
+    }
+    pub(super) fn descent(&self) -> f64 {
+        self.descent * self.scale
+    }
+}
+
+// The widths in /W, an array of `c [w1 w2 ...]` (widths for CIDs from c on) and `c_first c_last w`.
+fn cid_widths(document: &Document, w: &ArrayObject) -> BTreeMap<u32, f64> {
+    let mut widths = BTreeMap::new();
+    let mut elements = w.elements().map(|element| document.resolved(element));
+    while let Some(Some(first)) = elements.next() {
+        let Some(first) = first.as_number() else {
+            break;
+        };
+        match elements.next() {
+            Some(Some(Object::Array(array))) => {
+                for (i, width) in array.elements().enumerate() {
+                    // (Any past the largest CID there can be are skipped.)
+                    let cid = u32::try_from(i)
+                        .ok()
+                        .and_then(|i| (first as u32).checked_add(i));
+                    let width = document.resolved(width).and_then(|w| w.as_number());
+                    if let (Some(cid), Some(width)) = (cid, width) {
+                        widths.insert(cid, width);
+                    }
+                }
+            }
+            Some(Some(last)) => {
+                let (Some(last), Some(Some(width))) = (
+                    last.as_number(),
+                    elements.next().map(|w| w.and_then(|w| w.as_number())),
+                ) else {
+                    break;
+                };
+                // (Capped, lest a bad range take forever.)
+                for cid in (first as u32..=last as u32).take(0x10000) {
+                    widths.insert(cid, width);
+                }
+            }
+            _ => break,
+        }
+    }
+    widths
+}
+
+// The CMap that `value` names (if it's a predefined one that's known) or is (a stream), along
+// with the one it builds on (per /UseCMap, or `usecmap`), if that can be found. `depth` is how
+// many CMaps have been built on so far, which is limited in case of a cycle.
+fn load_cmap(document: &Document, value: &ObjectOrReference, depth: usize) -> Option<CMap> {
+    if let Some(Object::Name(name)) = document.resolved(value) {
+        return CMap::predefined(&name.decoded_bytes());
+    }
+    let (number, generation, stream) = document.referenced_stream(value)?;
+    let data = document.stream_data(number, generation, stream).ok()?;
+    let mut cmap = parse_cmap(&data).ok()?;
+    let parent = match (stream.dict().get(b"UseCMap"), &cmap.use_cmap) {
+        _ if depth >= 8 => None,
+        (Some(parent), _) => load_cmap(document, parent, depth + 1),
+        (None, Some(name)) => CMap::predefined(name),
+        (None, None) => None,
+    };
+    if let Some(parent) = parent {
+        cmap.inherit(parent);
+    }
+    Some(cmap)
+}
+
+#[test]
+fn test_composite_font() {
+    let bytes = file_with_objects(&[
+        "<< /Type /Font /Subtype /Type0 /BaseFont /Example /Encoding 2 0 R
+           /DescendantFonts [3 0 R] /ToUnicode 4 0 R >>",
+        &stream_object(
+            "/Type /CMap /CMapName /Example-H /UseCMap /Identity-H",
+            "2 begincodespacerange <00> <7F> <8000> <FFFF> endcodespacerange
+             1 begincidrange <20> <7E> 1 endcidrange",
+        ),
+        "<< /Type /Font /Subtype /CIDFontType0 /BaseFont /Example /DW 1000
+           /W [34 [600] 32833 32833 250 4294967295 [500 700]] /FontDescriptor 5 0 R >>",
+        &stream_object(
+            "",
+            "1 begincodespacerange <00> <FF> endcodespacerange
+             2 beginbfchar <41> <0041> <8041> <3042> endbfchar",
+        ),
+        "<< /Type /FontDescriptor /FontName /Example /Flags 4 /Ascent 880 /Descent -120 >>",
+    ]);
+    let doc = Document::open(&bytes).unwrap();
+    let Object::Dictionary(dict) = doc.object(1).unwrap().object() else {
+        panic!("not a dictionary");
+    };
+    let font = Font::new(&doc, dict);
+    let codes: Vec<&[u8]> = font.codes(b"A\x80\x41\x90").collect();
+    assert_eq!(codes, [&b"A"[..], b"\x80\x41", b"\x90"]);
+    // "A" is CID 34 by the CMap, and <8041> is CID 32833 by the Identity-H it builds on.
+    assert_eq!(font.width(b"A"), 0.6);
+    assert_eq!(font.width(b"\x80\x41"), 0.25);
+    assert_eq!(font.width(b"B"), 1.0);
+    // (The width for the CID after the largest there can be is skipped.)
+    assert_eq!(font.widths.get(&u32::MAX), Some(&500.0));
+    assert_eq!(font.widths.len(), 3);
+    assert_eq!(font.text(b"A").as_deref(), Some("A"));
+    assert_eq!(font.text(b"\x80\x41").as_deref(), Some("\u{3042}"));
+    assert_eq!(font.text(b"B"), None);
+    assert_eq!((font.ascent(), font.descent()), (0.88, -0.12));
+}
+
+#[test]
+fn test_builtin_encodings() {
+    let bytes = file_with_objects(&[
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Symbol >>",
+        "<< /Type /Font /Subtype /Type1 /BaseFont /ZapfDingbats >>",
+        "<< /Type /Font /Subtype /Type3 /Encoding << /Differences [97 /a12] >> >>",
+    ]);
+    let doc = Document::open(&bytes).unwrap();
+    let font = |number| {
+        let Object::Dictionary(dict) = doc.object(number).unwrap().object() else {
+            panic!("not a dictionary");
+        };
+        Font::new(&doc, dict)
+    };
+    let symbol = font(1);
+    assert_eq!(symbol.text(b"a").as_deref(), Some("\u{3B1}"));
+    assert_eq!(symbol.text(b"\xA3").as_deref(), Some("\u{2264}"));
+    assert_eq!(font(2).text(b"+").as_deref(), Some("\u{261E}"));
+    // Only ZapfDingbats' a12 is a dingbat.
+    assert_eq!(font(3).text(b"a"), None);
+}
+
+#[test]
+fn test_simple_font_widths() {
+    let bytes = file_with_objects(&[
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Example /FirstChar 32 /Widths [250 300] >>",
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Example /FirstChar 4294967295
+           /Widths [500 600] >>",
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Example /FirstChar -5 /Widths [500] >>",
+    ]);
+    let doc = Document::open(&bytes).unwrap();
+    let font = |number| {
+        let Object::Dictionary(dict) = doc.object(number).unwrap().object() else {
+            panic!("not a dictionary");
+        };
+        Font::new(&doc, dict)
+    };
+    assert_eq!(font(1).width(b"!"), 0.3);
+    // (The width for the code after the largest there can be is skipped.)
+    let large = font(2);
+    assert_eq!(large.widths.get(&u32::MAX), Some(&500.0));
+    assert_eq!(large.widths.len(), 1);
+    assert_eq!(font(3).widths.get(&0), Some(&500.0));
+}
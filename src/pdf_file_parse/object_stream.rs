@@ -0,0 +1,356 @@
+// ===================
+// 7.5.7 Object Streams
+// ===================
+// Since PDF 1.5, (non-stream) objects can be stored "compressed" inside an object stream:
+//     7 0 obj << /Type /ObjStm /N 5 /First 20 ... >> stream ... endstream endobj
+// The decoded stream data starts with N pairs of integers (object number, and byte offset
+// relative to /First), and the objects themselves start at byte /First.
+// The cross-reference stream says which object stream (and index in it) each such object is in.
+
+use super::*;
+
+/// The decoded data of an object stream, from which the objects in it can be parsed.
+pub struct ObjectStream {
+    container: u32, // The object number of the object stream itself
+    n: usize,       // From /N: the number of objects in the stream
+    first: usize,   // From /First: the offset in `data` of the first object
+    data: Vec<u8>,  // The decoded stream data
+}
+
+/// An object that was stored in an object stream.
+#[derive(Serialize, Debug)]
+pub struct CompressedObject<'a> {
+    pub container: u32,     // The object number of the object stream
+    pub index: usize,       // Its position in the object stream, as in the cross-reference stream
+    pub object_number: u32, // (The generation number is always 0.)
+    #[serde(borrow)]
+    pub object: Object<'a>,
+}
+
+// An error about the object stream as a whole, rather than at some offset in its data.
+fn object_stream_error(expected: impl Into<String>) -> ParseError {
+    ParseError {
+        offset: 0,
+        rule: "object_stream",
+        expected: expected.into(),
+    }
+}
+
+// The stream in `def`, if it is an object stream.
+fn object_stream<'d>(
+    def: &'d IndirectObjectDefinition,
+) -> Result<&'d StreamObject<'d>, ParseError> {
+    match &def.object {
+        Object::Stream(s)
+            if s.dict
+                .get(b"Type")
+                .and_then(|t| t.as_name())
+                .is_some_and(|t| t.is(b"ObjStm")) =>
+        {
+            Ok(s)
+        }
+        _ => Err(object_stream_error("a stream with /Type /ObjStm")),
+    }
+}
+
+impl ObjectStream {
+    /// Decodes the object stream defined by `def`.
+    pub fn new(def: &IndirectObjectDefinition) -> Result<ObjectStream, ParseError> {
+        let data = object_stream(def)?
+            .decoded()
+            .map_err(|e| object_stream_error(format!("decodable stream data ({})", e)))?
+            .into_owned();
+        Self::with_data(def, data)
+    }
+
+    // Like `new`, but with the stream data already decoded (and decrypted, if need be).
+    pub(super) fn with_data(
+        def: &IndirectObjectDefinition,
+        data: Vec<u8>,
+    ) -> Result<ObjectStream, ParseError> {
+        let stream = object_stream(def)?;
+        let container = def
+            .object_number
+            .value()
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or_else(|| object_stream_error("a valid object number"))?;
+        let to_usize = |o: &ObjectOrReference| o.as_integer().and_then(|i| usize::try_from(i).ok());
+        let n = stream
+            .dict
+            .get(b"N")
+            .and_then(to_usize)
+            .ok_or_else(|| object_stream_error("an /N integer"))?;
+        let first = stream
+            .dict
+            .get(b"First")
+            .and_then(to_usize)
+            .ok_or_else(|| object_stream_error("a /First integer"))?;
+        if first > data.len() {
+            return Err(object_stream_error(format!(
+                "a /First offset within the {} bytes of stream data (got {})",
+                data.len(),
+                first
+            )));
+        }
+        Ok(ObjectStream {
+            container,
+            n,
+            first,
+            data,
+        })
+    }
+
+    /// The object number of the object stream itself.
+    pub fn container(&self) -> u32 {
+        self.container
+    }
+
+    /// The decoded stream data. Offsets in errors from `objects` are offsets into this.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    // The (object number, offset in `data`) pairs from the header.
+    fn header(&self) -> Result<Vec<(u32, usize)>, ParseError> {
+        let mut input = &self.data[..self.first];
+        let mut pairs = vec![];
+        for _ in 0..self.n {
+            let (rest, (_, number, _, offset)) = tuple((
+                whitespace_and_comments,
+                integer_without_sign,
+                whitespace_and_comments,
+                integer_without_sign,
+            ))(input)
+            .map_err(|e| {
+                ParseError::from_nom(
+                    &self.data[..self.first],
+                    e.map(|e| e.within("object_stream")),
+                )
+            })?;
+            input = rest;
+            let number = number.value().and_then(|n| u32::try_from(n).ok());
+            let offset = offset
+                .value()
+                .and_then(|o| usize::try_from(o).ok())
+                .and_then(|o| o.checked_add(self.first))
+                .filter(|&o| o <= self.data.len());
+            match (number, offset) {
+                (Some(number), Some(offset)) => pairs.push((number, offset)),
+                _ => {
+                    return Err(ParseError {
+                        offset: self.first - rest.len(),
+                        rule: "object_stream",
+                        expected: "an object number and an offset within the stream".to_string(),
+                    })
+                }
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// Parses the header, then each of the objects in the stream.
+    /// (The spans of the objects are offsets into `data`.)
+    pub fn objects(&self) -> Result<Vec<CompressedObject<'_>>, ParseError> {
+        let mut objects = vec![];
+        for (index, (object_number, offset)) in self.header()?.into_iter().enumerate() {
+            let (_, (_, object)) = with_own_context(&self.data, || {
+                tuple((whitespace_and_comments, object))(&self.data[offset..])
+            })
+            .map_err(|e| ParseError::from_nom(&self.data, e))?;
+            objects.push(CompressedObject {
+                container: self.container,
+                index,
+                object_number,
+                object,
+            });
+        }
+        Ok(objects)
+    }
+}
+
+// The objects parsed from an object stream borrow from its decoded data, which is not part of the
+// file; to keep them beyond the `ObjectStream`, they need their own copies of everything.
+fn owned(bytes: Cow<'_, [u8]>) -> Cow<'static, [u8]> {
+    Cow::Owned(bytes.into_owned())
+}
+
+impl CompressedObject<'_> {
+    pub(super) fn into_owned(self) -> CompressedObject<'static> {
+        CompressedObject {
+            container: self.container,
+            index: self.index,
+            object_number: self.object_number,
+            object: self.object.into_owned(),
+        }
+    }
+}
+
+impl Object<'_> {
+    fn into_owned(self) -> Object<'static> {
+        match self {
+            Object::Boolean(b, span) => Object::Boolean(b, span),
+            Object::Numeric(n) => Object::Numeric(n.into_owned()),
+            Object::String(s) => Object::String(s.into_owned()),
+            Object::Name(n) => Object::Name(n),
+            Object::Array(a) => Object::Array(a.into_owned()),
+            Object::Dictionary(d) => Object::Dictionary(d.into_owned()),
+            Object::Stream(s) => Object::Stream(s.into_owned()),
+            Object::Null(span) => Object::Null(span),
+        }
+    }
+}
+
+impl Integer<'_> {
+    fn into_owned(self) -> Integer<'static> {
+        Integer {
+            sign: self.sign,
+            digits: owned(self.digits),
+            span: self.span,
+        }
+    }
+}
+
+impl NumericObject<'_> {
+    fn into_owned(self) -> NumericObject<'static> {
+        match self {
+            NumericObject::Integer(i) => NumericObject::Integer(i.into_owned()),
+            NumericObject::Real(r) => NumericObject::Real(Real {
+                sign: r.sign,
+                digits_before: owned(r.digits_before),
+                digits_after: owned(r.digits_after),
+                span: r.span,
+            }),
+        }
+    }
+}
+
+impl StringObject<'_> {
+    fn into_owned(self) -> StringObject<'static> {
+        match self {
+            StringObject::Literal(s) => StringObject::Literal(LiteralString {
+                parts: s
+                    .parts
+                    .into_iter()
+                    .map(|part| match part {
+                        LiteralStringPart::Regular(r) => LiteralStringPart::Regular(owned(r)),
+                        LiteralStringPart::Escaped(e) => LiteralStringPart::Escaped(owned(e)),
+                    })
+                    .collect(),
+                span: s.span,
+            }),
+            StringObject::Hex(h) => StringObject::Hex(HexadecimalString {
+                chars: owned(h.chars),
+                span: h.span,
+            }),
+        }
+    }
+}
+
+impl ObjectOrReference<'_> {
+    fn into_owned(self) -> ObjectOrReference<'static> {
+        match self {
+            ObjectOrReference::Object(o) => ObjectOrReference::Object(o.into_owned()),
+            ObjectOrReference::Reference(r) => {
+                ObjectOrReference::Reference(IndirectObjectReference {
+                    object_number: r.object_number.into_owned(),
+                    ws1: owned(r.ws1),
+                    generation_number: r.generation_number.into_owned(),
+                    ws2: owned(r.ws2),
+                    span: r.span,
+                })
+            }
+        }
+    }
+}
+
+impl ArrayObject<'_> {
+    fn into_owned(self) -> ArrayObject<'static> {
+        ArrayObject {
+            parts: self
+                .parts
+                .into_iter()
+                .map(|part| match part {
+                    ArrayObjectPart::ObjectOrRef(o) => ArrayObjectPart::ObjectOrRef(o.into_owned()),
+                    ArrayObjectPart::Whitespace(w) => ArrayObjectPart::Whitespace(owned(w)),
+                })
+                .collect(),
+            span: self.span,
+        }
+    }
+}
+
+impl DictionaryObject<'_> {
+    fn into_owned(self) -> DictionaryObject<'static> {
+        DictionaryObject {
+            parts: self
+                .parts
+                .into_iter()
+                .map(|part| match part {
+                    DictionaryPart::Whitespace(w) => DictionaryPart::Whitespace(owned(w)),
+                    DictionaryPart::KeyValuePair(kv) => {
+                        DictionaryPart::KeyValuePair(KeyValuePair {
+                            key: kv.key,
+                            ws: owned(kv.ws),
+                            value: kv.value.into_owned(),
+                            span: kv.span,
+                        })
+                    }
+                })
+                .collect(),
+            span: self.span,
+        }
+    }
+}
+
+impl StreamObject<'_> {
+    fn into_owned(self) -> StreamObject<'static> {
+        StreamObject {
+            dict: self.dict.into_owned(),
+            ws_and_comments: owned(self.ws_and_comments),
+            eol_after_stream_begin: self.eol_after_stream_begin,
+            content: owned(self.content),
+            length: self.length,
+            span: self.span,
+        }
+    }
+}
+
+#[test]
+fn test_object_stream() {
+    let bytes = include_bytes!("../test_xref_stream.pdf");
+    let (_, def) = indirect_object_definition(&bytes[107..]).unwrap();
+    let object_stream = ObjectStream::new(&def).unwrap();
+    assert_eq!(object_stream.container(), 7);
+    let objects = object_stream.objects().unwrap();
+    assert_eq!(
+        objects
+            .iter()
+            .map(|o| (o.container, o.index, o.object_number))
+            .collect::<Vec<_>>(),
+        vec![(7, 0, 1), (7, 1, 2), (7, 2, 3), (7, 3, 5), (7, 4, 6)]
+    );
+    let mut out: Vec<u8> = vec![];
+    objects[0].object.serialize_to(&mut out).unwrap();
+    assert_eq!(out, b"<< /Type /Catalog /Pages 2 0 R >>");
+    // Spans are offsets in the decoded data.
+    let span = objects[0].object.span();
+    assert_eq!(&object_stream.data()[span.start..span.end], &out[..]);
+}
+
+#[test]
+fn test_object_stream_errors() {
+    let not_object_stream =
+        b"1 0 obj\n<< /N 1 /First 4 /Length 7 >>\nstream\n1 0 42\nendstream\nendobj";
+    let (_, def) = indirect_object_definition(not_object_stream).unwrap();
+    assert_eq!(
+        ObjectStream::new(&def).err().unwrap().expected,
+        "a stream with /Type /ObjStm"
+    );
+
+    // Says there are two objects, but the header only has one pair.
+    let short_header =
+        b"1 0 obj\n<< /Type /ObjStm /N 2 /First 4 /Length 7 >>\nstream\n5 0 42\nendstream\nendobj";
+    let (_, def) = indirect_object_definition(short_header).unwrap();
+    let err = ObjectStream::new(&def).unwrap().objects().err().unwrap();
+    assert_eq!((err.offset, err.rule), (4, "object_stream"));
+}
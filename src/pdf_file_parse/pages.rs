@@ -0,0 +1,525 @@
+// ==================
+// 7.7.3 Page Tree
+// ==================
+// The pages of a document are the leaves of a tree, rooted at the catalog's /Pages. Each inner node
+// (/Type /Pages) lists its children in /Kids, and says in /Count how many pages are below it.
+// A page can leave some of its attributes (/Resources, /MediaBox, /CropBox and /Rotate) to be
+// inherited from the nearest ancestor that has them. Nothing but care keeps the tree a tree: a
+// /Kids entry can point back up, or to a node that is already elsewhere in the tree.
+
+use super::*;
+use std::collections::BTreeSet;
+
+/// A page: a leaf of the page tree, with its inheritable attributes looked up in its ancestors.
+pub struct Page<'d, 'a> {
+    document: &'d Document<'a>,
+    /// The object number of the page object.
+    pub number: u32,
+    /// The generation number of the page object.
+    pub generation: u16,
+    /// The page object.
+    pub dict: &'d DictionaryObject<'a>,
+    /// /Resources, from the page or else its nearest ancestor that has it.
+    pub resources: Option<&'d DictionaryObject<'a>>,
+    /// /MediaBox (inherited likewise), as `[llx lly urx ury]`.
+    pub media_box: Option<[f64; 4]>,
+    /// /CropBox (inherited likewise), or else the media box: "the default value is the page's media box".
+    pub crop_box: Option<[f64; 4]>,
+    /// /Rotate (inherited likewise), in degrees clockwise; 0 if none.
+    pub rotate: i64,
+}
+
+impl std::fmt::Debug for Page<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Page")
+            .field("number", &self.number)
+            .field("generation", &self.generation)
+            .field("dict", &self.dict)
+            .field("resources", &self.resources)
+            .field("media_box", &self.media_box)
+            .field("crop_box", &self.crop_box)
+            .field("rotate", &self.rotate)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'d, 'a> Page<'d, 'a> {
+    /// The document the page is in.
+    pub fn document(&self) -> &'d Document<'a> {
+        self.document
+    }
+
+    /// The page's content stream: the decoded data of /Contents, or (if it's an array) of each of
+    /// its streams, joined by newlines. Empty if there is no /Contents.
+    pub fn contents(&self) -> Result<Vec<u8>, DecodeError> {
+        let streams: Vec<_> = match self.dict.get(b"Contents") {
+            None => vec![],
+            Some(contents) => match self.document.resolved(contents) {
+                Some(Object::Array(array)) => array.elements().collect(),
+                _ => vec![contents],
+            },
+        };
+        let mut data = vec![];
+        for (i, stream) in streams.into_iter().enumerate() {
+            let Some((number, generation, stream)) = self.document.referenced_stream(stream) else {
+                continue;
+            };
+            if i > 0 {
+                data.push(b'\n');
+            }
+            data.extend_from_slice(&self.document.stream_data(number, generation, stream)?);
+        }
+        Ok(data)
+    }
+}
+
+/// Something wrong with the page tree. Such a node is skipped, and the walk goes on.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum PageTreeProblem {
+    /// There is no page tree to walk: the trailer's /Root, or the catalog's /Pages, is not (a
+    /// reference to) a dictionary.
+    Root { problem: String },
+    /// Entry `index` of the /Kids of node `parent` is not a reference to a dictionary (or
+    /// /Kids is not an array, if `index` is `None`).
+    Kids { parent: u32, index: Option<usize> },
+    /// Entry `index` of the /Kids of node `parent` refers to `kid`, which is `parent` itself or
+    /// one of its ancestors.
+    Cycle { parent: u32, index: usize, kid: u32 },
+    /// Entry `index` of the /Kids of node `parent` refers to `kid`, which is already elsewhere in
+    /// the tree.
+    Duplicate { parent: u32, index: usize, kid: u32 },
+    /// Entry `index` of the /Kids of node `parent` refers to `kid`, which is deeper in the tree
+    /// than `MAX_TREE_DEPTH`, so it (and what's below it) is left out.
+    Depth { parent: u32, index: usize, kid: u32 },
+    /// The /Count of node `number` is `count` (`None` if it's missing, or not an integer), but
+    /// there are `actual` pages below it.
+    Count {
+        number: u32,
+        count: Option<i64>,
+        actual: usize,
+    },
+}
+
+impl std::fmt::Display for PageTreeProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PageTreeProblem::Root { problem } => write!(f, "no page tree: {}", problem),
+            PageTreeProblem::Kids {
+                parent,
+                index: None,
+            } => write!(f, "node {}: /Kids is not an array", parent),
+            PageTreeProblem::Kids {
+                parent,
+                index: Some(index),
+            } => write!(
+                f,
+                "node {}: /Kids entry {} is not a reference to a dictionary",
+                parent, index
+            ),
+            PageTreeProblem::Cycle { parent, index, kid } => write!(
+                f,
+                "node {}: /Kids entry {} refers to {}, making a cycle",
+                parent, index, kid
+            ),
+            PageTreeProblem::Duplicate { parent, index, kid } => write!(
+                f,
+                "node {}: /Kids entry {} refers to {}, which is already in the tree",
+                parent, index, kid
+            ),
+            PageTreeProblem::Depth { parent, index, kid } => write!(
+                f,
+                "node {}: /Kids entry {} refers to {}, which is more than {} levels deep",
+                parent, index, kid, MAX_TREE_DEPTH
+            ),
+            PageTreeProblem::Count {
+                number,
+                count,
+                actual,
+            } => {
+                match count {
+                    Some(count) => write!(f, "node {}: /Count is {}", number, count)?,
+                    None => write!(f, "node {}: /Count is missing", number)?,
+                }
+                write!(f, ", but it has {} pages", actual)
+            }
+        }
+    }
+}
+
+/// The pages of a document, in order, and what was wrong with the tree they were found in.
+#[derive(Debug)]
+pub struct PageTree<'d, 'a> {
+    pub pages: Vec<Page<'d, 'a>>,
+    pub problems: Vec<PageTreeProblem>,
+}
+
+// The inheritable attributes, from the nearest node (so far) that has each.
+#[derive(Clone, Copy, Default)]
+struct Inherited<'d, 'a> {
+    resources: Option<&'d DictionaryObject<'a>>,
+    media_box: Option<[f64; 4]>,
+    crop_box: Option<[f64; 4]>,
+    rotate: Option<i64>,
+}
+
+// How deeply the page tree's nodes may be nested. (Real trees are a few levels deep, but one
+// made of a long chain of nodes could otherwise overflow the stack.)
+const MAX_TREE_DEPTH: usize = 256;
+
+// The state of a walk through the page tree.
+struct Walk<'d, 'a> {
+    document: &'d Document<'a>,
+    tree: PageTree<'d, 'a>,
+    // The nodes seen so far, and those on the path from the root to the current one.
+    seen: BTreeSet<u32>,
+    ancestors: Vec<u32>,
+}
+
+impl<'a> Document<'a> {
+    /// The pages, in order: the leaves of the page tree, from the trailer's /Root.
+    /// Any problems with the tree are skipped over; see `page_tree`.
+    pub fn pages(&self) -> impl Iterator<Item = Page<'_, 'a>> {
+        self.page_tree().pages.into_iter()
+    }
+
+    /// Walks the page tree, from the trailer's /Root: the pages, in order, and any problems.
+    pub fn page_tree(&self) -> PageTree<'_, 'a> {
+        let mut walk = Walk {
+            document: self,
+            tree: PageTree {
+                pages: vec![],
+                problems: vec![],
+            },
+            seen: BTreeSet::new(),
+            ancestors: vec![],
+        };
+        match self.page_tree_root() {
+            Ok((number, generation, dict)) => {
+                walk.seen.insert(number);
+                walk.node(number, generation, dict, Inherited::default());
+            }
+            Err(problem) => walk.tree.problems.push(PageTreeProblem::Root { problem }),
+        }
+        walk.tree
+    }
+
+    // The root of the page tree: the node that the catalog's /Pages refers to.
+    fn page_tree_root(&self) -> Result<(u32, u16, &DictionaryObject<'a>), String> {
+        let catalog = match self.trailer().get(b"Root").map(|root| self.resolved(root)) {
+            None => return Err("the trailer has no /Root".to_string()),
+            Some(Some(Object::Dictionary(catalog))) => catalog,
+            Some(_) => return Err("/Root is not a dictionary".to_string()),
+        };
+        let pages = match catalog.get(b"Pages") {
+            None => return Err("the catalog has no /Pages".to_string()),
+            Some(ObjectOrReference::Reference(pages)) => pages,
+            Some(ObjectOrReference::Object(_)) => {
+                return Err("/Pages is not a reference".to_string())
+            }
+        };
+        match (pages.id(), self.resolve(pages)) {
+            (Some((number, generation)), Some(Object::Dictionary(dict))) => {
+                Ok((number, generation, dict))
+            }
+            _ => Err("/Pages is not a dictionary".to_string()),
+        }
+    }
+
+    // The object that `value` is, or refers to.
+    pub(super) fn resolved<'d>(
+        &'d self,
+        value: &'d ObjectOrReference<'a>,
+    ) -> Option<&'d Object<'a>> {
+        match value {
+            ObjectOrReference::Object(object) => Some(object),
+            ObjectOrReference::Reference(reference) => self.resolve(reference),
+        }
+    }
+
+    // The stream that `value` refers to, with its object number and generation number.
+    pub(super) fn referenced_stream<'d>(
+        &'d self,
+        value: &ObjectOrReference<'a>,
+    ) -> Option<(u32, u16, &'d StreamObject<'a>)> {
+        let ObjectOrReference::Reference(reference) = value else {
+            return None;
+        };
+        match (reference.id(), self.resolve(reference)) {
+            (Some((number, generation)), Some(Object::Stream(stream))) => {
+                Some((number, generation, stream))
+            }
+            _ => None,
+        }
+    }
+
+    // The rectangle that `value` is (or refers to): an array of 4 numbers.
+    pub(super) fn rectangle(&self, value: &ObjectOrReference<'a>) -> Option<[f64; 4]> {
+        let Some(Object::Array(array)) = self.resolved(value) else {
+            return None;
+        };
+        let numbers: Vec<f64> = array
+            .elements()
+            .map(|element| self.resolved(element)?.as_number())
+            .collect::<Option<_>>()?;
+        numbers.try_into().ok()
+    }
+}
+
+impl<'d, 'a> Walk<'d, 'a> {
+    // Visits node `number` (whose dictionary is `dict`), adding the pages below it, and returns
+    // how many there are.
+    fn node(
+        &mut self,
+        number: u32,
+        generation: u16,
+        dict: &'d DictionaryObject<'a>,
+        inherited: Inherited<'d, 'a>,
+    ) -> usize {
+        let document = self.document;
+        let inherited = Inherited {
+            resources: match dict.get(b"Resources").map(|r| document.resolved(r)) {
+                Some(Some(Object::Dictionary(resources))) => Some(resources),
+                _ => inherited.resources,
+            },
+            media_box: dict
+                .get(b"MediaBox")
+                .and_then(|r| document.rectangle(r))
+                .or(inherited.media_box),
+            crop_box: dict
+                .get(b"CropBox")
+                .and_then(|r| document.rectangle(r))
+                .or(inherited.crop_box),
+            rotate: match dict.get(b"Rotate").map(|r| document.resolved(r)) {
+                Some(Some(Object::Numeric(NumericObject::Integer(i)))) => i.value(),
+                _ => None,
+            }
+            .or(inherited.rotate),
+        };
+
+        // (A node without /Type is taken to be a page, unless it has /Kids.)
+        let is_page = match dict.get(b"Type").and_then(|t| t.as_name()) {
+            Some(name) => name.is(b"Page"),
+            None => dict.get(b"Kids").is_none(),
+        };
+        if is_page {
+            self.tree.pages.push(Page {
+                document,
+                number,
+                generation,
+                dict,
+                resources: inherited.resources,
+                media_box: inherited.media_box,
+                crop_box: inherited.crop_box.or(inherited.media_box),
+                rotate: inherited.rotate.unwrap_or(0),
+            });
+            return 1;
+        }
+
+        let mut actual = 0;
+        let kids = match dict.get(b"Kids").map(|kids| document.resolved(kids)) {
+            Some(Some(Object::Array(kids))) => kids,
+            _ => {
+                self.problem(PageTreeProblem::Kids {
+                    parent: number,
+                    index: None,
+                });
+                return 0;
+            }
+        };
+        self.ancestors.push(number);
+        for (index, kid) in kids.elements().enumerate() {
+            let bad_kid = PageTreeProblem::Kids {
+                parent: number,
+                index: Some(index),
+            };
+            let ObjectOrReference::Reference(reference) = kid else {
+                self.problem(bad_kid);
+                continue;
+            };
+            let (Some((kid, kid_generation)), Some(Object::Dictionary(kid_dict))) =
+                (reference.id(), document.resolve(reference))
+            else {
+                self.problem(bad_kid);
+                continue;
+            };
+            if self.ancestors.contains(&kid) {
+                self.problem(PageTreeProblem::Cycle {
+                    parent: number,
+                    index,
+                    kid,
+                });
+            } else if self.ancestors.len() >= MAX_TREE_DEPTH {
+                self.problem(PageTreeProblem::Depth {
+                    parent: number,
+                    index,
+                    kid,
+                });
+            } else if !self.seen.insert(kid) {
+                self.problem(PageTreeProblem::Duplicate {
+                    parent: number,
+                    index,
+                    kid,
+                });
+            } else {
+                actual += self.node(kid, kid_generation, kid_dict, inherited);
+            }
+        }
+        self.ancestors.pop();
+
+        let count = match dict.get(b"Count").map(|c| document.resolved(c)) {
+            Some(Some(Object::Numeric(NumericObject::Integer(i)))) => i.value(),
+            _ => None,
+        };
+        if count != Some(actual as i64) {
+            self.problem(PageTreeProblem::Count {
+                number,
+                count,
+                actual,
+            });
+        }
+        actual
+    }
+
+    fn problem(&mut self, problem: PageTreeProblem) {
+        self.tree.problems.push(problem);
+    }
+}
+
+#[cfg(test)]
+// A file with these objects (numbered from 1), a cross-reference table, and a trailer whose
+// /Root is object 1.
+pub(super) fn file_with_objects(objects: &[&str]) -> Vec<u8> {
+    let mut bytes = b"%PDF-1.4\n".to_vec();
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(bytes.len());
+        bytes.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).into_bytes());
+    }
+    let xref = bytes.len();
+    bytes.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+    for offset in offsets {
+        bytes.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+    }
+    bytes.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .into_bytes(),
+    );
+    bytes
+}
+
+#[test]
+fn test_pages() {
+    let bytes = include_bytes!("../test_simple.pdf");
+    let doc = Document::open(bytes).unwrap();
+    let tree = doc.page_tree();
+    assert_eq!(tree.problems, vec![]);
+    assert_eq!(tree.pages.len(), 1);
+    let page = &tree.pages[0];
+    assert_eq!((page.number, page.generation), (3, 0));
+    assert_eq!(page.media_box, Some([0.0, 0.0, 612.0, 792.0]));
+    assert_eq!(page.crop_box, page.media_box);
+    assert_eq!(page.rotate, 0);
+    assert!(page.resources.unwrap().get(b"Font").is_some());
+
+    // The page tree is in an object stream.
+    let bytes = include_bytes!("../test_xref_stream.pdf");
+    let doc = Document::open(bytes).unwrap();
+    let numbers: Vec<u32> = doc.pages().map(|page| page.number).collect();
+    assert_eq!(numbers, [3]);
+}
+
+#[test]
+fn test_pages_inherited() {
+    let bytes = file_with_objects(&[
+        "<< /Type /Catalog /Pages 2 0 R >>",
+        "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 3 /MediaBox [0 0 612 792] /Rotate 90 /Resources 7 0 R >>",
+        "<< /Type /Page /Parent 2 0 R /CropBox [10 10 600.5 780] >>",
+        "<< /Type /Pages /Parent 2 0 R /Kids [5 0 R 6 0 R] /Count 2 /Rotate 180 /MediaBox 8 0 R >>",
+        "<< /Type /Page /Parent 4 0 R /Resources << /Font << >> >> >>",
+        "<< /Type /Page /Parent 4 0 R /Rotate 0 >>",
+        "<< /ProcSet [/PDF] >>",
+        "[0 0 300 400]",
+    ]);
+    let doc = Document::open(&bytes).unwrap();
+    let tree = doc.page_tree();
+    assert_eq!(tree.problems, vec![]);
+    let pages: Vec<_> = tree
+        .pages
+        .iter()
+        .map(|page| {
+            let resources = page.resources.unwrap();
+            let own = resources.get(b"Font").is_some();
+            (page.number, page.media_box, page.crop_box, page.rotate, own)
+        })
+        .collect();
+    let letter = Some([0.0, 0.0, 612.0, 792.0]);
+    let small = Some([0.0, 0.0, 300.0, 400.0]);
+    assert_eq!(
+        pages,
+        [
+            (3, letter, Some([10.0, 10.0, 600.5, 780.0]), 90, false),
+            (5, small, small, 180, true),
+            (6, small, small, 0, false),
+        ]
+    );
+}
+
+#[test]
+fn test_page_tree_problems() {
+    let bytes = file_with_objects(&[
+        "<< /Type /Catalog /Pages 2 0 R >>",
+        "<< /Type /Pages /Kids [3 0 R 4 0 R 3 0 R 5 0 R] /Count 5 >>",
+        "<< /Type /Page /Parent 2 0 R >>",
+        "<< /Type /Pages /Parent 2 0 R /Kids [2 0 R 6 0 R 7 0 R] >>",
+        "(not a dictionary)",
+        "<< /Type /Page /Parent 4 0 R >>",
+        "<< /Type /Pages /Parent 4 0 R /Kids [4 0 R] /Count 0 >>",
+    ]);
+    let doc = Document::open(&bytes).unwrap();
+    let tree = doc.page_tree();
+    let numbers: Vec<u32> = tree.pages.iter().map(|page| page.number).collect();
+    assert_eq!(numbers, [3, 6]);
+    let problems: Vec<String> = tree.problems.iter().map(|p| p.to_string()).collect();
+    assert_eq!(
+        problems,
+        [
+            "node 4: /Kids entry 0 refers to 2, making a cycle",
+            "node 7: /Kids entry 0 refers to 4, making a cycle",
+            "node 4: /Count is missing, but it has 1 pages",
+            "node 2: /Kids entry 2 refers to 3, which is already in the tree",
+            "node 2: /Kids entry 3 is not a reference to a dictionary",
+            "node 2: /Count is 5, but it has 2 pages",
+        ]
+    );
+
+    // A chain of nodes, each the only kid of the one before it, with a page at the end.
+    let mut objects = vec!["<< /Type /Catalog /Pages 2 0 R >>".to_string()];
+    for number in 2..MAX_TREE_DEPTH as u32 + 3 {
+        objects.push(format!(
+            "<< /Type /Pages /Kids [{} 0 R] /Count 1 >>",
+            number + 1
+        ));
+    }
+    objects.push("<< /Type /Page >>".to_string());
+    let bytes = file_with_objects(&objects.iter().map(|o| o.as_str()).collect::<Vec<_>>());
+    let doc = Document::open(&bytes).unwrap();
+    let tree = doc.page_tree();
+    assert_eq!(tree.pages.len(), 0);
+    assert_eq!(
+        tree.problems[0].to_string(),
+        "node 257: /Kids entry 0 refers to 258, which is more than 256 levels deep"
+    );
+
+    let bytes = file_with_objects(&["<< /Type /Catalog >>"]);
+    let doc = Document::open(&bytes).unwrap();
+    assert_eq!(
+        doc.page_tree().problems,
+        vec![PageTreeProblem::Root {
+            problem: "the catalog has no /Pages".to_string()
+        }]
+    );
+}
@@ -0,0 +1,522 @@
+// ==========================================
+// Repair: rebuilding the cross-reference table
+// ==========================================
+// When the cross-reference table is broken (wrong offsets) or missing, a reader that starts from
+// `startxref` cannot find anything. But the objects themselves are usually all still there, and we
+// can find them the way `pdf_file` does: by walking the body forwards. `repair` does that, skipping
+// anything that isn't an object definition (old tables, trailers, junk), and writes the objects out
+// again with a new cross-reference table, trailer and `startxref` that say where they now are.
+
+use super::object_stream::ObjectStream;
+use super::xref_stream::is_cross_reference_stream;
+use super::*;
+
+// What a forward scan of a (possibly damaged) file finds.
+struct Scan<'a> {
+    header: &'a [u8],
+    // In the order they are in the file (so a later definition of an object replaces an earlier one).
+    definitions: Vec<IndirectObjectDefinition<'a>>,
+    // Trailer dictionaries, and the dictionaries of cross-reference streams, in the order they are in the file.
+    trailers: Vec<DictionaryObject<'a>>,
+}
+
+// Where to try again after failing to parse anything at the start of `input`: the next number (which
+// could start an object definition), or the next "xref" or "trailer".
+fn skip_junk(input: &[u8]) -> &[u8] {
+    let next = (1..input.len()).find(|&i| {
+        (input[i].is_ascii_digit() && !input[i - 1].is_ascii_digit())
+            || input[i..].starts_with(b"xref")
+            || input[i..].starts_with(b"trailer")
+    });
+    &input[next.unwrap_or(input.len())..]
+}
+
+fn scan(whole: &[u8]) -> Scan<'_> {
+    let (mut input, header) = match whitespace_and_comments(whole) {
+        Ok(parsed) => parsed,
+        Err(_) => (whole, &whole[..0]),
+    };
+    let mut definitions = vec![];
+    let mut trailers = vec![];
+    while !input.is_empty() {
+        match body_part(input) {
+            Ok((rest, BodyPart::ObjDef(def))) => {
+                input = rest;
+                match def.object {
+                    Object::Stream(s) if is_cross_reference_stream(&def) => trailers.push(s.dict),
+                    _ => definitions.push(def),
+                }
+                continue;
+            }
+            Ok((rest, BodyPart::Whitespace(ws))) if !ws.is_empty() => {
+                input = rest;
+                continue;
+            }
+            _ => {}
+        }
+        if input.starts_with(b"xref") {
+            if let Ok((rest, t)) = cross_reference_table_and_trailer(input) {
+                trailers.push(t.trailer.dict);
+                input = rest;
+                continue;
+            }
+        }
+        if input.starts_with(b"trailer") {
+            if let Ok((rest, t)) = trailer(input) {
+                trailers.push(t.dict);
+                input = rest;
+                continue;
+            }
+        }
+        // A damaged table, a `startxref`, a malformed object definition, or just junk.
+        input = skip_junk(input);
+    }
+    Scan {
+        header,
+        definitions,
+        trailers,
+    }
+}
+
+// The values of the objects that are integers, for looking up indirect stream lengths.
+fn integer_values(definitions: &[IndirectObjectDefinition]) -> BTreeMap<(u32, u16), usize> {
+    definitions
+        .iter()
+        .filter_map(|def| {
+            let id = def.id()?;
+            match &def.object {
+                Object::Numeric(NumericObject::Integer(i)) => {
+                    Some((id, usize::try_from(i.value()?).ok()?))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn integer(n: impl ToString) -> Integer<'static> {
+    Integer {
+        sign: Sign::None,
+        digits: Cow::Owned(n.to_string().into_bytes()),
+        span: Span::default(),
+    }
+}
+
+fn name(key: &[u8]) -> NameObject {
+    NameObject {
+        chars: key.iter().map(|&c| NameObjectChar::Regular(c)).collect(),
+        span: Span::default(),
+    }
+}
+
+fn reference(number: u32, generation: u16) -> ObjectOrReference<'static> {
+    ObjectOrReference::Reference(IndirectObjectReference {
+        object_number: integer(number),
+        ws1: Cow::Borrowed(b" "),
+        generation_number: integer(generation),
+        ws2: Cow::Borrowed(b" "),
+        span: Span::default(),
+    })
+}
+
+fn key_value_pair<'a>(key: &[u8], value: ObjectOrReference<'a>) -> DictionaryPart<'a> {
+    DictionaryPart::KeyValuePair(KeyValuePair {
+        key: name(key),
+        ws: Cow::Borrowed(b" "),
+        value,
+        span: Span::default(),
+    })
+}
+
+impl<'a> DictionaryObject<'a> {
+    // Removes the (first) entry for `key`, returning its value.
+    fn remove(&mut self, key: &[u8]) -> Option<ObjectOrReference<'a>> {
+        let i = self
+            .parts
+            .iter()
+            .position(|part| matches!(part, DictionaryPart::KeyValuePair(kv) if kv.key.is(key)))?;
+        match self.parts.remove(i) {
+            DictionaryPart::KeyValuePair(kv) => Some(kv.value),
+            DictionaryPart::Whitespace(_) => unreachable!("Already checked it's a key-value pair"),
+        }
+    }
+
+    // Replaces the value of the (first) entry for `key`, or else adds one at the end.
+    fn set(&mut self, key: &[u8], value: ObjectOrReference<'a>) {
+        for part in &mut self.parts {
+            if let DictionaryPart::KeyValuePair(kv) = part {
+                if kv.key.is(key) {
+                    kv.value = value;
+                    return;
+                }
+            }
+        }
+        self.parts.push(key_value_pair(key, value));
+        self.parts
+            .push(DictionaryPart::Whitespace(Cow::Borrowed(b" ")));
+    }
+}
+
+// Whether the object is a dictionary with /Type /Catalog.
+fn is_catalog(def: &IndirectObjectDefinition) -> bool {
+    match &def.object {
+        Object::Dictionary(d) => d
+            .get(b"Type")
+            .and_then(|t| t.as_name())
+            .is_some_and(|t| t.is(b"Catalog")),
+        _ => false,
+    }
+}
+
+// The object definitions to write out, replacing each object stream by the objects in it (as a
+// table can't point into object streams), and with the later definition of any object that's
+// defined more than once. In the order of the (last) definitions in the file.
+fn objects(definitions: Vec<IndirectObjectDefinition<'_>>) -> Vec<IndirectObjectDefinition<'_>> {
+    let mut latest: BTreeMap<u32, (usize, IndirectObjectDefinition)> = BTreeMap::new();
+    for (position, def) in definitions.into_iter().enumerate() {
+        let Some((number, _)) = def.id() else {
+            continue;
+        };
+        let compressed = ObjectStream::new(&def).and_then(|s| {
+            Ok(s.objects()?
+                .into_iter()
+                .map(|o| o.into_owned())
+                .collect::<Vec<_>>())
+        });
+        match compressed {
+            // (An object stream that can't be read, e.g. because the file is encrypted, is kept as it is.)
+            Ok(compressed) => {
+                for o in compressed {
+                    let def = IndirectObjectDefinition {
+                        object_number: integer(o.object_number),
+                        ws1: Cow::Borrowed(b" "),
+                        generation_number: integer(0),
+                        ws2: Cow::Borrowed(b" "),
+                        ws3: Cow::Borrowed(b"\n"),
+                        object: o.object,
+                        ws4: Cow::Borrowed(b"\n"),
+                        missing_endobj: false,
+                        span: Span::default(),
+                    };
+                    latest.insert(o.object_number, (position, def));
+                }
+            }
+            Err(_) => {
+                latest.insert(number, (position, def));
+            }
+        }
+    }
+    let mut objects: Vec<_> = latest.into_values().collect();
+    objects.sort_by_key(|&(position, _)| position);
+    objects.into_iter().map(|(_, def)| def).collect()
+}
+
+// Fixes what the (lenient) scan accepted in the definition: a missing "endobj", "obj" glued to the
+// numbers, and a lone CR after "stream". Also, if a stream's /Length was missing or wrong, sets it
+// to the length of the data.
+fn fix(def: &mut IndirectObjectDefinition) {
+    def.missing_endobj = false;
+    if def.ws2.is_empty() {
+        def.ws2 = Cow::Borrowed(b" ");
+    }
+    if def.ws3.is_empty() && matches!(def.object, Object::Numeric(_)) {
+        def.ws3 = Cow::Borrowed(b"\n");
+    }
+    if let Object::Stream(s) = &mut def.object {
+        if matches!(s.eol_after_stream_begin, EolMarker::CR) {
+            s.eol_after_stream_begin = EolMarker::LF;
+        }
+        if s.length == StreamLength::Scanned {
+            let length = integer(s.data().len());
+            s.dict.set(
+                b"Length",
+                ObjectOrReference::Object(Object::Numeric(NumericObject::Integer(length))),
+            );
+        }
+    }
+}
+
+fn repair_error(expected: &str) -> ParseError {
+    ParseError {
+        offset: 0,
+        rule: "repair",
+        expected: expected.to_string(),
+    }
+}
+
+// The largest object number that's kept: the spec's limit on the number of objects (Annex C).
+// A larger one is likely from junk, and would need a table with too many entries.
+const MAX_OBJECT_NUMBER: u32 = 8_388_607;
+
+fn xref_entry(
+    offset: usize,
+    generation: u16,
+    in_use: bool,
+) -> Result<CrossReferenceEntry, ParseError> {
+    let nnnnnnnnnn = format!("{:010}", offset).into_bytes();
+    Ok(CrossReferenceEntry {
+        nnnnnnnnnn: nnnnnnnnnn
+            .try_into()
+            .map_err(|_| repair_error("offsets of at most 10 digits"))?,
+        ggggg: format!("{:05}", generation)
+            .into_bytes()
+            .try_into()
+            .unwrap(),
+        n_or_f: if in_use {
+            CrossReferenceEntryInUse::InUse
+        } else {
+            CrossReferenceEntryInUse::Free
+        },
+        eol: b" \n".to_vec(),
+        span: Span::default(),
+    })
+}
+
+// A file with all the objects found in `input`, and a cross-reference table and trailer for them.
+fn repaired(input: &[u8]) -> Result<PdfFile<'_>, ParseError> {
+    // Streams with an indirect /Length can only be parsed once we know the lengths; the first scan
+    // finds those (and gets the streams wrong only if their data contains "endstream").
+    let lenient = ParseOptions {
+        strict: false,
+        ..ParseOptions::default()
+    };
+    let context = ParseContext {
+        input_len: Some(input.len()),
+        ..ParseContext::new(&lenient)
+    };
+    let (first, _) = with_context(context, || scan(input));
+    let context = ParseContext {
+        input_len: Some(input.len()),
+        indirect_lengths: integer_values(&first.definitions),
+        ..ParseContext::new(&lenient)
+    };
+    let (
+        Scan {
+            header,
+            definitions,
+            mut trailers,
+        },
+        _,
+    ) = with_context(context, || scan(input));
+    let header: Cow<[u8]> = if header.starts_with(b"%PDF-") {
+        Cow::Borrowed(header)
+    } else {
+        Cow::Borrowed(b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n")
+    };
+
+    let mut objects = objects(definitions);
+    if objects.is_empty() {
+        return Err(repair_error("at least one object definition"));
+    }
+    objects.iter_mut().for_each(fix);
+
+    // The catalog that the (latest) trailer points to, if it has one, else the last one.
+    let catalogs: Vec<(u32, u16)> = objects
+        .iter()
+        .filter(|def| is_catalog(def))
+        .filter_map(|def| def.id())
+        .collect();
+    let trailer_root = trailers
+        .iter()
+        .rev()
+        .find_map(|t| match t.get(b"Root") {
+            Some(ObjectOrReference::Reference(r)) => r.id(),
+            _ => None,
+        })
+        .filter(|root| catalogs.contains(root));
+    let (root_number, root_generation) = trailer_root
+        .or(catalogs.last().copied())
+        .ok_or_else(|| repair_error("an object with /Type /Catalog"))?;
+
+    let mut offset = header.len();
+    let mut body = vec![];
+    let mut in_use: BTreeMap<u32, (usize, u16)> = BTreeMap::new();
+    for def in objects {
+        if let Some((number, generation)) = def.id() {
+            if number > MAX_OBJECT_NUMBER {
+                continue;
+            }
+            in_use.insert(number, (offset, generation));
+        }
+        offset += serialized_len(&def) + 1;
+        body.push(BodyPart::ObjDef(def));
+        body.push(BodyPart::Whitespace(Cow::Borrowed(b"\n")));
+    }
+    let xref_offset = offset;
+
+    // Object 0, and any numbers not in use, are free: each links to the next one, and the last to 0.
+    // (Built backwards, so that the next free number is known.)
+    let size = in_use.keys().last().map_or(1, |n| n + 1);
+    let mut entries = vec![];
+    let mut next_free = 0;
+    for number in (0..size).rev() {
+        entries.push(match in_use.get(&number) {
+            Some(&(offset, generation)) => xref_entry(offset, generation, true)?,
+            None => {
+                let generation = if number == 0 { 65535 } else { 0 };
+                let entry = xref_entry(next_free as usize, generation, false)?;
+                next_free = number;
+                entry
+            }
+        });
+    }
+    entries.reverse();
+
+    let mut dict_parts = vec![];
+    let mut add = |key: &[u8], value| {
+        dict_parts.push(DictionaryPart::Whitespace(Cow::Borrowed(b" ")));
+        dict_parts.push(key_value_pair(key, value));
+    };
+    add(
+        b"Size",
+        ObjectOrReference::Object(Object::Numeric(NumericObject::Integer(integer(size)))),
+    );
+    add(b"Root", reference(root_number, root_generation));
+    // Other entries that are about the whole file, from the latest trailer that has them.
+    for key in [&b"Info"[..], b"ID", b"Encrypt"] {
+        if let Some(value) = trailers.iter_mut().rev().find_map(|t| t.remove(key)) {
+            add(key, value);
+        }
+    }
+    dict_parts.push(DictionaryPart::Whitespace(Cow::Borrowed(b" ")));
+
+    let cross_reference_table_and_trailer = CrossReferenceTableAndTrailer {
+        cross_reference_table: CrossReferenceTable {
+            ws1: Cow::Borrowed(b"\n"),
+            subsections: vec![CrossReferenceSubsection {
+                first_object_number: integer(0),
+                number_of_entries: integer(size),
+                ws: Cow::Borrowed(b"\n"),
+                entries,
+                span: Span::default(),
+            }],
+            ws2: Cow::Borrowed(b""),
+            span: Span::default(),
+        },
+        trailer: Trailer {
+            ws1: Cow::Borrowed(b"\n"),
+            dict: DictionaryObject {
+                parts: dict_parts,
+                span: Span::default(),
+            },
+            ws2: Cow::Borrowed(b"\n"),
+            span: Span::default(),
+        },
+        span: Span::default(),
+    };
+    Ok(PdfFile {
+        before_header: Cow::Borrowed(b""),
+        header,
+        body_crossref_trailers: vec![BodyCrossrefTrailer {
+            body,
+            cross_reference_table_and_trailer: Some(cross_reference_table_and_trailer),
+            startxref_offset_eof: StartxrefOffsetEof {
+                ws3: Cow::Borrowed(b"\n"),
+                last_crossref_offset: integer(xref_offset),
+                eol_marker: Cow::Borrowed(b"\n"),
+                span: Span::default(),
+            },
+            cross_reference_stream: None,
+            span: Span::default(),
+        }],
+        post_eof: Cow::Borrowed(b"\n"),
+        warnings: vec![],
+        parsed: false,
+    })
+}
+
+/// Rebuilds a file whose cross-reference table (or stream) is broken or missing: finds every object
+/// definition by scanning the file forwards, and writes them out (each object in an object stream
+/// as an ordinary object, and a stream with a missing or wrong `/Length` with the right one), then
+/// a new cross-reference table, and a trailer whose `/Root` is the object with `/Type /Catalog`.
+/// If an object is defined more than once (as in incremental updates), the last definition is kept.
+/// (Objects that an update deleted come back, as deletions are only recorded in the table.)
+/// The scan is not strict (see `ParseOptions`), and what it accepts is written out as the spec says.
+pub fn repair(input: &[u8]) -> Result<Vec<u8>, ParseError> {
+    let file = repaired(input)?;
+    let mut buf: Vec<u8> = vec![];
+    file.serialize_to(&mut buf)
+        .expect("Writing a file with one section to memory should not fail");
+    Ok(buf)
+}
+
+#[test]
+fn test_repair() {
+    let bytes = include_bytes!("../test_simple.pdf");
+    // An intact file (written the usual way) comes back unchanged.
+    assert_eq!(repair(bytes).unwrap(), bytes);
+
+    // Offsets that are all a little wrong (which `Document::open` gets past), and a damaged table
+    // (which it doesn't).
+    let shifted = replace_first(bytes, b"%PDF-1.4\n", b"%PDF-1.4\n\n\n\n");
+    assert!(Document::open(&shifted).unwrap().object_errors().is_empty());
+    let damaged = replace_first(&shifted, b"0000000247 00000 n \n", b"0000000247 0 n\n");
+    assert!(Document::open(&damaged).is_err());
+    let repaired = repair(&damaged).unwrap();
+    assert_eq!(parse_pdf(&repaired).unwrap().validate_xref(), vec![]);
+    let document = Document::open(&repaired).unwrap();
+    assert!(document.trailer().get(b"Info").is_some());
+    assert_eq!(stream_data_len(&document, 4), 43);
+}
+
+#[test]
+fn test_repair_large_object_numbers() {
+    let bytes = include_bytes!("../test_simple.pdf");
+    let end = bytes.windows(4).position(|w| w == b"xref").unwrap();
+    // An object number that's too large for a table is dropped; a large one that isn't is kept.
+    for (number, kept) in [(4294967295u32, false), (100000, true)] {
+        let mut damaged = bytes[..end].to_vec();
+        damaged.extend_from_slice(format!("{} 0 obj\n(junk)\nendobj\n", number).as_bytes());
+        let repaired = repair(&damaged).unwrap();
+        let document = Document::open(&repaired).unwrap();
+        assert_eq!(document.object(number).is_some(), kept);
+        assert!(document.object(4).is_some());
+    }
+}
+
+#[cfg(test)]
+fn stream_data_len(document: &Document, number: u32) -> usize {
+    match &document.object(number).unwrap().object {
+        Object::Stream(s) => s.data().len(),
+        o => panic!("not a stream: {:?}", o),
+    }
+}
+
+#[test]
+fn test_repair_without_xref() {
+    let bytes = include_bytes!("../test_simple.pdf");
+    // No table, trailer or startxref at all; a stream whose /Length is wrong; junk in between; and
+    // things that only a lenient parse accepts.
+    let end = bytes.windows(4).position(|w| w == b"xref").unwrap();
+    let damaged = replace_first(&bytes[..end], b"/Length 43", b"/Length 40");
+    let damaged = replace_first(&damaged, b"endobj\n5 0 obj", b"endobj\n(junk) 5 0 obj");
+    let damaged = replace_first(
+        &damaged,
+        b"/Helvetica >>\nendobj\n6 0 obj",
+        b"/Helvetica >>\n6 0obj",
+    );
+    let repaired = repair(&damaged).unwrap();
+    let fixed = b"/Helvetica >>\nendobj\n6 0 obj";
+    assert!(repaired.windows(fixed.len()).any(|w| w == fixed));
+    let file = parse_pdf(&repaired).unwrap();
+    assert_eq!(file.validate_xref(), vec![]);
+    assert!(repaired.ends_with(b"trailer\n<< /Size 7 /Root 1 0 R >>\nstartxref\n468\n%%EOF\n"));
+    // (Without a right /Length, the EOL before "endstream" is not part of the data.)
+    assert!(repaired.windows(10).any(|w| w == b"/Length 42"));
+    let document = Document::open(&repaired).unwrap();
+    assert_eq!(stream_data_len(&document, 4), 42);
+    assert!(document.object(5).is_some());
+}
+
+#[test]
+fn test_repair_object_streams() {
+    let bytes = include_bytes!("../test_xref_stream.pdf");
+    let repaired = repair(bytes).unwrap();
+    let file = parse_pdf(&repaired).unwrap();
+    assert_eq!(file.validate_xref(), vec![]);
+    // The objects from the object stream are now ordinary objects, and there's no cross-reference stream.
+    let document = Document::open(&repaired).unwrap();
+    let numbers: Vec<u32> = document.objects().map(|(n, _)| n).collect();
+    assert_eq!(numbers, vec![1, 2, 3, 4, 5, 6]);
+    assert!(is_catalog(document.object(1).unwrap()));
+}
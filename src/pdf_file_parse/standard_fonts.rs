@@ -0,0 +1,358 @@
+// ================================
+// 9.6.2.2 Standard Type 1 Fonts
+// ================================
+// Every reader knows 14 fonts by name, and before PDF 1.5 a font dictionary for one of them may
+// leave out /Widths and /FontDescriptor, so the metrics have to come from elsewhere: these are
+// from Adobe's font metrics (AFM) files. Only the widths of the glyphs that text is mostly made
+// of are here: the printable ASCII ones, and a few more. (In these fonts, a letter with an
+// accent is as wide as the letter, but for i, whose accented forms are as wide as dotlessi.)
+// Courier's glyphs are all 600 wide. Symbol's widths are by code, since it has its own
+// encoding, and are only for codes 32 to 126; ZapfDingbats' are left out.
+// So for the other glyphs (like quotesinglbase, guillemotleft, section, copyright or degree), the
+// width is a guess, and `PositionedText::estimated` says so.
+
+/// The metrics of one of the standard 14 fonts.
+pub(super) struct StandardFont {
+    pub(super) name: &'static str,
+    // The heights of the ascenders and descenders, in glyph space.
+    pub(super) ascent: f64,
+    pub(super) descent: f64,
+    widths: StandardWidths,
+}
+
+enum StandardWidths {
+    Fixed(u16),
+    ByName(&'static [(&'static str, u16)]),
+    ByCode(&'static [u16; 95]), // For codes 32 to 126
+    Unknown,
+}
+
+impl StandardFont {
+    /// The width (in glyph space) of the glyph for `code`, which is named `name` by the font's
+    /// encoding.
+    pub(super) fn width(&self, code: u8, name: Option<&[u8]>) -> Option<f64> {
+        let width = match self.widths {
+            StandardWidths::Fixed(width) => Some(width),
+            StandardWidths::ByCode(widths) => {
+                widths.get(usize::from(code.checked_sub(32)?)).copied()
+            }
+            StandardWidths::ByName(widths) => {
+                let name = name?;
+                let find = |name: &[u8]| {
+                    let i = widths
+                        .binary_search_by(|(n, _)| n.as_bytes().cmp(name))
+                        .ok()?;
+                    Some(widths[i].1)
+                };
+                find(name).or_else(|| {
+                    let letter = ACCENTS
+                        .iter()
+                        .find_map(|accent| name.strip_suffix(accent.as_bytes()))
+                        .filter(|letter| letter.len() == 1)?;
+                    find(if letter == b"i" { b"dotlessi" } else { letter })
+                })
+            }
+            StandardWidths::Unknown => None,
+        };
+        width.map(f64::from)
+    }
+}
+
+const ACCENTS: [&str; 8] = [
+    "acute",
+    "grave",
+    "circumflex",
+    "dieresis",
+    "tilde",
+    "ring",
+    "cedilla",
+    "caron",
+];
+
+/// The standard font called `base_font` (or one of the usual other names for it, like Arial).
+pub(super) fn standard_font(base_font: &[u8]) -> Option<&'static StandardFont> {
+    let name = ALIASES
+        .iter()
+        .find(|(alias, _)| alias.as_bytes() == base_font)
+        .map_or(base_font, |(_, name)| name.as_bytes());
+    STANDARD_FONTS
+        .iter()
+        .find(|font| font.name.as_bytes() == name)
+}
+
+// Other names that the standard fonts go by.
+const ALIASES: [(&str, &str); 16] = [
+    ("Arial", "Helvetica"),
+    ("ArialMT", "Helvetica"),
+    ("Arial,Bold", "Helvetica-Bold"),
+    ("Arial-BoldMT", "Helvetica-Bold"),
+    ("Arial,Italic", "Helvetica-Oblique"),
+    ("Arial-ItalicMT", "Helvetica-Oblique"),
+    ("Arial,BoldItalic", "Helvetica-BoldOblique"),
+    ("Arial-BoldItalicMT", "Helvetica-BoldOblique"),
+    ("TimesNewRoman", "Times-Roman"),
+    ("TimesNewRomanPSMT", "Times-Roman"),
+    ("TimesNewRoman,Bold", "Times-Bold"),
+    ("TimesNewRoman,Italic", "Times-Italic"),
+    ("TimesNewRoman,BoldItalic", "Times-BoldItalic"),
+    ("CourierNew", "Courier"),
+    ("CourierNewPSMT", "Courier"),
+    ("Symbol,Regular", "Symbol"),
+];
+
+const STANDARD_FONTS: [StandardFont; 14] = [
+    StandardFont {
+        name: "Helvetica",
+        ascent: 718.0,
+        descent: -207.0,
+        widths: StandardWidths::ByName(&HELVETICA_WIDTHS),
+    },
+    StandardFont {
+        name: "Helvetica-Bold",
+        ascent: 718.0,
+        descent: -207.0,
+        widths: StandardWidths::ByName(&HELVETICA_BOLD_WIDTHS),
+    },
+    StandardFont {
+        name: "Helvetica-Oblique",
+        ascent: 718.0,
+        descent: -207.0,
+        widths: StandardWidths::ByName(&HELVETICA_WIDTHS),
+    },
+    StandardFont {
+        name: "Helvetica-BoldOblique",
+        ascent: 718.0,
+        descent: -207.0,
+        widths: StandardWidths::ByName(&HELVETICA_BOLD_WIDTHS),
+    },
+    StandardFont {
+        name: "Times-Roman",
+        ascent: 683.0,
+        descent: -217.0,
+        widths: StandardWidths::ByName(&TIMES_ROMAN_WIDTHS),
+    },
+    StandardFont {
+        name: "Times-Bold",
+        ascent: 676.0,
+        descent: -205.0,
+        widths: StandardWidths::ByName(&TIMES_BOLD_WIDTHS),
+    },
+    StandardFont {
+        name: "Times-Italic",
+        ascent: 683.0,
+        descent: -205.0,
+        widths: StandardWidths::ByName(&TIMES_ITALIC_WIDTHS),
+    },
+    StandardFont {
+        name: "Times-BoldItalic",
+        ascent: 683.0,
+        descent: -205.0,
+        widths: StandardWidths::ByName(&TIMES_BOLD_ITALIC_WIDTHS),
+    },
+    StandardFont {
+        name: "Courier",
+        ascent: 629.0,
+        descent: -157.0,
+        widths: StandardWidths::Fixed(600),
+    },
+    StandardFont {
+        name: "Courier-Bold",
+        ascent: 629.0,
+        descent: -157.0,
+        widths: StandardWidths::Fixed(600),
+    },
+    StandardFont {
+        name: "Courier-Oblique",
+        ascent: 629.0,
+        descent: -157.0,
+        widths: StandardWidths::Fixed(600),
+    },
+    StandardFont {
+        name: "Courier-BoldOblique",
+        ascent: 629.0,
+        descent: -157.0,
+        widths: StandardWidths::Fixed(600),
+    },
+    StandardFont {
+        name: "Symbol",
+        ascent: 1010.0,
+        descent: -293.0,
+        widths: StandardWidths::ByCode(&SYMBOL_WIDTHS),
+    },
+    StandardFont {
+        name: "ZapfDingbats",
+        ascent: 820.0,
+        descent: -143.0,
+        widths: StandardWidths::Unknown,
+    },
+];
+
+// Helvetica (and Helvetica-Oblique).
+#[rustfmt::skip]
+const HELVETICA_WIDTHS: [(&str, u16); 106] = [
+    ("A", 667), ("B", 667), ("C", 722), ("D", 722), ("E", 667), ("F", 611), ("G", 778), ("H", 722),
+    ("I", 278), ("J", 500), ("K", 667), ("L", 556), ("M", 833), ("N", 722), ("O", 778), ("P", 667),
+    ("Q", 778), ("R", 722), ("S", 667), ("T", 611), ("U", 722), ("V", 667), ("W", 944), ("X", 667),
+    ("Y", 667), ("Z", 611), ("a", 556), ("ampersand", 667), ("asciicircum", 469),
+    ("asciitilde", 584), ("asterisk", 389), ("at", 1015), ("b", 556), ("backslash", 278),
+    ("bar", 260), ("braceleft", 334), ("braceright", 334), ("bracketleft", 278),
+    ("bracketright", 278), ("bullet", 350), ("c", 500), ("colon", 278), ("comma", 278), ("d", 556),
+    ("dollar", 556), ("dotlessi", 278), ("e", 556), ("eight", 556), ("ellipsis", 1000),
+    ("emdash", 1000), ("endash", 556), ("equal", 584), ("exclam", 278), ("f", 278), ("fi", 500),
+    ("five", 556), ("fl", 500), ("four", 556), ("g", 556), ("grave", 333), ("greater", 584),
+    ("h", 556), ("hyphen", 333), ("i", 222), ("j", 222), ("k", 500), ("l", 222), ("less", 584),
+    ("m", 833), ("n", 556), ("nine", 556), ("numbersign", 556), ("o", 556), ("one", 556),
+    ("p", 556), ("parenleft", 333), ("parenright", 333), ("percent", 889), ("period", 278),
+    ("plus", 584), ("q", 556), ("question", 556), ("quotedbl", 355), ("quotedblleft", 333),
+    ("quotedblright", 333), ("quoteleft", 222), ("quoteright", 222), ("quotesingle", 191),
+    ("r", 333), ("s", 500), ("semicolon", 278), ("seven", 556), ("six", 556), ("slash", 278),
+    ("space", 278), ("t", 278), ("three", 556), ("two", 556), ("u", 556), ("underscore", 556),
+    ("v", 500), ("w", 722), ("x", 500), ("y", 500), ("z", 500), ("zero", 556),
+];
+// Helvetica-Bold (and Helvetica-BoldOblique).
+#[rustfmt::skip]
+const HELVETICA_BOLD_WIDTHS: [(&str, u16); 106] = [
+    ("A", 722), ("B", 722), ("C", 722), ("D", 722), ("E", 667), ("F", 611), ("G", 778), ("H", 722),
+    ("I", 278), ("J", 556), ("K", 722), ("L", 611), ("M", 833), ("N", 722), ("O", 778), ("P", 667),
+    ("Q", 778), ("R", 722), ("S", 667), ("T", 611), ("U", 722), ("V", 667), ("W", 944), ("X", 667),
+    ("Y", 667), ("Z", 611), ("a", 556), ("ampersand", 722), ("asciicircum", 584),
+    ("asciitilde", 584), ("asterisk", 389), ("at", 975), ("b", 611), ("backslash", 278),
+    ("bar", 280), ("braceleft", 389), ("braceright", 389), ("bracketleft", 333),
+    ("bracketright", 333), ("bullet", 350), ("c", 556), ("colon", 333), ("comma", 278), ("d", 611),
+    ("dollar", 556), ("dotlessi", 278), ("e", 556), ("eight", 556), ("ellipsis", 1000),
+    ("emdash", 1000), ("endash", 556), ("equal", 584), ("exclam", 333), ("f", 333), ("fi", 611),
+    ("five", 556), ("fl", 611), ("four", 556), ("g", 611), ("grave", 333), ("greater", 584),
+    ("h", 611), ("hyphen", 333), ("i", 278), ("j", 278), ("k", 556), ("l", 278), ("less", 584),
+    ("m", 889), ("n", 611), ("nine", 556), ("numbersign", 556), ("o", 611), ("one", 556),
+    ("p", 611), ("parenleft", 333), ("parenright", 333), ("percent", 889), ("period", 278),
+    ("plus", 584), ("q", 611), ("question", 611), ("quotedbl", 474), ("quotedblleft", 500),
+    ("quotedblright", 500), ("quoteleft", 278), ("quoteright", 278), ("quotesingle", 238),
+    ("r", 389), ("s", 556), ("semicolon", 333), ("seven", 556), ("six", 556), ("slash", 278),
+    ("space", 278), ("t", 333), ("three", 556), ("two", 556), ("u", 611), ("underscore", 556),
+    ("v", 556), ("w", 778), ("x", 556), ("y", 556), ("z", 500), ("zero", 556),
+];
+// Times-Roman.
+#[rustfmt::skip]
+const TIMES_ROMAN_WIDTHS: [(&str, u16); 106] = [
+    ("A", 722), ("B", 667), ("C", 667), ("D", 722), ("E", 611), ("F", 556), ("G", 722), ("H", 722),
+    ("I", 333), ("J", 389), ("K", 722), ("L", 611), ("M", 889), ("N", 722), ("O", 722), ("P", 556),
+    ("Q", 722), ("R", 667), ("S", 556), ("T", 611), ("U", 722), ("V", 722), ("W", 944), ("X", 722),
+    ("Y", 722), ("Z", 611), ("a", 444), ("ampersand", 778), ("asciicircum", 469),
+    ("asciitilde", 541), ("asterisk", 500), ("at", 921), ("b", 500), ("backslash", 278),
+    ("bar", 200), ("braceleft", 480), ("braceright", 480), ("bracketleft", 333),
+    ("bracketright", 333), ("bullet", 350), ("c", 444), ("colon", 278), ("comma", 250), ("d", 500),
+    ("dollar", 500), ("dotlessi", 278), ("e", 444), ("eight", 500), ("ellipsis", 1000),
+    ("emdash", 1000), ("endash", 500), ("equal", 564), ("exclam", 333), ("f", 333), ("fi", 556),
+    ("five", 500), ("fl", 556), ("four", 500), ("g", 500), ("grave", 333), ("greater", 564),
+    ("h", 500), ("hyphen", 333), ("i", 278), ("j", 278), ("k", 500), ("l", 278), ("less", 564),
+    ("m", 778), ("n", 500), ("nine", 500), ("numbersign", 500), ("o", 500), ("one", 500),
+    ("p", 500), ("parenleft", 333), ("parenright", 333), ("percent", 833), ("period", 250),
+    ("plus", 564), ("q", 500), ("question", 444), ("quotedbl", 408), ("quotedblleft", 444),
+    ("quotedblright", 444), ("quoteleft", 333), ("quoteright", 333), ("quotesingle", 180),
+    ("r", 333), ("s", 389), ("semicolon", 278), ("seven", 500), ("six", 500), ("slash", 278),
+    ("space", 250), ("t", 278), ("three", 500), ("two", 500), ("u", 500), ("underscore", 500),
+    ("v", 500), ("w", 722), ("x", 500), ("y", 500), ("z", 444), ("zero", 500),
+];
+// Times-Bold.
+#[rustfmt::skip]
+const TIMES_BOLD_WIDTHS: [(&str, u16); 106] = [
+    ("A", 722), ("B", 667), ("C", 722), ("D", 722), ("E", 667), ("F", 611), ("G", 778), ("H", 778),
+    ("I", 389), ("J", 500), ("K", 778), ("L", 667), ("M", 944), ("N", 722), ("O", 778), ("P", 611),
+    ("Q", 778), ("R", 722), ("S", 556), ("T", 667), ("U", 722), ("V", 722), ("W", 1000), ("X", 722),
+    ("Y", 722), ("Z", 667), ("a", 500), ("ampersand", 833), ("asciicircum", 581),
+    ("asciitilde", 520), ("asterisk", 500), ("at", 930), ("b", 556), ("backslash", 278),
+    ("bar", 220), ("braceleft", 394), ("braceright", 394), ("bracketleft", 333),
+    ("bracketright", 333), ("bullet", 350), ("c", 444), ("colon", 333), ("comma", 250), ("d", 556),
+    ("dollar", 500), ("dotlessi", 278), ("e", 444), ("eight", 500), ("ellipsis", 1000),
+    ("emdash", 1000), ("endash", 500), ("equal", 570), ("exclam", 333), ("f", 333), ("fi", 556),
+    ("five", 500), ("fl", 556), ("four", 500), ("g", 500), ("grave", 333), ("greater", 570),
+    ("h", 556), ("hyphen", 333), ("i", 278), ("j", 333), ("k", 556), ("l", 278), ("less", 570),
+    ("m", 833), ("n", 556), ("nine", 500), ("numbersign", 500), ("o", 500), ("one", 500),
+    ("p", 556), ("parenleft", 333), ("parenright", 333), ("percent", 1000), ("period", 250),
+    ("plus", 570), ("q", 556), ("question", 500), ("quotedbl", 555), ("quotedblleft", 500),
+    ("quotedblright", 500), ("quoteleft", 333), ("quoteright", 333), ("quotesingle", 278),
+    ("r", 444), ("s", 389), ("semicolon", 333), ("seven", 500), ("six", 500), ("slash", 278),
+    ("space", 250), ("t", 333), ("three", 500), ("two", 500), ("u", 556), ("underscore", 500),
+    ("v", 500), ("w", 722), ("x", 500), ("y", 500), ("z", 444), ("zero", 500),
+];
+// Times-Italic.
+#[rustfmt::skip]
+const TIMES_ITALIC_WIDTHS: [(&str, u16); 106] = [
+    ("A", 611), ("B", 611), ("C", 667), ("D", 722), ("E", 611), ("F", 611), ("G", 722), ("H", 722),
+    ("I", 333), ("J", 444), ("K", 667), ("L", 556), ("M", 833), ("N", 667), ("O", 722), ("P", 611),
+    ("Q", 722), ("R", 611), ("S", 500), ("T", 556), ("U", 722), ("V", 611), ("W", 833), ("X", 611),
+    ("Y", 556), ("Z", 556), ("a", 500), ("ampersand", 778), ("asciicircum", 422),
+    ("asciitilde", 541), ("asterisk", 500), ("at", 920), ("b", 500), ("backslash", 278),
+    ("bar", 275), ("braceleft", 400), ("braceright", 400), ("bracketleft", 389),
+    ("bracketright", 389), ("bullet", 350), ("c", 444), ("colon", 333), ("comma", 250), ("d", 500),
+    ("dollar", 500), ("dotlessi", 278), ("e", 444), ("eight", 500), ("ellipsis", 889),
+    ("emdash", 889), ("endash", 500), ("equal", 675), ("exclam", 333), ("f", 278), ("fi", 500),
+    ("five", 500), ("fl", 500), ("four", 500), ("g", 500), ("grave", 333), ("greater", 675),
+    ("h", 500), ("hyphen", 333), ("i", 278), ("j", 278), ("k", 444), ("l", 278), ("less", 675),
+    ("m", 722), ("n", 500), ("nine", 500), ("numbersign", 500), ("o", 500), ("one", 500),
+    ("p", 500), ("parenleft", 333), ("parenright", 333), ("percent", 833), ("period", 250),
+    ("plus", 675), ("q", 500), ("question", 500), ("quotedbl", 420), ("quotedblleft", 556),
+    ("quotedblright", 556), ("quoteleft", 333), ("quoteright", 333), ("quotesingle", 214),
+    ("r", 389), ("s", 389), ("semicolon", 333), ("seven", 500), ("six", 500), ("slash", 278),
+    ("space", 250), ("t", 278), ("three", 500), ("two", 500), ("u", 500), ("underscore", 500),
+    ("v", 444), ("w", 667), ("x", 444), ("y", 444), ("z", 389), ("zero", 500),
+];
+// Times-BoldItalic.
+#[rustfmt::skip]
+const TIMES_BOLD_ITALIC_WIDTHS: [(&str, u16); 106] = [
+    ("A", 667), ("B", 667), ("C", 667), ("D", 722), ("E", 667), ("F", 667), ("G", 722), ("H", 778),
+    ("I", 389), ("J", 500), ("K", 667), ("L", 611), ("M", 889), ("N", 722), ("O", 722), ("P", 611),
+    ("Q", 722), ("R", 667), ("S", 556), ("T", 611), ("U", 722), ("V", 667), ("W", 889), ("X", 667),
+    ("Y", 611), ("Z", 611), ("a", 500), ("ampersand", 778), ("asciicircum", 570),
+    ("asciitilde", 570), ("asterisk", 500), ("at", 832), ("b", 500), ("backslash", 278),
+    ("bar", 220), ("braceleft", 348), ("braceright", 348), ("bracketleft", 333),
+    ("bracketright", 333), ("bullet", 350), ("c", 444), ("colon", 333), ("comma", 250), ("d", 500),
+    ("dollar", 500), ("dotlessi", 278), ("e", 444), ("eight", 500), ("ellipsis", 1000),
+    ("emdash", 1000), ("endash", 500), ("equal", 570), ("exclam", 389), ("f", 333), ("fi", 556),
+    ("five", 500), ("fl", 556), ("four", 500), ("g", 500), ("grave", 333), ("greater", 570),
+    ("h", 556), ("hyphen", 333), ("i", 278), ("j", 278), ("k", 500), ("l", 278), ("less", 570),
+    ("m", 778), ("n", 556), ("nine", 500), ("numbersign", 500), ("o", 500), ("one", 500),
+    ("p", 500), ("parenleft", 333), ("parenright", 333), ("percent", 833), ("period", 250),
+    ("plus", 570), ("q", 500), ("question", 500), ("quotedbl", 555), ("quotedblleft", 500),
+    ("quotedblright", 500), ("quoteleft", 333), ("quoteright", 333), ("quotesingle", 278),
+    ("r", 389), ("s", 389), ("semicolon", 333), ("seven", 500), ("six", 500), ("slash", 278),
+    ("space", 250), ("t", 278), ("three", 500), ("two", 500), ("u", 556), ("underscore", 500),
+    ("v", 444), ("w", 667), ("x", 500), ("y", 444), ("z", 389), ("zero", 500),
+];
+// Symbol, for codes 32 to 126 of its built-in encoding.
+#[rustfmt::skip]
+const SYMBOL_WIDTHS: [u16; 95] = [
+    250, 333, 713, 500, 549, 833, 778, 439, 333, 333, 500, 549, 250, 549, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 278, 278, 549, 549, 549, 444,
+    549, 722, 667, 722, 612, 611, 763, 603, 722, 333, 631, 722, 686, 889, 722, 722,
+    768, 741, 556, 592, 611, 690, 439, 768, 645, 795, 611, 333, 863, 333, 658, 500,
+    500, 631, 549, 549, 494, 439, 521, 411, 603, 329, 603, 549, 549, 576, 521, 549,
+    549, 521, 549, 603, 439, 576, 713, 686, 493, 686, 494, 480, 200, 480, 549,
+];
+
+#[test]
+fn test_standard_font() {
+    let helvetica = standard_font(b"Helvetica").unwrap();
+    assert_eq!(helvetica.width(b'W', Some(b"W")), Some(944.0));
+    assert_eq!(helvetica.width(0xE9, Some(b"eacute")), Some(556.0));
+    assert_eq!(helvetica.width(0xEF, Some(b"idieresis")), Some(278.0));
+    assert_eq!(helvetica.width(0xA4, Some(b"currency")), None);
+    assert_eq!(helvetica.width(b'x', None), None);
+    let arial = standard_font(b"Arial,Bold").unwrap();
+    assert_eq!(
+        (arial.name, arial.width(b'i', Some(b"i"))),
+        ("Helvetica-Bold", Some(278.0))
+    );
+    assert_eq!(
+        standard_font(b"Courier-Oblique").unwrap().width(0xE9, None),
+        Some(600.0)
+    );
+    let symbol = standard_font(b"Symbol").unwrap();
+    assert_eq!(symbol.width(b'a', Some(b"a")), Some(631.0));
+    assert_eq!(symbol.width(0x1F, None), None);
+    assert_eq!(
+        standard_font(b"ZapfDingbats").unwrap().width(b'a', None),
+        None
+    );
+    assert!(standard_font(b"Frutiger").is_none());
+}
@@ -0,0 +1,651 @@
+// =========================
+// 9.4 Text Objects
+// =========================
+// Text is drawn by the operators of a content stream, so extracting it means running them:
+// keeping track of the graphics state (saved by `q` and restored by `Q`, with `cm` changing the
+// current transformation matrix), the text state (`Tf` sets the font and size; `Tc`, `Tw`, `Tz`,
+// `TL` and `Ts` the spacing, scaling, leading and rise), and the text matrix (reset by `BT`, and
+// moved by `Td`, `TD`, `Tm` and `T*`). Each glyph shown by `Tj`, `TJ`, `'` and `"` is then put
+// where the text matrix says, and the text matrix moves past it. Form XObjects (`Do`) are run
+// in turn, as part of the page.
+// Nothing says which glyphs make a line or a word: that is guessed from where they are. A glyph
+// that is off the baseline of the one before it starts a new line, and one that is further
+// along the baseline than a fraction of the font size starts a new word.
+
+use super::fonts::Font;
+#[cfg(test)]
+use super::pages::file_with_objects;
+use super::*;
+use std::rc::Rc;
+
+/// Why the text of a page could not be extracted.
+#[derive(Debug)]
+pub enum TextError {
+    /// A content stream (the page's, or a form XObject's) could not be decoded.
+    Decode(DecodeError),
+    /// A content stream could not be parsed.
+    Parse(ParseError),
+}
+
+impl std::fmt::Display for TextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextError::Decode(e) => write!(f, "can't decode a content stream: {}", e),
+            TextError::Parse(e) => write!(f, "can't parse a content stream: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TextError {}
+
+// A transformation [a b c d e f], which maps (x, y) to (ax + cy + e, bx + dy + f).
+type Matrix = [f64; 6];
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+// The transformation `m` followed by `n`.
+fn concat(m: &Matrix, n: &Matrix) -> Matrix {
+    [
+        m[0] * n[0] + m[1] * n[2],
+        m[0] * n[1] + m[1] * n[3],
+        m[2] * n[0] + m[3] * n[2],
+        m[2] * n[1] + m[3] * n[3],
+        m[4] * n[0] + m[5] * n[2] + n[4],
+        m[4] * n[1] + m[5] * n[3] + n[5],
+    ]
+}
+
+fn apply(m: &Matrix, (x, y): (f64, f64)) -> (f64, f64) {
+    (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5])
+}
+
+/// A glyph on a page, or a word (a run of glyphs with no gap between them), and where it is.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PositionedText {
+    pub text: String,
+    /// The font's /BaseFont (that of the first glyph, for a word).
+    pub font: String,
+    /// The font size, in default user space units.
+    pub size: f64,
+    /// The bounding box, in default user space, as `[llx lly urx ury]`: from the glyphs' widths
+    /// along the baseline, and the font's ascent and descent across it.
+    pub bbox: [f64; 4],
+    /// Whether the width of the box is an estimate, as the width of a glyph isn't known: it's in
+    /// one of the standard 14 fonts without /Widths, and isn't one of the glyphs whose widths are
+    /// built in (the printable ASCII ones and a few more; none for ZapfDingbats).
+    pub estimated: bool,
+}
+
+// A glyph shown on the page, in default user space.
+pub(super) struct Glyph {
+    pub(super) text: String,
+    pub(super) font: Rc<Font>,
+    // Where it starts and ends on the baseline (the end being where the next glyph would start,
+    // but for character and word spacing).
+    pub(super) origin: (f64, f64),
+    pub(super) end: (f64, f64),
+    // The direction of the baseline (as a unit vector), and the font size.
+    pub(super) direction: (f64, f64),
+    pub(super) size: f64,
+    pub(super) bbox: [f64; 4],
+    pub(super) estimated: bool,
+}
+
+impl Glyph {
+    fn positioned(&self) -> PositionedText {
+        PositionedText {
+            text: self.text.clone(),
+            font: self.font.name.clone(),
+            size: self.size,
+            bbox: self.bbox,
+            estimated: self.estimated,
+        }
+    }
+}
+
+// The parts of the graphics state that matter for text.
+#[derive(Clone)]
+struct GraphicsState {
+    ctm: Matrix,
+    font: Option<Rc<Font>>,
+    font_size: f64,
+    char_spacing: f64,
+    word_spacing: f64,
+    horizontal_scaling: f64,
+    leading: f64,
+    rise: f64,
+}
+
+// How deeply form XObjects may be nested.
+const MAX_FORM_DEPTH: usize = 12;
+
+// The state of running a page's content streams.
+struct Interpreter<'d, 'a> {
+    document: &'d Document<'a>,
+    state: GraphicsState,
+    saved: Vec<GraphicsState>,
+    text_matrix: Matrix,
+    line_matrix: Matrix,
+    // The fonts loaded so far, by the object number and generation number of their font
+    // dictionary (one given directly in a resource dictionary is loaded each time instead).
+    fonts: BTreeMap<(u32, u16), Rc<Font>>,
+    // The object numbers of the form XObjects being run, innermost last.
+    forms: Vec<u32>,
+    glyphs: Vec<Glyph>,
+}
+
+impl Page<'_, '_> {
+    /// The text on the page, in the order it is drawn, with a newline wherever a glyph is not on
+    /// the same line as the one before, and a space wherever there is a gap between them.
+    pub fn extract_text(&self) -> Result<String, TextError> {
+        Ok(layout(&self.glyphs()?))
+    }
+
+    /// The glyphs on the page, in the order they are drawn, with where each is.
+    pub fn positioned_glyphs(&self) -> Result<Vec<PositionedText>, TextError> {
+        Ok(self.glyphs()?.iter().map(Glyph::positioned).collect())
+    }
+
+    /// The words on the page, in the order they are drawn, with where each is: the runs of
+    /// glyphs that `extract_text` puts no space or newline between (leaving out white space).
+    pub fn positioned_words(&self) -> Result<Vec<PositionedText>, TextError> {
+        Ok(words(&self.glyphs()?))
+    }
+
+    // The glyphs on the page, in the order they are drawn.
+    pub(super) fn glyphs(&self) -> Result<Vec<Glyph>, TextError> {
+        let mut interpreter = Interpreter {
+            document: self.document(),
+            state: GraphicsState {
+                ctm: IDENTITY,
+                font: None,
+                font_size: 0.0,
+                char_spacing: 0.0,
+                word_spacing: 0.0,
+                horizontal_scaling: 1.0,
+                leading: 0.0,
+                rise: 0.0,
+            },
+            saved: vec![],
+            text_matrix: IDENTITY,
+            line_matrix: IDENTITY,
+            fonts: BTreeMap::new(),
+            forms: vec![],
+            glyphs: vec![],
+        };
+        let contents = self.contents().map_err(TextError::Decode)?;
+        interpreter.run(&contents, self.resources)?;
+        Ok(interpreter.glyphs)
+    }
+}
+
+impl<'d, 'a> Interpreter<'d, 'a> {
+    // Runs content stream `data`, with `resources` for the fonts and XObjects it names.
+    fn run(
+        &mut self,
+        data: &[u8],
+        resources: Option<&'d DictionaryObject<'a>>,
+    ) -> Result<(), TextError> {
+        let content = parse_content_stream(data).map_err(TextError::Parse)?;
+        for operation in content.operations() {
+            let operands: Vec<&Object> = operation.operands().collect();
+            self.operation(operation.operator(), &operands, resources)?;
+        }
+        Ok(())
+    }
+
+    // Does an operation. One whose operands are not what they should be is ignored.
+    fn operation(
+        &mut self,
+        operator: &[u8],
+        operands: &[&Object],
+        resources: Option<&'d DictionaryObject<'a>>,
+    ) -> Result<(), TextError> {
+        let numbers: Vec<f64> = operands.iter().filter_map(|o| o.as_number()).collect();
+        let number = numbers.first().copied();
+        let string = |i: usize| match operands.get(i) {
+            Some(Object::String(s)) => Some(s.decoded_bytes()),
+            _ => None,
+        };
+        match operator {
+            b"q" => self.saved.push(self.state.clone()),
+            b"Q" => {
+                if let Some(state) = self.saved.pop() {
+                    self.state = state;
+                }
+            }
+            b"cm" => {
+                if let Ok(m) = <Matrix>::try_from(&numbers[..]) {
+                    self.state.ctm = concat(&m, &self.state.ctm);
+                }
+            }
+            b"BT" => {
+                self.text_matrix = IDENTITY;
+                self.line_matrix = IDENTITY;
+            }
+            b"Tc" => self.state.char_spacing = number.unwrap_or(0.0),
+            b"Tw" => self.state.word_spacing = number.unwrap_or(0.0),
+            b"Tz" => self.state.horizontal_scaling = number.unwrap_or(100.0) / 100.0,
+            b"TL" => self.state.leading = number.unwrap_or(0.0),
+            b"Ts" => self.state.rise = number.unwrap_or(0.0),
+            b"Tf" => {
+                if let (Some(Object::Name(name)), Some(size)) = (operands.first(), number) {
+                    self.state.font = self.font(resources, &name.decoded_bytes());
+                    self.state.font_size = size;
+                }
+            }
+            b"Td" | b"TD" => {
+                if let [tx, ty] = numbers[..] {
+                    if operator == b"TD" {
+                        self.state.leading = -ty;
+                    }
+                    self.next_line(tx, ty);
+                }
+            }
+            b"Tm" => {
+                if let Ok(m) = <Matrix>::try_from(&numbers[..]) {
+                    self.text_matrix = m;
+                    self.line_matrix = m;
+                }
+            }
+            b"T*" => self.next_line(0.0, -self.state.leading),
+            b"Tj" => {
+                if let Some(s) = string(0) {
+                    self.show(&s);
+                }
+            }
+            b"'" => {
+                if let Some(s) = string(0) {
+                    self.next_line(0.0, -self.state.leading);
+                    self.show(&s);
+                }
+            }
+            b"\"" => {
+                if let (&[aw, ac], Some(s)) = (&numbers[..], string(2)) {
+                    self.state.word_spacing = aw;
+                    self.state.char_spacing = ac;
+                    self.next_line(0.0, -self.state.leading);
+                    self.show(&s);
+                }
+            }
+            b"TJ" => {
+                if let Some(Object::Array(array)) = operands.first() {
+                    for element in array.elements() {
+                        match element {
+                            ObjectOrReference::Object(Object::String(s)) => {
+                                self.show(&s.decoded_bytes())
+                            }
+                            ObjectOrReference::Object(object) => {
+                                if let Some(adjustment) = object.as_number() {
+                                    let state = &self.state;
+                                    let tx = -adjustment / 1000.0
+                                        * state.font_size
+                                        * state.horizontal_scaling;
+                                    self.text_matrix =
+                                        concat(&[1.0, 0.0, 0.0, 1.0, tx, 0.0], &self.text_matrix);
+                                }
+                            }
+                            ObjectOrReference::Reference(_) => {}
+                        }
+                    }
+                }
+            }
+            b"Do" => {
+                if let Some(Object::Name(name)) = operands.first() {
+                    self.form(resources, &name.decoded_bytes())?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // The font named `name` in `resources`.
+    fn font(
+        &mut self,
+        resources: Option<&'d DictionaryObject<'a>>,
+        name: &[u8],
+    ) -> Option<Rc<Font>> {
+        let document = self.document;
+        let fonts = match document.resolved(resources?.get(b"Font")?)? {
+            Object::Dictionary(fonts) => fonts,
+            _ => return None,
+        };
+        let font = fonts.get(name)?;
+        let Object::Dictionary(dict) = document.resolved(font)? else {
+            return None;
+        };
+        let id = match font {
+            ObjectOrReference::Reference(reference) => reference.id(),
+            ObjectOrReference::Object(_) => None,
+        };
+        let Some(id) = id else {
+            return Some(Rc::new(Font::new(document, dict)));
+        };
+        let font = self
+            .fonts
+            .entry(id)
+            .or_insert_with(|| Rc::new(Font::new(document, dict)));
+        Some(font.clone())
+    }
+
+    // Runs the form XObject named `name` in `resources` (other XObjects have no text).
+    fn form(
+        &mut self,
+        resources: Option<&'d DictionaryObject<'a>>,
+        name: &[u8],
+    ) -> Result<(), TextError> {
+        let document = self.document;
+        let xobject = || {
+            let Object::Dictionary(xobjects) = document.resolved(resources?.get(b"XObject")?)?
+            else {
+                return None;
+            };
+            document.referenced_stream(xobjects.get(name)?)
+        };
+        let Some((number, generation, stream)) = xobject() else {
+            return Ok(());
+        };
+        let dict = stream.dict();
+        let is_form =
+            matches!(dict.get(b"Subtype").and_then(|s| s.as_name()), Some(s) if s.is(b"Form"));
+        // (A form that draws itself, directly or not, is drawn only once.)
+        if !is_form || self.forms.contains(&number) || self.forms.len() >= MAX_FORM_DEPTH {
+            return Ok(());
+        }
+        let data = document
+            .stream_data(number, generation, stream)
+            .map_err(TextError::Decode)?;
+        let matrix = match dict.get(b"Matrix").and_then(|m| document.resolved(m)) {
+            Some(Object::Array(m)) => m
+                .elements()
+                .map(|n| document.resolved(n)?.as_number())
+                .collect::<Option<Vec<f64>>>()
+                .and_then(|m| <Matrix>::try_from(m).ok())
+                .unwrap_or(IDENTITY),
+            _ => IDENTITY,
+        };
+        let form_resources = match dict.get(b"Resources").and_then(|r| document.resolved(r)) {
+            Some(Object::Dictionary(form_resources)) => Some(form_resources),
+            _ => resources,
+        };
+        self.saved.push(self.state.clone());
+        self.state.ctm = concat(&matrix, &self.state.ctm);
+        self.forms.push(number);
+        let result = self.run(&data, form_resources);
+        self.forms.pop();
+        if let Some(state) = self.saved.pop() {
+            self.state = state;
+        }
+        result
+    }
+
+    // Moves to the start of the next line, offset by (tx, ty) from the start of the current one.
+    fn next_line(&mut self, tx: f64, ty: f64) {
+        self.line_matrix = concat(&[1.0, 0.0, 0.0, 1.0, tx, ty], &self.line_matrix);
+        self.text_matrix = self.line_matrix;
+    }
+
+    // Shows the glyphs for the codes in string `bytes`.
+    fn show(&mut self, bytes: &[u8]) {
+        let Some(font) = self.state.font.clone() else {
+            return;
+        };
+        let state = &self.state;
+        for code in font.codes(bytes) {
+            let size = state.font_size;
+            let scaling = state.horizontal_scaling;
+            let rendering = [size * scaling, 0.0, 0.0, size, 0.0, state.rise];
+            let m = concat(&concat(&rendering, &self.text_matrix), &state.ctm);
+            let width = font.width(code);
+            let length = m[0].hypot(m[1]);
+            let corners = [
+                apply(&m, (0.0, font.descent())),
+                apply(&m, (width, font.descent())),
+                apply(&m, (0.0, font.ascent())),
+                apply(&m, (width, font.ascent())),
+            ];
+            let (xs, ys) = (corners.map(|c| c.0), corners.map(|c| c.1));
+            let min = |a: [f64; 4]| a.into_iter().fold(f64::INFINITY, f64::min);
+            let max = |a: [f64; 4]| a.into_iter().fold(f64::NEG_INFINITY, f64::max);
+            self.glyphs.push(Glyph {
+                text: font
+                    .text(code)
+                    .unwrap_or_else(|| char::REPLACEMENT_CHARACTER.to_string()),
+                font: font.clone(),
+                origin: apply(&m, (0.0, 0.0)),
+                end: apply(&m, (width, 0.0)),
+                direction: if length > 0.0 {
+                    (m[0] / length, m[1] / length)
+                } else {
+                    (1.0, 0.0)
+                },
+                size: m[2].hypot(m[3]),
+                bbox: [min(xs), min(ys), max(xs), max(ys)],
+                estimated: font.width_is_guess(code),
+            });
+            // "Word spacing is applied to every occurrence of the single-byte character code 32".
+            let word_spacing = if code == b" " {
+                state.word_spacing
+            } else {
+                0.0
+            };
+            let tx = (width * size + state.char_spacing + word_spacing) * scaling;
+            self.text_matrix = concat(&[1.0, 0.0, 0.0, 1.0, tx, 0.0], &self.text_matrix);
+        }
+    }
+}
+
+// What goes between `glyph` and the one before it: a newline if it's not on the same line, a
+// space if there's a gap between them, or nothing.
+fn separator(previous: &Glyph, glyph: &Glyph) -> Option<char> {
+    // How far this glyph is from the end of the one before: along its baseline, and across.
+    let (dx, dy) = (
+        glyph.origin.0 - previous.end.0,
+        glyph.origin.1 - previous.end.1,
+    );
+    let (ux, uy) = previous.direction;
+    let along = dx * ux + dy * uy;
+    let across = dy * ux - dx * uy;
+    let size = previous.size.max(glyph.size);
+    if across.abs() > 0.5 * size {
+        Some('\n')
+    } else if along > 0.15 * size || along < -size {
+        Some(' ')
+    } else {
+        None
+    }
+}
+
+// The text of `glyphs`, with newlines and spaces where they seem to be.
+fn layout(glyphs: &[Glyph]) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&Glyph> = None;
+    for glyph in glyphs {
+        let is_space = |c: Option<char>| c.is_some_and(char::is_whitespace);
+        match previous.and_then(|previous| separator(previous, glyph)) {
+            Some('\n') if !text.ends_with('\n') => text.push('\n'),
+            Some(' ') if !is_space(text.chars().last()) && !is_space(glyph.text.chars().next()) => {
+                text.push(' ')
+            }
+            _ => {}
+        }
+        text.push_str(&glyph.text);
+        previous = Some(glyph);
+    }
+    text
+}
+
+// The words that `glyphs` make.
+fn words(glyphs: &[Glyph]) -> Vec<PositionedText> {
+    let mut words: Vec<PositionedText> = vec![];
+    let mut in_word = false;
+    let mut previous: Option<&Glyph> = None;
+    for glyph in glyphs {
+        let is_blank = glyph.text.chars().all(char::is_whitespace);
+        let joined = previous.is_some_and(|previous| separator(previous, glyph).is_none());
+        previous = Some(glyph);
+        if glyph.text.is_empty() {
+            continue;
+        }
+        match words.last_mut() {
+            _ if is_blank => in_word = false,
+            Some(word) if in_word && joined => {
+                word.text.push_str(&glyph.text);
+                let [llx, lly, urx, ury] = glyph.bbox;
+                word.bbox = [
+                    word.bbox[0].min(llx),
+                    word.bbox[1].min(lly),
+                    word.bbox[2].max(urx),
+                    word.bbox[3].max(ury),
+                ];
+                word.estimated |= glyph.estimated;
+            }
+            _ => {
+                words.push(glyph.positioned());
+                in_word = true;
+            }
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+// A stream object, with `entries` in its dictionary, and `data`.
+pub(super) fn stream_object(entries: &str, data: &str) -> String {
+    format!(
+        "<< {} /Length {} >>\nstream\n{}\nendstream",
+        entries,
+        data.len(),
+        data
+    )
+}
+
+#[test]
+fn test_extract_text() {
+    let bytes = include_bytes!("../test_simple.pdf");
+    let doc = Document::open(bytes).unwrap();
+    let page = doc.pages().next().unwrap();
+    assert_eq!(page.extract_text().unwrap(), "Hello World");
+
+    let bytes = file_with_objects(&[
+        "<< /Type /Catalog /Pages 2 0 R >>",
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents [4 0 R 10 0 R]
+           /Resources << /Font << /F1 5 0 R /F2 6 0 R >> /XObject << /X1 9 0 R >> >> >>",
+        &stream_object(
+            "",
+            "BT /F1 10 Tf 14 TL 1 0 0 1 72 700 Tm [(Tw)120(o words)] TJ
+             (caf\\351 \\001nd caf\\002) '
+             0 -30 Td (next) Tj 30 0 Td (line) Tj
+             ET
+             q 1 0 0 1 72 600 cm /X1 Do Q",
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica
+           /Encoding << /BaseEncoding /WinAnsiEncoding /Differences [1 /f_i /eacute] >> >>",
+        "<< /Type /Font /Subtype /Type0 /BaseFont /Example /Encoding /Identity-H
+           /DescendantFonts [7 0 R] /ToUnicode 8 0 R >>",
+        "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /Example /DW 500 /W [36 [600 700]] >>",
+        &stream_object(
+            "",
+            "1 begincodespacerange <0000> <FFFF> endcodespacerange
+             1 beginbfrange <0024> <0026> <0041> endbfrange",
+        ),
+        &stream_object(
+            "/Type /XObject /Subtype /Form /BBox [0 0 100 100]",
+            "BT /F1 10 Tf (in a form) Tj ET /X1 Do",
+        ),
+        &stream_object("", "BT /F2 10 Tf 72 580 Td <00240025> Tj <0026> Tj ET"),
+    ]);
+    let doc = Document::open(&bytes).unwrap();
+    let page = doc.pages().next().unwrap();
+    assert_eq!(
+        page.extract_text().unwrap(),
+        "Two words\ncaf\u{E9} find caf\u{E9}\nnext line\nin a form\nABC"
+    );
+    let glyphs = page.glyphs().unwrap();
+    let c = glyphs.last().unwrap();
+    // After A (0.6 wide) and B (0.7), at size 10.
+    assert_eq!(
+        (c.text.as_str(), c.origin, c.size),
+        ("C", (85.0, 580.0), 10.0)
+    );
+    // (0.5 wide, per /DW; the font has no descriptor, so the usual ascent and descent.)
+    assert_eq!(c.bbox, [85.0, 577.5, 90.0, 587.5]);
+
+    // A font dictionary given directly, rather than as an object of its own.
+    let bytes = file_with_objects(&[
+        "<< /Type /Catalog /Pages 2 0 R >>",
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        "<< /Type /Page /Parent 2 0 R /Contents 4 0 R
+           /Resources << /Font << /F1 << /Type /Font /Subtype /Type1 /BaseFont /Courier >> >> >> >>",
+        &stream_object("", "BT /F1 10 Tf (direct) Tj /F1 10 Tf ( font) Tj ET"),
+    ]);
+    let doc = Document::open(&bytes).unwrap();
+    let page = doc.pages().next().unwrap();
+    assert_eq!(page.extract_text().unwrap(), "direct font");
+
+    let bytes = file_with_objects(&[
+        "<< /Type /Catalog /Pages 2 0 R >>",
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        "<< /Type /Page /Parent 2 0 R /Contents 4 0 R >>",
+        &stream_object("", "BT (unclosed Tj ET"),
+    ]);
+    let doc = Document::open(&bytes).unwrap();
+    let page = doc.pages().next().unwrap();
+    assert!(matches!(page.extract_text(), Err(TextError::Parse(_))));
+}
+
+#[test]
+fn test_positioned_words() {
+    let bytes = file_with_objects(&[
+        "<< /Type /Catalog /Pages 2 0 R >>",
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        "<< /Type /Page /Parent 2 0 R /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> >>",
+        &stream_object(
+            "",
+            "BT /F1 10 Tf 72 700 Td (Hello World) Tj ET
+             q 0 1 -1 0 300 300 cm BT /F1 10 Tf (Hi) Tj ET Q
+             BT /F1 10 Tf 72 650 Td (\\253Hi\\273) Tj ET",
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+    ]);
+    let doc = Document::open(&bytes).unwrap();
+    let page = doc.pages().next().unwrap();
+    let glyphs = page.positioned_glyphs().unwrap();
+    assert_eq!(glyphs.len(), 17);
+    assert_eq!(
+        (glyphs[5].text.as_str(), glyphs[5].font.as_str()),
+        (" ", "Helvetica")
+    );
+    let words = page.positioned_words().unwrap();
+    let rounded: Vec<(&str, f64, [f64; 4])> = words
+        .iter()
+        .map(|word| {
+            (
+                word.text.as_str(),
+                word.size,
+                word.bbox.map(|x| (x * 100.0).round() / 100.0),
+            )
+        })
+        .collect();
+    // Helvetica's widths: H 722, e 556, l 222, o 556, space 278, W 944, r 333, d 556, i 222; its
+    // ascent is 718, and descent -207.
+    assert_eq!(
+        rounded,
+        [
+            ("Hello", 10.0, [72.0, 697.93, 94.78, 707.18]),
+            ("World", 10.0, [97.56, 697.93, 123.67, 707.18]),
+            ("Hi", 10.0, [292.82, 300.0, 302.07, 309.44]),
+            ("\u{AB}Hi\u{BB}", 10.0, [72.0, 647.93, 91.44, 657.18]),
+        ]
+    );
+    // The widths of the guillemets aren't built in, so they're guessed (500).
+    assert_eq!(
+        words.iter().map(|word| word.estimated).collect::<Vec<_>>(),
+        [false, false, false, true]
+    );
+    assert_eq!(
+        page.extract_text().unwrap(),
+        "Hello World\nHi\n\u{AB}Hi\u{BB}"
+    );
+}
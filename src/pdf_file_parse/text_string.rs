@@ -0,0 +1,123 @@
+// ===========================
+// 7.9.2 String Object Types
+// ===========================
+// Strings meant for people to read (e.g. /Title and /Author in the document information dictionary)
+// are "text strings": the bytes are UTF-16BE (if they start with the byte order marker FE FF),
+// UTF-8 (if they start with EF BB BF, since PDF 2.0), or otherwise PDFDocEncoding, which is
+// Latin-1 but for a few dozen codes (Annex D.3).
+
+use super::*;
+
+// The characters for the codes where PDFDocEncoding differs from Latin-1.
+// Codes that are undefined in PDFDocEncoding are U+FFFD (the replacement character).
+fn pdf_doc_encoding_char(byte: u8) -> char {
+    match byte {
+        0x18 => '\u{02D8}', // breve
+        0x19 => '\u{02C7}', // caron
+        0x1A => '\u{02C6}', // circumflex
+        0x1B => '\u{02D9}', // dotaccent
+        0x1C => '\u{02DD}', // hungarumlaut
+        0x1D => '\u{02DB}', // ogonek
+        0x1E => '\u{02DA}', // ring
+        0x1F => '\u{02DC}', // tilde
+        0x80 => '\u{2022}', // bullet
+        0x81 => '\u{2020}', // dagger
+        0x82 => '\u{2021}', // daggerdbl
+        0x83 => '\u{2026}', // ellipsis
+        0x84 => '\u{2014}', // emdash
+        0x85 => '\u{2013}', // endash
+        0x86 => '\u{0192}', // florin
+        0x87 => '\u{2044}', // fraction
+        0x88 => '\u{2039}', // guilsinglleft
+        0x89 => '\u{203A}', // guilsinglright
+        0x8A => '\u{2212}', // minus
+        0x8B => '\u{2030}', // perthousand
+        0x8C => '\u{201E}', // quotedblbase
+        0x8D => '\u{201C}', // quotedblleft
+        0x8E => '\u{201D}', // quotedblright
+        0x8F => '\u{2018}', // quoteleft
+        0x90 => '\u{2019}', // quoteright
+        0x91 => '\u{201A}', // quotesinglbase
+        0x92 => '\u{2122}', // trademark
+        0x93 => '\u{FB01}', // fi
+        0x94 => '\u{FB02}', // fl
+        0x95 => '\u{0141}', // Lslash
+        0x96 => '\u{0152}', // OE
+        0x97 => '\u{0160}', // Scaron
+        0x98 => '\u{0178}', // Ydieresis
+        0x99 => '\u{017D}', // Zcaron
+        0x9A => '\u{0131}', // dotlessi
+        0x9B => '\u{0142}', // lslash
+        0x9C => '\u{0153}', // oe
+        0x9D => '\u{0161}', // scaron
+        0x9E => '\u{017E}', // zcaron
+        0xA0 => '\u{20AC}', // Euro
+        0x7F | 0x9F | 0xAD => char::REPLACEMENT_CHARACTER,
+        _ => char::from(byte),
+    }
+}
+
+/// Decodes the bytes of a text string: UTF-16BE or UTF-8 if they start with the corresponding
+/// byte order marker, else PDFDocEncoding. Anything invalid becomes U+FFFD.
+pub fn decode_text_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(b"\xFE\xFF") {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        without_language_codes(&String::from_utf16_lossy(&units))
+    } else if let Some(utf8) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        without_language_codes(&String::from_utf8_lossy(utf8))
+    } else {
+        bytes.iter().map(|&b| pdf_doc_encoding_char(b)).collect()
+    }
+}
+
+// In a Unicode text string, an escape sequence (7.9.2.2.1) -- U+001B, a language code, U+001B --
+// can say what language the text that follows is in. It is not part of the text.
+fn without_language_codes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_escape = false;
+    for c in text.chars() {
+        if c == '\u{1B}' {
+            in_escape = !in_escape;
+        } else if !in_escape {
+            out.push(c);
+        }
+    }
+    out
+}
+
+impl StringObject<'_> {
+    /// The string, decoded as a text string (see `decode_text_string`).
+    /// (For a string in an encrypted file, use `Document::text_string` instead.)
+    pub fn to_text(&self) -> String {
+        decode_text_string(&self.decoded_bytes())
+    }
+}
+
+#[test]
+fn test_decode_text_string() {
+    assert_eq!(decode_text_string(b"Hello"), "Hello");
+    // PDFDocEncoding: Latin-1, except for codes like 0x80 (bullet) and 0xA0 (Euro).
+    assert_eq!(decode_text_string(b"caf\xE9 \x80 \xA0 \x93"), "café • € ﬁ");
+    assert_eq!(
+        decode_text_string(b"\xFE\xFF\x00H\x00i\x00 \xD8\x3D\xDE\x00"),
+        "Hi 😀"
+    );
+    assert_eq!(decode_text_string(b"\xEF\xBB\xBFna\xC3\xAFve"), "naïve");
+    // An escape sequence with a language code (here "en").
+    assert_eq!(
+        decode_text_string(b"\xFE\xFF\x00\x1B\x00e\x00n\x00\x1B\x00O\x00K"),
+        "OK"
+    );
+}
+
+#[test]
+fn test_string_to_text() {
+    let text = |input: &[u8]| object_string(input).unwrap().1.to_text();
+    assert_eq!(text(b"(PDF Explorer)"), "PDF Explorer");
+    assert_eq!(text(br"(\376\377\000A\000B)"), "AB");
+    assert_eq!(text(b"<FEFF00410042>"), "AB");
+    assert_eq!(text(br"(\222 \(c\))"), "™ (c)");
+}
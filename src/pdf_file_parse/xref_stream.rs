@@ -0,0 +1,328 @@
+// =============================
+// 7.5.8 Cross-Reference Streams
+// =============================
+// Since PDF 1.5, a section can have a cross-reference *stream* instead of the "xref" table and
+// "trailer": an indirect object like
+//     8 0 obj << /Type /XRef /Size 9 /W [1 2 1] ... >> stream ... endstream endobj
+// whose dictionary doubles as the trailer dictionary, and whose data is a sequence of
+// fixed-width binary rows, one per object. /W gives the width of each of the three fields in a row,
+// and /Index (default [0 Size]) the object numbers that the rows are about.
+
+use super::document::XrefEntry;
+use super::*;
+
+/// The decoded contents of a cross-reference stream.
+#[derive(Serialize, Debug)]
+pub struct CrossReferenceStream {
+    object_number: u32, // The object whose stream this is
+    size: u32,          // From /Size: one more than the highest object number
+    w: [usize; 3],      // From /W: the width in bytes of each field
+    subsections: Vec<CrossReferenceStreamSubsection>, // One for each pair in /Index
+}
+
+#[derive(Serialize, Debug)]
+pub struct CrossReferenceStreamSubsection {
+    first_object_number: u32,
+    entries: Vec<XrefEntry>,
+}
+
+// Whether `def` is a cross-reference stream, i.e. a stream with /Type /XRef.
+pub(super) fn is_cross_reference_stream(def: &IndirectObjectDefinition) -> bool {
+    match &def.object {
+        Object::Stream(s) => s
+            .dict
+            .get(b"Type")
+            .and_then(|t| t.as_name())
+            .is_some_and(|t| t.is(b"XRef")),
+        _ => false,
+    }
+}
+
+// A big-endian number of however many bytes.
+fn field(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |n, &b| (n << 8) | u64::from(b))
+}
+
+// The entry in `row`, or what was expected if a field is too large for what it is.
+fn entry_from_row(row: &[u8], w: [usize; 3]) -> Result<XrefEntry, String> {
+    let (f1, rest) = row.split_at(w[0]);
+    let (f2, f3) = rest.split_at(w[1]);
+    // "If the first element [of /W] is zero, the type field shall not be present, and shall default to type 1."
+    let entry_type = if w[0] == 0 { 1 } else { field(f1) };
+    let (f2, f3) = (field(f2), field(f3));
+    let too_large = |what: &str, value: u64| format!("{} (got {})", what, value);
+    let generation =
+        || u16::try_from(f3).map_err(|_| too_large("a generation number of at most 16 bits", f3));
+    Ok(match entry_type {
+        0 => XrefEntry::Free {
+            next_free_object_number: f2,
+            generation: generation()?,
+        },
+        1 => XrefEntry::InUse {
+            offset: usize::try_from(f2).map_err(|_| too_large("an offset that fits", f2))?,
+            generation: generation()?,
+        },
+        2 => XrefEntry::Compressed {
+            object_stream_number: u32::try_from(f2)
+                .map_err(|_| too_large("an object stream number of at most 32 bits", f2))?,
+            index: u32::try_from(f3)
+                .map_err(|_| too_large("an index in an object stream of at most 32 bits", f3))?,
+        },
+        // "Any other value shall be interpreted as a reference to the null object, thus permitting new entry types to be defined in the future."
+        _ => XrefEntry::Free {
+            next_free_object_number: 0,
+            generation: 0,
+        },
+    })
+}
+
+impl CrossReferenceStream {
+    // Decodes the cross-reference stream defined by `def`. On failure, says what was expected.
+    pub(super) fn from_definition(
+        def: &IndirectObjectDefinition,
+    ) -> Result<CrossReferenceStream, String> {
+        let stream = match &def.object {
+            Object::Stream(s) => s,
+            _ => return Err("a stream object".to_string()),
+        };
+        let dict = &stream.dict;
+        let object_number = def
+            .object_number
+            .value()
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or("a valid object number")?;
+        let to_u32 = |o: &ObjectOrReference| o.as_integer().and_then(|i| u32::try_from(i).ok());
+        let size = dict
+            .get(b"Size")
+            .and_then(to_u32)
+            .ok_or("a /Size integer")?;
+        let w: [usize; 3] = dict
+            .get(b"W")
+            .and_then(|w| w.as_array())
+            .and_then(|w| w.elements().map(to_u32).collect::<Option<Vec<u32>>>())
+            .filter(|w| w.iter().all(|&width| width <= 8))
+            .and_then(|w| <[u32; 3]>::try_from(w).ok())
+            .map(|w| w.map(|width| width as usize))
+            .ok_or("a /W array of three integers, each at most 8")?;
+        let index: Vec<u32> = match dict.get(b"Index") {
+            None => vec![0, size],
+            Some(index) => index
+                .as_array()
+                .and_then(|a| a.elements().map(to_u32).collect::<Option<Vec<u32>>>())
+                .filter(|index| index.len() % 2 == 0)
+                .ok_or("an /Index array of pairs of integers")?,
+        };
+        let row_len: usize = w.iter().sum();
+        if row_len == 0 {
+            return Err("a /W array with a nonzero total width".to_string());
+        }
+
+        let data = stream
+            .decoded()
+            .map_err(|e| format!("decodable stream data ({})", e))?;
+        let mut rows = data.chunks_exact(row_len);
+        let mut subsections = vec![];
+        for pair in index.chunks(2) {
+            let (first_object_number, count) = (pair[0], pair[1]);
+            let entries = (0..count)
+                .map(|_| rows.next())
+                .collect::<Option<Vec<&[u8]>>>()
+                .ok_or_else(|| {
+                    format!(
+                        "{} bytes of stream data for the entries in /Index (got {})",
+                        index.chunks(2).map(|p| p[1] as usize).sum::<usize>() * row_len,
+                        data.len()
+                    )
+                })?
+                .into_iter()
+                .map(|row| entry_from_row(row, w))
+                .collect::<Result<Vec<XrefEntry>, String>>()?;
+            subsections.push(CrossReferenceStreamSubsection {
+                first_object_number,
+                entries,
+            });
+        }
+        Ok(CrossReferenceStream {
+            object_number,
+            size,
+            w,
+            subsections,
+        })
+    }
+
+    // All the entries, with their object numbers.
+    pub(super) fn entries(&self) -> impl Iterator<Item = (u32, XrefEntry)> + '_ {
+        self.subsections.iter().flat_map(|subsection| {
+            // (Entries past the largest object number are left out.)
+            (subsection.first_object_number..=u32::MAX).zip(subsection.entries.iter().copied())
+        })
+    }
+}
+
+#[test]
+fn test_cross_reference_stream() {
+    let bytes = include_bytes!("../test_xref_stream.pdf");
+    let (_, def) = indirect_object_definition(&bytes[413..]).unwrap();
+    assert!(is_cross_reference_stream(&def));
+    let xref = CrossReferenceStream::from_definition(&def).unwrap();
+    assert_eq!(xref.object_number, 8);
+    assert_eq!(xref.w, [1, 2, 1]);
+    let entries: Vec<(u32, XrefEntry)> = xref.entries().collect();
+    assert_eq!(entries.len(), 9);
+    assert_eq!(
+        entries[0],
+        (
+            0,
+            XrefEntry::Free {
+                next_free_object_number: 0,
+                generation: 255
+            }
+        )
+    );
+    assert_eq!(
+        entries[4],
+        (
+            4,
+            XrefEntry::InUse {
+                offset: 15,
+                generation: 0
+            }
+        )
+    );
+    assert_eq!(
+        entries[6],
+        (
+            6,
+            XrefEntry::Compressed {
+                object_stream_number: 7,
+                index: 4
+            }
+        )
+    );
+}
+
+#[test]
+fn test_cross_reference_stream_with_index() {
+    // Two subsections, with no type field (so every entry has type 1), uncompressed.
+    let input = b"9 0 obj\n<< /Type /XRef /Size 20 /Index [3 1 17 2] /W [0 2 0] /Length 6 >>\nstream\n\x00\x10\x00\x20\x01\x00endstream\nendobj";
+    let (_, def) = indirect_object_definition(input).unwrap();
+    let xref = CrossReferenceStream::from_definition(&def).unwrap();
+    let entries: Vec<(u32, XrefEntry)> = xref.entries().collect();
+    assert_eq!(
+        entries,
+        vec![
+            (
+                3,
+                XrefEntry::InUse {
+                    offset: 16,
+                    generation: 0
+                }
+            ),
+            (
+                17,
+                XrefEntry::InUse {
+                    offset: 32,
+                    generation: 0
+                }
+            ),
+            (
+                18,
+                XrefEntry::InUse {
+                    offset: 256,
+                    generation: 0
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_cross_reference_stream_out_of_range() {
+    // Object numbers past the largest one are left out.
+    let input = b"9 0 obj\n<< /Type /XRef /Size 20 /Index [4294967295 2] /W [0 2 0] /Length 4 >>\nstream\n\x00\x10\x00\x20endstream\nendobj";
+    let (_, def) = indirect_object_definition(input).unwrap();
+    let xref = CrossReferenceStream::from_definition(&def).unwrap();
+    assert_eq!(
+        xref.entries().map(|(n, _)| n).collect::<Vec<_>>(),
+        vec![u32::MAX]
+    );
+    // A field too wide for what it is.
+    let input = b"9 0 obj\n<< /Type /XRef /Size 1 /W [1 5 1] /Length 7 >>\nstream\n\x02\x01\x00\x00\x00\x00\x00endstream\nendobj";
+    let (_, def) = indirect_object_definition(input).unwrap();
+    assert_eq!(
+        CrossReferenceStream::from_definition(&def).err(),
+        Some("an object stream number of at most 32 bits (got 4294967296)".to_string())
+    );
+    // A generation number too wide for a free or in-use entry (but not for a compressed one's index).
+    for (entry_type, expected) in [
+        (
+            0,
+            Some("a generation number of at most 16 bits (got 65536)".to_string()),
+        ),
+        (
+            1,
+            Some("a generation number of at most 16 bits (got 65536)".to_string()),
+        ),
+        (2, None),
+    ] {
+        let mut input =
+            b"9 0 obj\n<< /Type /XRef /Size 1 /W [1 1 3] /Length 5 >>\nstream\n".to_vec();
+        input.extend_from_slice(&[entry_type, 7, 1, 0, 0]);
+        input.extend_from_slice(b"endstream\nendobj");
+        let (_, def) = indirect_object_definition(&input).unwrap();
+        assert_eq!(CrossReferenceStream::from_definition(&def).err(), expected);
+    }
+}
+
+#[test]
+fn test_cross_reference_stream_in_json() {
+    let bytes = include_bytes!("../test_xref_stream.pdf");
+    let parsed = parse_pdf(bytes).unwrap();
+    let bct = &parsed.body_crossref_trailers[0];
+    assert!(bct.cross_reference_table_and_trailer.is_none());
+    assert!(matches!(bct.cross_reference_stream, Some(Ok(_))));
+    let json = serde_json::to_string(&parsed).unwrap();
+    assert!(json.contains(r#"{"Compressed":{"object_stream_number":7,"index":0}}"#));
+    // The decoded entries are not needed (nor used) to get the bytes back.
+    let deserialized: PdfFile = serde_json::from_str(&json).unwrap();
+    let mut out: Vec<u8> = vec![];
+    deserialized.serialize_to(&mut out).unwrap();
+    assert_eq!(out, bytes);
+}
+
+#[test]
+fn test_cross_reference_stream_with_predictor() {
+    // Two rows, PNG "Up"-encoded: the second is stored as the difference from the first.
+    let rows = [2, 0, 0, 0, 255, 2, 1, 0, 15, 1];
+    let mut encoder = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(&rows).unwrap();
+    let data = encoder.finish().unwrap();
+    let mut input = format!(
+        "9 0 obj\n<< /Type /XRef /Size 2 /W [1 2 1] /Filter /FlateDecode /DecodeParms << /Predictor 12 /Columns 4 >> /Length {} >>\nstream\n",
+        data.len()
+    )
+    .into_bytes();
+    input.extend_from_slice(&data);
+    input.extend_from_slice(b"endstream\nendobj");
+    let (_, def) = indirect_object_definition(&input).unwrap();
+    let xref = CrossReferenceStream::from_definition(&def).unwrap();
+    assert_eq!(
+        xref.entries().collect::<Vec<_>>(),
+        vec![
+            (
+                0,
+                XrefEntry::Free {
+                    next_free_object_number: 0,
+                    generation: 255
+                }
+            ),
+            (
+                1,
+                XrefEntry::InUse {
+                    offset: 15,
+                    generation: 0
+                }
+            ),
+        ]
+    );
+}
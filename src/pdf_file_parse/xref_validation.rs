@@ -0,0 +1,400 @@
+// ===========================================
+// 7.5.4 Cross-Reference Table: checking it
+// ===========================================
+// Nothing in the syntax makes the offsets in a cross-reference table (or stream) right: a reader
+// that trusts them (like `Document`) just finds the wrong bytes. Parsing the file forwards tells us
+// where each object really is, so we can compare. When a viewer says a file is "damaged", it's
+// usually because of one of these.
+
+use super::xref_stream::is_cross_reference_stream;
+use super::*;
+
+/// A way in which the cross-reference sections disagree with the file.
+/// Sections are numbered from 0, in the order they are in the file.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum XrefProblem {
+    /// The `startxref` at the end of `section` says `offset`, but the section's cross-reference
+    /// table (or stream) is at `actual` (or it has none).
+    StartxrefOffset {
+        section: usize,
+        offset: Option<u64>,
+        actual: Option<usize>,
+    },
+    /// An in-use entry says the object is at `offset`, but it is defined at `actual`.
+    WrongOffset {
+        section: usize,
+        number: u32,
+        generation: u16,
+        offset: usize,
+        actual: usize,
+    },
+    /// An in-use entry for an object that is not defined anywhere in the file.
+    MissingObject {
+        section: usize,
+        number: u32,
+        generation: u16,
+        offset: usize,
+    },
+    /// An object defined (at `offset`, in the body of `section`) that no section has an in-use entry for.
+    NotInXref {
+        section: usize,
+        number: u32,
+        generation: u16,
+        offset: usize,
+    },
+    /// The list of free entries (in the combined table, with newer sections taking precedence) is
+    /// broken at object `number`.
+    FreeList { number: u32, problem: String },
+}
+
+impl std::fmt::Display for XrefProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XrefProblem::StartxrefOffset {
+                section,
+                offset,
+                actual,
+            } => {
+                match offset {
+                    Some(offset) => write!(f, "section {}: startxref is {}", section, offset)?,
+                    None => write!(f, "section {}: startxref is out of range", section)?,
+                }
+                match actual {
+                    Some(actual) => write!(f, ", but the cross-reference section is at {}", actual),
+                    None => write!(f, ", but there is no cross-reference table or stream"),
+                }
+            }
+            XrefProblem::WrongOffset {
+                section,
+                number,
+                generation,
+                offset,
+                actual,
+            } => write!(
+                f,
+                "section {}: object {} {} is at {}, not {}",
+                section, number, generation, actual, offset
+            ),
+            XrefProblem::MissingObject {
+                section,
+                number,
+                generation,
+                offset,
+            } => write!(
+                f,
+                "section {}: object {} {} (at {}) is not defined anywhere",
+                section, number, generation, offset
+            ),
+            XrefProblem::NotInXref {
+                section,
+                number,
+                generation,
+                offset,
+            } => write!(
+                f,
+                "section {}: object {} {} (at {}) is not in any cross-reference section",
+                section, number, generation, offset
+            ),
+            XrefProblem::FreeList { number, problem } => {
+                write!(f, "free list, at object {}: {}", number, problem)
+            }
+        }
+    }
+}
+
+impl IndirectObjectDefinition<'_> {
+    /// The object number and generation number, if they are in range.
+    pub fn id(&self) -> Option<(u32, u16)> {
+        let number = self
+            .object_number
+            .value()
+            .and_then(|n| u32::try_from(n).ok())?;
+        let generation = self
+            .generation_number
+            .value()
+            .and_then(|g| u16::try_from(g).ok())?;
+        Some((number, generation))
+    }
+}
+
+impl BodyCrossrefTrailer<'_> {
+    /// The object definitions in the body.
+    pub fn definitions(&self) -> impl Iterator<Item = &IndirectObjectDefinition<'_>> {
+        self.body.iter().filter_map(|part| match part {
+            BodyPart::ObjDef(def) => Some(def),
+            BodyPart::Whitespace(_) => None,
+        })
+    }
+
+    // The entries of the section's cross-reference table, and of any cross-reference streams in
+    // the body (which, in a "hybrid-reference" file, is in addition to the table).
+    fn xref_entries(&self) -> Vec<(u32, XrefEntry)> {
+        let mut entries = match &self.cross_reference_table_and_trailer {
+            Some(t) => t.cross_reference_table.entries().unwrap_or_default(),
+            None => vec![],
+        };
+        for def in self
+            .definitions()
+            .filter(|def| is_cross_reference_stream(def))
+        {
+            if let Ok(stream) = CrossReferenceStream::from_definition(def) {
+                entries.extend(stream.entries());
+            }
+        }
+        entries
+    }
+
+    // Where the cross-reference table (or else the last cross-reference stream) starts.
+    fn xref_offset(&self) -> Option<usize> {
+        match &self.cross_reference_table_and_trailer {
+            Some(t) => Some(t.span.start),
+            None => self
+                .definitions()
+                .filter(|def| is_cross_reference_stream(def))
+                .last()
+                .map(|def| def.span.start),
+        }
+    }
+}
+
+impl PdfFile<'_> {
+    /// Compares the cross-reference sections (and `startxref` offsets) with where the objects
+    /// actually are, and checks the list of free entries.
+    /// (Offsets are from the start of the file, which is not quite right if there's junk before `%PDF`.)
+    pub fn validate_xref(&self) -> Vec<XrefProblem> {
+        let mut problems = vec![];
+        let sections: Vec<Vec<(u32, XrefEntry)>> = self
+            .body_crossref_trailers
+            .iter()
+            .map(|section| section.xref_entries())
+            .collect();
+
+        // (object number, generation number) -> the sections and offsets where it is defined.
+        let mut defined: BTreeMap<(u32, u16), Vec<(usize, usize)>> = BTreeMap::new();
+        for (i, section) in self.body_crossref_trailers.iter().enumerate() {
+            for def in section.definitions() {
+                if let Some(id) = def.id() {
+                    defined.entry(id).or_default().push((i, def.span.start));
+                }
+            }
+        }
+
+        for (i, section) in self.body_crossref_trailers.iter().enumerate() {
+            let offset = section
+                .startxref_offset_eof
+                .last_crossref_offset
+                .value()
+                .and_then(|o| u64::try_from(o).ok());
+            let actual = section.xref_offset();
+            if actual.is_none() || offset != actual.map(|a| a as u64) {
+                problems.push(XrefProblem::StartxrefOffset {
+                    section: i,
+                    offset,
+                    actual,
+                });
+            }
+
+            for &(number, entry) in &sections[i] {
+                let XrefEntry::InUse { offset, generation } = entry else {
+                    continue;
+                };
+                match defined.get(&(number, generation)) {
+                    None => problems.push(XrefProblem::MissingObject {
+                        section: i,
+                        number,
+                        generation,
+                        offset,
+                    }),
+                    Some(places) if places.iter().all(|&(_, o)| o != offset) => {
+                        // The definition this section would mean: the last one up to this section.
+                        let (_, actual) = places
+                            .iter()
+                            .rev()
+                            .find(|&&(s, _)| s <= i)
+                            .unwrap_or(&places[0]);
+                        problems.push(XrefProblem::WrongOffset {
+                            section: i,
+                            number,
+                            generation,
+                            offset,
+                            actual: *actual,
+                        });
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        for (&(number, generation), places) in &defined {
+            let in_xref = sections.iter().flatten().any(|&(n, entry)| {
+                n == number
+                    && matches!(entry, XrefEntry::InUse { generation: g, .. } if g == generation)
+            });
+            if !in_xref {
+                for &(section, offset) in places {
+                    problems.push(XrefProblem::NotInXref {
+                        section,
+                        number,
+                        generation,
+                        offset,
+                    });
+                }
+            }
+        }
+
+        let mut combined: BTreeMap<u32, XrefEntry> = BTreeMap::new();
+        for entries in &sections {
+            combined.extend(entries.iter().copied());
+        }
+        problems.extend(free_list_problems(&combined));
+        problems
+    }
+}
+
+// "The cross-reference table contains a list of all free objects, with object 0 at the head, and
+// the last free entry [linking] back to object number 0."
+fn free_list_problems(xref: &BTreeMap<u32, XrefEntry>) -> Vec<XrefProblem> {
+    let problem = |number: u32, problem: String| XrefProblem::FreeList { number, problem };
+    let mut problems = vec![];
+    let next_free = |number: u32| match xref.get(&number) {
+        Some(XrefEntry::Free {
+            next_free_object_number,
+            ..
+        }) => Some(*next_free_object_number),
+        _ => None,
+    };
+    if next_free(0).is_none() {
+        problems.push(problem(0, "object 0 is not free".to_string()));
+        return problems;
+    }
+
+    let mut on_list = vec![0];
+    let mut current = 0;
+    while let Some(next) = next_free(current) {
+        if next == 0 {
+            break;
+        }
+        let next_number = u32::try_from(next).ok().filter(|&n| next_free(n).is_some());
+        match next_number {
+            None => {
+                problems.push(problem(
+                    current,
+                    format!("links to object {}, which is not free", next),
+                ));
+                break;
+            }
+            Some(n) if on_list.contains(&n) => {
+                problems.push(problem(
+                    current,
+                    format!("links to object {}, which is already on the list", n),
+                ));
+                break;
+            }
+            Some(n) => {
+                on_list.push(n);
+                current = n;
+            }
+        }
+    }
+    for (&number, entry) in xref {
+        if matches!(entry, XrefEntry::Free { .. }) && !on_list.contains(&number) {
+            problems.push(problem(number, "free, but not on the list".to_string()));
+        }
+    }
+    problems
+}
+
+#[test]
+fn test_validate_xref() {
+    let bytes = include_bytes!("../test_simple.pdf");
+    assert_eq!(parse_pdf(bytes).unwrap().validate_xref(), vec![]);
+    let bytes = include_bytes!("../test_xref_stream.pdf");
+    assert_eq!(parse_pdf(bytes).unwrap().validate_xref(), vec![]);
+}
+
+#[test]
+fn test_validate_xref_problems() {
+    let bytes = include_bytes!("../test_simple.pdf");
+    // Object 3's offset is off by one; object 5's entry has the wrong generation number;
+    // object 6 is marked free (but isn't on the free list); and startxref is off by one.
+    let bytes = replace_first(bytes, b"0000000121 00000 n", b"0000000122 00000 n");
+    let bytes = replace_first(&bytes, b"0000000339 00000 n", b"0000000339 00001 n");
+    let bytes = replace_first(&bytes, b"0000000409 00000 n", b"0000000000 00000 f");
+    let bytes = replace_first(&bytes, b"startxref\n468", b"startxref\n467");
+    let problems = parse_pdf(&bytes).unwrap().validate_xref();
+    assert_eq!(
+        problems,
+        vec![
+            XrefProblem::StartxrefOffset {
+                section: 0,
+                offset: Some(467),
+                actual: Some(468),
+            },
+            XrefProblem::WrongOffset {
+                section: 0,
+                number: 3,
+                generation: 0,
+                offset: 122,
+                actual: 121,
+            },
+            XrefProblem::MissingObject {
+                section: 0,
+                number: 5,
+                generation: 1,
+                offset: 339,
+            },
+            XrefProblem::NotInXref {
+                section: 0,
+                number: 5,
+                generation: 0,
+                offset: 339,
+            },
+            XrefProblem::NotInXref {
+                section: 0,
+                number: 6,
+                generation: 0,
+                offset: 409,
+            },
+            XrefProblem::FreeList {
+                number: 6,
+                problem: "free, but not on the list".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        problems[1].to_string(),
+        "section 0: object 3 0 is at 121, not 122"
+    );
+}
+
+#[test]
+fn test_validate_free_list() {
+    let free = |next: u64, generation: u16| XrefEntry::Free {
+        next_free_object_number: next,
+        generation,
+    };
+    let in_use = XrefEntry::InUse {
+        offset: 0,
+        generation: 0,
+    };
+    let problems = |entries: &[XrefEntry]| {
+        let xref = (0..).zip(entries.iter().copied()).collect();
+        free_list_problems(&xref)
+            .into_iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert!(problems(&[free(2, 65535), in_use, free(0, 1)]).is_empty());
+    assert_eq!(
+        problems(&[free(1, 65535), in_use]),
+        ["free list, at object 0: links to object 1, which is not free"]
+    );
+    assert_eq!(
+        problems(&[free(1, 65535), free(2, 0), free(1, 0)]),
+        ["free list, at object 2: links to object 1, which is already on the list"]
+    );
+    assert_eq!(
+        problems(&[in_use]),
+        ["free list, at object 0: object 0 is not free"]
+    );
+}